@@ -0,0 +1,183 @@
+//! Diagnostics for fallible mesh builds.
+//!
+//! [`LSystemMeshBuilder::build`](crate::LSystemMeshBuilder::build) quietly
+//! drops zero-length segments and meshes whatever it is given, NaNs included.
+//! [`LSystemMeshBuilder::try_build`](crate::LSystemMeshBuilder::try_build)
+//! produces the same meshes plus a [`BuildReport`] describing what was built,
+//! what was wrong with the input, and which builder options were clamped, and
+//! fails with a [`BuildError`] as soon as the output outgrows a vertex or
+//! triangle budget.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use bevy_symbios::LSystemMeshBuilder;
+//!
+//! match LSystemMeshBuilder::new()
+//!     .with_vertex_budget(200_000)
+//!     .try_build(&skeleton)
+//! {
+//!     Ok((meshes, report)) => {
+//!         for issue in &report.non_finite_inputs {
+//!             warn!("strand {} point {}: bad {:?}", issue.strand, issue.point, issue.field);
+//!         }
+//!     }
+//!     Err(err) => error!("L-system too large: {err}"),
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use symbios_turtle_3d::Skeleton;
+
+/// Vertex and triangle counts of one material's mesh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MaterialStats {
+    pub vertices: usize,
+    pub triangles: usize,
+}
+
+/// The field of a [`NonFiniteInput`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputField {
+    Position,
+    Radius,
+}
+
+/// A skeleton point with a NaN or infinite value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonFiniteInput {
+    pub strand: usize,
+    /// Index of the point within its strand.
+    pub point: usize,
+    pub field: InputField,
+}
+
+/// A builder option that was clamped into its valid range, e.g. a resolution
+/// of 500 reduced to 128.
+///
+/// Only clamps are recorded. Values that are replaced outright (a degenerate
+/// custom cross-section falling back to a circle, say) only log a warning.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClampedOption {
+    /// Builder method the value was passed to, e.g. `"with_resolution"`.
+    pub method: &'static str,
+    /// Parameter within that method, e.g. `"resolution"` or `"sides"`.
+    pub parameter: &'static str,
+    /// Material ID for per-material methods such as `with_cards`.
+    pub material_id: Option<u16>,
+    pub requested: f64,
+    pub effective: f64,
+}
+
+/// What [`LSystemMeshBuilder::try_build`](crate::LSystemMeshBuilder::try_build)
+/// produced and what it had to work around.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BuildReport {
+    /// Counts for every material mesh in the output.
+    pub materials: BTreeMap<u16, MaterialStats>,
+    /// Points skipped because they repeat the previous point of their strand
+    /// (zero-length segments) or have a non-finite position.
+    pub dropped_points: usize,
+    /// Every non-finite position or radius in the skeleton, in strand order.
+    pub non_finite_inputs: Vec<NonFiniteInput>,
+    /// Output vertices whose position came out non-finite anyway, e.g. from
+    /// an infinite radius.
+    pub non_finite_vertices: usize,
+    /// Builder options that were clamped when set, in the order they were
+    /// set. Setting an option again replaces its entry.
+    pub clamped_options: Vec<ClampedOption>,
+}
+
+impl BuildReport {
+    /// Vertices across all materials.
+    pub fn vertex_count(&self) -> usize {
+        self.materials.values().map(|s| s.vertices).sum()
+    }
+
+    /// Triangles across all materials.
+    pub fn triangle_count(&self) -> usize {
+        self.materials.values().map(|s| s.triangles).sum()
+    }
+
+    /// Whether the skeleton was meshed without dropping or repairing anything.
+    /// Clamped builder options are about the configuration, not the skeleton,
+    /// and do not count.
+    pub fn is_clean(&self) -> bool {
+        self.dropped_points == 0
+            && self.non_finite_inputs.is_empty()
+            && self.non_finite_vertices == 0
+    }
+
+    /// Scans `skeleton` for non-finite positions and radii.
+    pub(crate) fn scan_inputs(&mut self, skeleton: &Skeleton) {
+        for (strand_idx, strand) in skeleton.strands.iter().enumerate() {
+            for (point_idx, point) in strand.iter().enumerate() {
+                let issue = |field| NonFiniteInput {
+                    strand: strand_idx,
+                    point: point_idx,
+                    field,
+                };
+                if !point.position.is_finite() {
+                    self.non_finite_inputs.push(issue(InputField::Position));
+                }
+                if !point.radius.is_finite() {
+                    self.non_finite_inputs.push(issue(InputField::Radius));
+                }
+            }
+        }
+    }
+}
+
+/// Why [`LSystemMeshBuilder::try_build`](crate::LSystemMeshBuilder::try_build)
+/// gave up. Budgets are checked after each strand, where `strand` is the one
+/// that pushed the total over, and again on the finished meshes, where it is
+/// `None`: finishing can add vertices, e.g. seams for lightmap UVs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildError {
+    VertexBudgetExceeded {
+        budget: usize,
+        vertices: usize,
+        strand: Option<usize>,
+    },
+    TriangleBudgetExceeded {
+        budget: usize,
+        triangles: usize,
+        strand: Option<usize>,
+    },
+}
+
+/// Where a budget was exceeded, for [`BuildError`] messages.
+fn budget_stage(strand: &Option<usize>) -> String {
+    match strand {
+        Some(strand) => format!("after strand {strand}"),
+        None => "in the finished meshes".to_string(),
+    }
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::VertexBudgetExceeded {
+                budget,
+                vertices,
+                strand,
+            } => write!(
+                f,
+                "Vertex budget of {budget} exceeded ({vertices} vertices {})",
+                budget_stage(strand)
+            ),
+            Self::TriangleBudgetExceeded {
+                budget,
+                triangles,
+                strand,
+            } => write!(
+                f,
+                "Triangle budget of {budget} exceeded ({triangles} triangles {})",
+                budget_stage(strand)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
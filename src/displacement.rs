@@ -0,0 +1,383 @@
+//! Surface relief for L-System tubes.
+//!
+//! A [`Displacement`] pushes every tube ring vertex in or out from its
+//! strand's axis by a height in `0..=1`, read either from deterministic 3D
+//! value noise or from a [`Heightfield`] sampled at the vertex UV. Heights of
+//! 0.5 leave the surface in place, so the average radius is unchanged. After
+//! displacement the tube normals are recomputed from the displaced triangles.
+//!
+//! Enable it for every material with
+//! [`LSystemMeshBuilder::with_displacement`](crate::LSystemMeshBuilder::with_displacement),
+//! or for bark only with
+//! [`LSystemMeshBuilder::with_material_displacement`](crate::LSystemMeshBuilder::with_material_displacement).
+//!
+//! # Example
+//!
+//! ```ignore
+//! use std::sync::Arc;
+//! use bevy::prelude::*;
+//! use bevy_symbios::LSystemMeshBuilder;
+//! use bevy_symbios::displacement::{Displacement, Heightfield};
+//! use bevy_symbios::materials::ProceduralTextureConfig;
+//!
+//! // Relief that lines up with the bark texture applied to material 0.
+//! let bark = ProceduralTextureConfig::Bark(Default::default());
+//! let field = Heightfield::from_texture_config(&bark, 128).unwrap();
+//! let meshes = LSystemMeshBuilder::new()
+//!     .with_material_displacement(
+//!         0,
+//!         Displacement::Heightfield {
+//!             amplitude: 0.15,
+//!             field: Arc::new(field),
+//!             tiling: Vec2::ONE,
+//!         },
+//!     )
+//!     .build(&skeleton);
+//! ```
+//!
+//! Displacement only adds relief to the rings the tube already has; raise the
+//! resolution (and smoothing subdivisions) for finer detail.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy_symbios_texture::TextureConfig;
+use bevy_symbios_texture::generator::TextureMap;
+
+/// Largest accepted [`Displacement`] amplitude. At 1.0 the deepest valleys
+/// reach half the radius, so rings never turn inside out.
+const MAX_AMPLITUDE: f32 = 1.0;
+
+/// How tube ring vertices are offset before normals are recomputed.
+///
+/// `amplitude` is the peak-to-peak relief as a fraction of the local radius,
+/// so twigs and trunks get proportionally deep bark. Negative amplitudes
+/// invert the relief.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Displacement {
+    /// Leave the tube surface as swept.
+    #[default]
+    None,
+    /// Smooth 3D value noise sampled at each vertex's undisplaced mesh-space
+    /// position. `frequency` is in cells per world unit; `seed` picks the
+    /// pattern.
+    Noise {
+        amplitude: f32,
+        frequency: f32,
+        seed: u32,
+    },
+    /// A heightfield sampled at each vertex's UV times `tiling`, wrapping at
+    /// its edges. `tiling.x` is rounded to a whole number so the field stays
+    /// seamless around the ring. Use the same tiling as the material's UV
+    /// transform to line relief up with its texture.
+    Heightfield {
+        amplitude: f32,
+        field: Arc<Heightfield>,
+        tiling: Vec2,
+    },
+}
+
+impl Displacement {
+    /// Clamps parameters into a usable range. Non-finite amplitudes or
+    /// frequencies disable displacement with a warning.
+    pub(crate) fn sanitized(self) -> Self {
+        let amplitude = |amplitude: f32| {
+            if amplitude.abs() > MAX_AMPLITUDE {
+                warn!(
+                    "Displacement amplitude {} exceeds maximum of {}; clamping",
+                    amplitude, MAX_AMPLITUDE
+                );
+            }
+            amplitude.clamp(-MAX_AMPLITUDE, MAX_AMPLITUDE)
+        };
+        match self {
+            Self::Noise { amplitude: a, .. } | Self::Heightfield { amplitude: a, .. }
+                if !a.is_finite() =>
+            {
+                warn!("Displacement amplitude {} is not finite; disabling", a);
+                Self::None
+            }
+            Self::Noise { frequency, .. } if !(frequency.is_finite() && frequency > 0.0) => {
+                warn!(
+                    "Displacement noise frequency {} is not a positive number; disabling",
+                    frequency
+                );
+                Self::None
+            }
+            Self::Noise {
+                amplitude: a,
+                frequency,
+                seed,
+            } => Self::Noise {
+                amplitude: amplitude(a),
+                frequency,
+                seed,
+            },
+            Self::Heightfield {
+                amplitude: a,
+                field,
+                tiling,
+            } => {
+                let tiling = if tiling.is_finite() && tiling.cmpgt(Vec2::ZERO).all() {
+                    tiling
+                } else {
+                    warn!(
+                        "Displacement tiling {} is not positive; using {}",
+                        tiling,
+                        Vec2::ONE
+                    );
+                    Vec2::ONE
+                };
+                Self::Heightfield {
+                    amplitude: amplitude(a),
+                    field,
+                    tiling: Vec2::new(tiling.x.round().max(1.0), tiling.y),
+                }
+            }
+            Self::None => Self::None,
+        }
+    }
+
+    /// Numeric parameters by name, for clamp reports.
+    pub(crate) fn params(&self) -> Vec<(&'static str, f64)> {
+        match self {
+            Self::None => Vec::new(),
+            Self::Noise {
+                amplitude,
+                frequency,
+                ..
+            } => vec![
+                ("amplitude", f64::from(*amplitude)),
+                ("frequency", f64::from(*frequency)),
+            ],
+            Self::Heightfield {
+                amplitude, tiling, ..
+            } => vec![
+                ("amplitude", f64::from(*amplitude)),
+                ("tiling_x", f64::from(tiling.x)),
+                ("tiling_y", f64::from(tiling.y)),
+            ],
+        }
+    }
+
+    /// Whether this displacement changes any geometry.
+    pub(crate) fn is_active(&self) -> bool {
+        match self {
+            Self::None => false,
+            Self::Noise { amplitude, .. } | Self::Heightfield { amplitude, .. } => {
+                *amplitude != 0.0
+            }
+        }
+    }
+
+    /// Height in `0..=1` for a vertex at mesh-space `position` with texture
+    /// coordinates `uv`.
+    pub fn height(&self, position: Vec3, uv: Vec2) -> f32 {
+        match self {
+            Self::None => 0.5,
+            Self::Noise {
+                frequency, seed, ..
+            } => value_noise(position * *frequency, *seed),
+            Self::Heightfield { field, tiling, .. } => field.sample(uv * *tiling),
+        }
+    }
+
+    /// Moves one ring's `positions` radially away from `center` by the height
+    /// at each vertex, scaled by the ring's `radius`. Coincident vertices (the
+    /// U seam and hard corners) move together, since the direction depends on
+    /// position alone.
+    pub(crate) fn displace_ring(
+        &self,
+        positions: &mut [Vec3],
+        uvs: &[[f32; 2]],
+        center: Vec3,
+        radius: f32,
+    ) {
+        let amplitude = match self {
+            Self::None => return,
+            Self::Noise { amplitude, .. } | Self::Heightfield { amplitude, .. } => *amplitude,
+        };
+        for (position, uv) in positions.iter_mut().zip(uvs) {
+            let outward = (*position - center).normalize_or_zero();
+            let height = self.height(*position, Vec2::from_array(*uv));
+            *position += outward * (amplitude * radius * (height - 0.5));
+        }
+    }
+
+    pub(crate) fn hash_into<H: Hasher>(&self, hasher: &mut H) {
+        match self {
+            Self::None => 0u8.hash(hasher),
+            Self::Noise {
+                amplitude,
+                frequency,
+                seed,
+            } => {
+                1u8.hash(hasher);
+                amplitude.to_bits().hash(hasher);
+                frequency.to_bits().hash(hasher);
+                seed.hash(hasher);
+            }
+            Self::Heightfield {
+                amplitude,
+                field,
+                tiling,
+            } => {
+                2u8.hash(hasher);
+                amplitude.to_bits().hash(hasher);
+                field.fingerprint.hash(hasher);
+                tiling.x.to_bits().hash(hasher);
+                tiling.y.to_bits().hash(hasher);
+            }
+        }
+    }
+}
+
+/// A grid of heights in `0..=1`, sampled bilinearly with wrapping.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heightfield {
+    width: u32,
+    height: u32,
+    values: Vec<f32>,
+    /// Hash of the contents, so builder fingerprints need not rehash them.
+    fingerprint: u64,
+}
+
+impl Heightfield {
+    /// Creates a heightfield from row-major `values`, clamped to `0..=1`.
+    /// Returns `None` if either dimension is zero or `values` does not hold
+    /// `width * height` entries. Non-finite values become 0.5.
+    pub fn new(width: u32, height: u32, values: Vec<f32>) -> Option<Self> {
+        if width == 0 || height == 0 || values.len() != width as usize * height as usize {
+            return None;
+        }
+        let values: Vec<f32> = values
+            .into_iter()
+            .map(|v| {
+                if v.is_finite() {
+                    v.clamp(0.0, 1.0)
+                } else {
+                    0.5
+                }
+            })
+            .collect();
+        let mut hasher = DefaultHasher::new();
+        width.hash(&mut hasher);
+        height.hash(&mut hasher);
+        for v in &values {
+            v.to_bits().hash(&mut hasher);
+        }
+        Some(Self {
+            width,
+            height,
+            values,
+            fingerprint: hasher.finish(),
+        })
+    }
+
+    /// Derives a heightfield from a generated texture: the luminance of its
+    /// albedo, stretched to span `0..=1`. Procedural surfaces shade their
+    /// crevices darker, so bright texels read as ridges.
+    pub fn from_texture_map(map: &TextureMap) -> Option<Self> {
+        let texels = map.width as usize * map.height as usize;
+        let albedo = map.albedo.get(..texels * 4)?;
+        let luminance: Vec<f32> = albedo
+            .chunks_exact(4)
+            .map(|rgba| {
+                (0.2126 * f32::from(rgba[0])
+                    + 0.7152 * f32::from(rgba[1])
+                    + 0.0722 * f32::from(rgba[2]))
+                    / 255.0
+            })
+            .collect();
+        let (min, max) = luminance
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(lo, hi), &l| (lo.min(l), hi.max(l)));
+        let span = max - min;
+        let values = luminance
+            .into_iter()
+            .map(|l| {
+                if span > f32::EPSILON {
+                    (l - min) / span
+                } else {
+                    0.5
+                }
+            })
+            .collect();
+        Self::new(map.width, map.height, values)
+    }
+
+    /// Generates `config` at `size`×`size` texels and derives a heightfield
+    /// from it with [`Self::from_texture_map`]. Returns `None` for
+    /// [`TextureConfig::None`] or if generation fails.
+    ///
+    /// Generation runs synchronously; keep `size` small (64–256 is plenty for
+    /// relief) or call this off the main thread.
+    pub fn from_texture_config(config: &TextureConfig, size: u32) -> Option<Self> {
+        match config.generate_sync(size.max(1), size.max(1))? {
+            Ok(map) => Self::from_texture_map(&map),
+            Err(err) => {
+                warn!("Failed to generate heightfield texture: {}", err);
+                None
+            }
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Bilinearly interpolated height at `uv`, wrapping outside `0..1` on
+    /// both axes. Texel centers sit at half-texel offsets, as on the GPU.
+    pub fn sample(&self, uv: Vec2) -> f32 {
+        let (w, h) = (self.width as i64, self.height as i64);
+        let x = uv.x.rem_euclid(1.0) * w as f32 - 0.5;
+        let y = uv.y.rem_euclid(1.0) * h as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let texel = |xi: i64, yi: i64| {
+            let xi = xi.rem_euclid(w) as usize;
+            let yi = yi.rem_euclid(h) as usize;
+            self.values[yi * self.width as usize + xi]
+        };
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let top = texel(x0, y0) + (texel(x0 + 1, y0) - texel(x0, y0)) * fx;
+        let bottom = texel(x0, y0 + 1) + (texel(x0 + 1, y0 + 1) - texel(x0, y0 + 1)) * fx;
+        top + (bottom - top) * fy
+    }
+}
+
+/// Smooth value noise in `0..=1` with one random value per integer lattice
+/// point, blended with a quintic fade.
+fn value_noise(p: Vec3, seed: u32) -> f32 {
+    let cell = p.floor();
+    let f = p - cell;
+    let fade = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+    let (x, y, z) = (cell.x as i32, cell.y as i32, cell.z as i32);
+    let corner = |dx: i32, dy: i32, dz: i32| lattice_hash(seed, x + dx, y + dy, z + dz);
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+
+    let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), fade.x);
+    let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), fade.x);
+    let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), fade.x);
+    let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), fade.x);
+    lerp(lerp(x00, x10, fade.y), lerp(x01, x11, fade.y), fade.z)
+}
+
+/// Deterministic pseudo-random value in `0..=1` for one lattice point.
+fn lattice_hash(seed: u32, x: i32, y: i32, z: i32) -> f32 {
+    let mut h = seed.wrapping_mul(0x9E37_79B9)
+        ^ (x as u32).wrapping_mul(0x85EB_CA6B)
+        ^ (y as u32).wrapping_mul(0xC2B2_AE35)
+        ^ (z as u32).wrapping_mul(0x27D4_EB2F);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    h = h.wrapping_mul(0x297A_2D39);
+    h ^= h >> 15;
+    h as f32 / u32::MAX as f32
+}
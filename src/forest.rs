@@ -0,0 +1,376 @@
+//! Batching many L-System meshes into spatial chunks.
+//!
+//! Dense vegetation built tree by tree costs one entity and one draw per tree
+//! and material. A [`ForestBatch`] collects the output of many
+//! [`LSystemMeshBuilder`](crate::LSystemMeshBuilder) runs, each with its own
+//! world [`Transform`], buckets the trees into square chunks on the XZ plane
+//! by their root position, and merges every chunk into one mesh per material.
+//!
+//! Editing a tree only marks the chunks it left and entered as dirty.
+//! [`sync_forest_chunks`] re-merges and respawns just those chunks, or call
+//! [`ForestBatch::build_chunk`] directly to manage the entities yourself.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use bevy::prelude::*;
+//! use bevy_symbios::LSystemMeshBuilder;
+//! use bevy_symbios::forest::{ForestBatch, sync_forest_chunks};
+//!
+//! app.insert_resource(ForestBatch::new(32.0))
+//!     .add_systems(Update, sync_forest_chunks);
+//!
+//! fn plant(mut forest: ResMut<ForestBatch>, skeleton: symbios_turtle_3d::Skeleton) {
+//!     let meshes = LSystemMeshBuilder::new().build(&skeleton);
+//!     for x in 0..100 {
+//!         let transform = Transform::from_xyz(x as f32 * 3.0, 0.0, 0.0);
+//!         forest.insert(transform, meshes.clone());
+//!     }
+//! }
+//! ```
+//!
+//! Chunk meshes are in world space, including the growth spines, and root
+//! distances and radii are scaled with their tree. Trees only merge when their meshes for a
+//! material carry the same vertex attributes, so build every tree of a forest
+//! with the same builder options.
+
+use bevy::camera::primitives::{Aabb, MeshAabb};
+use bevy::mesh::VertexAttributeValues;
+use bevy::platform::collections::hash_map::Entry;
+use bevy::platform::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+
+use crate::materials::MaterialPalette;
+use crate::mesher::{
+    ATTRIBUTE_BRANCH_RADIUS, ATTRIBUTE_PREVIOUS_SPINE, ATTRIBUTE_ROOT_DISTANCE, ATTRIBUTE_SPINE,
+};
+
+/// Chunk edge length used by [`ForestBatch::default`], in world units.
+pub const DEFAULT_CHUNK_SIZE: f32 = 64.0;
+
+/// Handle to a tree inside a [`ForestBatch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ForestTreeId(u32);
+
+struct ForestTree {
+    transform: Transform,
+    meshes: HashMap<u16, Mesh>,
+    chunk: IVec2,
+}
+
+/// Trees grouped into square XZ chunks, each merged into one mesh per
+/// material. See the [module docs](self).
+#[derive(Resource)]
+pub struct ForestBatch {
+    chunk_size: f32,
+    trees: HashMap<ForestTreeId, ForestTree>,
+    /// Trees of every non-empty chunk, in insertion order.
+    chunks: HashMap<IVec2, Vec<ForestTreeId>>,
+    dirty: HashSet<IVec2>,
+    next_id: u32,
+}
+
+impl Default for ForestBatch {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHUNK_SIZE)
+    }
+}
+
+impl ForestBatch {
+    /// Creates an empty batch with chunks `chunk_size` world units across.
+    /// Non-positive or non-finite sizes fall back to [`DEFAULT_CHUNK_SIZE`].
+    pub fn new(chunk_size: f32) -> Self {
+        let chunk_size = if chunk_size.is_finite() && chunk_size > 0.0 {
+            chunk_size
+        } else {
+            warn!(
+                "Forest chunk size {} is not a positive number; using {}",
+                chunk_size, DEFAULT_CHUNK_SIZE
+            );
+            DEFAULT_CHUNK_SIZE
+        };
+        Self {
+            chunk_size,
+            trees: HashMap::new(),
+            chunks: HashMap::new(),
+            dirty: HashSet::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn chunk_size(&self) -> f32 {
+        self.chunk_size
+    }
+
+    /// Number of trees in the batch.
+    pub fn len(&self) -> usize {
+        self.trees.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trees.is_empty()
+    }
+
+    /// The chunk containing `position`, ignoring its height.
+    pub fn chunk_key(&self, position: Vec3) -> IVec2 {
+        (position.xz() / self.chunk_size).floor().as_ivec2()
+    }
+
+    /// Adds a tree whose per-material `meshes` (as from
+    /// [`LSystemMeshBuilder::build`](crate::LSystemMeshBuilder::build)) are
+    /// placed by `transform`. Its chunk is chosen by the transform's
+    /// translation and marked dirty.
+    pub fn insert(&mut self, transform: Transform, meshes: HashMap<u16, Mesh>) -> ForestTreeId {
+        let id = ForestTreeId(self.next_id);
+        self.next_id += 1;
+        let chunk = self.chunk_key(transform.translation);
+        self.chunks.entry(chunk).or_default().push(id);
+        self.dirty.insert(chunk);
+        self.trees.insert(
+            id,
+            ForestTree {
+                transform,
+                meshes,
+                chunk,
+            },
+        );
+        id
+    }
+
+    /// Replaces the transform and meshes of tree `id`, marking both its old
+    /// and new chunk dirty. Returns `false` if the tree does not exist.
+    pub fn replace(
+        &mut self,
+        id: ForestTreeId,
+        transform: Transform,
+        meshes: HashMap<u16, Mesh>,
+    ) -> bool {
+        let chunk = self.chunk_key(transform.translation);
+        let Some(tree) = self.trees.get_mut(&id) else {
+            return false;
+        };
+        let old = tree.chunk;
+        *tree = ForestTree {
+            transform,
+            meshes,
+            chunk,
+        };
+        if old != chunk {
+            self.detach(id, old);
+            self.chunks.entry(chunk).or_default().push(id);
+        }
+        self.dirty.insert(old);
+        self.dirty.insert(chunk);
+        true
+    }
+
+    /// Removes tree `id`, marking its chunk dirty. Returns `false` if the
+    /// tree does not exist.
+    pub fn remove(&mut self, id: ForestTreeId) -> bool {
+        let Some(tree) = self.trees.remove(&id) else {
+            return false;
+        };
+        self.detach(id, tree.chunk);
+        self.dirty.insert(tree.chunk);
+        true
+    }
+
+    /// The chunk tree `id` belongs to.
+    pub fn chunk_of(&self, id: ForestTreeId) -> Option<IVec2> {
+        self.trees.get(&id).map(|tree| tree.chunk)
+    }
+
+    /// Keys of every chunk holding at least one tree.
+    pub fn chunks(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.chunks.keys().copied()
+    }
+
+    /// Trees in chunk `key`, in insertion order.
+    pub fn trees_in(&self, key: IVec2) -> &[ForestTreeId] {
+        self.chunks.get(&key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Flags chunk `key` for rebuilding, e.g. after editing materials.
+    pub fn mark_dirty(&mut self, key: IVec2) {
+        self.dirty.insert(key);
+    }
+
+    /// Returns and clears the chunks changed since the last call, sorted.
+    /// Chunks that became empty are included so their entities can be
+    /// despawned.
+    pub fn take_dirty(&mut self) -> Vec<IVec2> {
+        let mut dirty: Vec<IVec2> = self.dirty.drain().collect();
+        dirty.sort_by_key(|key| (key.x, key.y));
+        dirty
+    }
+
+    /// Merges the trees of chunk `key` into one world-space mesh per
+    /// material. An empty or unknown chunk yields no meshes.
+    ///
+    /// A tree's mesh whose vertex attributes differ from the ones already
+    /// merged for that material is skipped with a warning.
+    pub fn build_chunk(&self, key: IVec2) -> ForestChunk {
+        let mut meshes: HashMap<u16, Mesh> = HashMap::new();
+        for id in self.trees_in(key) {
+            let tree = &self.trees[id];
+            for (material_id, mesh) in &tree.meshes {
+                let placed = place(mesh, &tree.transform);
+                match meshes.entry(*material_id) {
+                    Entry::Vacant(vacant) => {
+                        vacant.insert(placed);
+                    }
+                    Entry::Occupied(mut occupied) => {
+                        let merged = occupied.get_mut();
+                        if !same_attributes(merged, &placed) {
+                            warn!(
+                                "Forest tree {:?} has different vertex attributes for material {}; skipping it in chunk {}",
+                                id, material_id, key
+                            );
+                            continue;
+                        }
+                        if let Err(err) = merged.merge(&placed) {
+                            warn!("Failed to merge forest tree {:?}: {}", id, err);
+                        }
+                    }
+                }
+            }
+        }
+        let aabb = meshes
+            .values()
+            .filter_map(MeshAabb::compute_aabb)
+            .reduce(|a, b| {
+                Aabb::from_min_max(a.min().min(b.min()).into(), a.max().max(b.max()).into())
+            });
+        ForestChunk { key, meshes, aabb }
+    }
+
+    fn detach(&mut self, id: ForestTreeId, chunk: IVec2) {
+        if let Some(ids) = self.chunks.get_mut(&chunk) {
+            ids.retain(|other| *other != id);
+            if ids.is_empty() {
+                self.chunks.remove(&chunk);
+            }
+        }
+    }
+}
+
+/// A copy of `mesh` moved into world space by `transform`.
+///
+/// [`Mesh::transformed_by`] only covers positions, normals and tangents, so
+/// the growth spines are transformed here too (the birth time in the `w` of
+/// [`ATTRIBUTE_PREVIOUS_SPINE`] is left alone). Root distances and radii are
+/// multiplied by the geometric mean of the scale, which is exact for uniform
+/// scales.
+fn place(mesh: &Mesh, transform: &Transform) -> Mesh {
+    let mut placed = mesh.clone().transformed_by(*transform);
+    if let Some(VertexAttributeValues::Float32x3(spines)) = placed.attribute_mut(ATTRIBUTE_SPINE) {
+        for spine in spines.iter_mut() {
+            *spine = transform.transform_point(Vec3::from(*spine)).into();
+        }
+    }
+    if let Some(VertexAttributeValues::Float32x4(spines)) =
+        placed.attribute_mut(ATTRIBUTE_PREVIOUS_SPINE)
+    {
+        for spine in spines.iter_mut() {
+            let [x, y, z, birth] = *spine;
+            let [x, y, z] = transform.transform_point(Vec3::new(x, y, z)).into();
+            *spine = [x, y, z, birth];
+        }
+    }
+    let scale = transform.scale.element_product().abs().cbrt();
+    for attribute in [ATTRIBUTE_ROOT_DISTANCE, ATTRIBUTE_BRANCH_RADIUS] {
+        if let Some(VertexAttributeValues::Float32(values)) = placed.attribute_mut(attribute) {
+            for value in values.iter_mut() {
+                *value *= scale;
+            }
+        }
+    }
+    placed
+}
+
+/// Whether `a` and `b` carry the same set of vertex attributes.
+fn same_attributes(a: &Mesh, b: &Mesh) -> bool {
+    let ids = |mesh: &Mesh| {
+        let mut ids: Vec<_> = mesh
+            .attributes()
+            .map(|(attribute, _)| attribute.id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    };
+    ids(a) == ids(b)
+}
+
+/// Merged meshes of one chunk, as produced by [`ForestBatch::build_chunk`].
+pub struct ForestChunk {
+    pub key: IVec2,
+    /// One world-space mesh per material ID.
+    pub meshes: HashMap<u16, Mesh>,
+    /// Bounds of all meshes together, or `None` for an empty chunk.
+    pub aabb: Option<Aabb>,
+}
+
+/// Marker on the entities spawned by [`spawn_forest_chunk`], recording the
+/// chunk and material bucket each one renders.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForestChunkPart {
+    pub key: IVec2,
+    pub material_id: u16,
+}
+
+/// Spawns one entity per material of `chunk`, with its [`Aabb`] precomputed.
+/// Materials are looked up in `palette`, falling back to
+/// [`MaterialPalette::primary_material`]. Returns the spawned entities.
+pub fn spawn_forest_chunk(
+    commands: &mut Commands,
+    chunk: ForestChunk,
+    meshes: &mut Assets<Mesh>,
+    palette: &MaterialPalette,
+) -> Vec<Entity> {
+    let mut spawned = Vec::with_capacity(chunk.meshes.len());
+    for (material_id, mesh) in chunk.meshes {
+        let material = palette
+            .materials
+            .get(&material_id)
+            .unwrap_or(&palette.primary_material)
+            .clone();
+        let aabb = mesh.compute_aabb();
+        let mut entity = commands.spawn((
+            Mesh3d(meshes.add(mesh)),
+            MeshMaterial3d(material),
+            ForestChunkPart {
+                key: chunk.key,
+                material_id,
+            },
+        ));
+        if let Some(aabb) = aabb {
+            entity.insert(aabb);
+        }
+        spawned.push(entity.id());
+    }
+    spawned
+}
+
+/// Update system that rebuilds every dirty chunk of the [`ForestBatch`]
+/// resource: its old [`ForestChunkPart`] entities are despawned and the
+/// re-merged meshes spawned in their place.
+pub fn sync_forest_chunks(
+    mut commands: Commands,
+    mut batch: ResMut<ForestBatch>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    palette: Res<MaterialPalette>,
+    parts: Query<(Entity, &ForestChunkPart)>,
+) {
+    if batch.dirty.is_empty() {
+        return;
+    }
+    let dirty = batch.take_dirty();
+    for (entity, part) in &parts {
+        if dirty.contains(&part.key) {
+            commands.entity(entity).despawn();
+        }
+    }
+    for key in dirty {
+        spawn_forest_chunk(&mut commands, batch.build_chunk(key), &mut meshes, &palette);
+    }
+}
@@ -0,0 +1,211 @@
+//! Growth animation for L-System meshes.
+//!
+//! [`GrowthMaterial`] is an [`ExtendedMaterial`] over [`StandardMaterial`]
+//! whose vertex stage reveals a mesh from the root outward, using the
+//! attributes baked by [`LSystemMeshBuilder::with_growth_attributes`]. Tubes
+//! emerge behind a moving growth front and thicken to full radius, so one
+//! mesh animates smoothly from seedling to full plant instead of popping
+//! between derivation steps.
+//!
+//! Progress is driven per entity by the [`GrowthProgress`] component, read
+//! from the mesh entity itself or its parent (so one component on the entity
+//! owning the [`LSystemMeshPart`](crate::mesh_tasks::LSystemMeshPart)
+//! children drives all of them). [`GrowthRate`] advances it automatically.
+//! Progress lives in the material uniform, so entities that should grow
+//! independently need their own [`GrowthMaterial`] handles.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use bevy::prelude::*;
+//! use bevy_symbios::LSystemMeshBuilder;
+//! use bevy_symbios::growth::{GrowthMaterial, GrowthPlugin, GrowthProgress, GrowthRate};
+//!
+//! app.add_plugins(GrowthPlugin);
+//!
+//! fn spawn_seedling(
+//!     mut commands: Commands,
+//!     mut meshes: ResMut<Assets<Mesh>>,
+//!     mut materials: ResMut<Assets<GrowthMaterial>>,
+//!     skeleton: symbios_turtle_3d::Skeleton,
+//! ) {
+//!     let tree = commands
+//!         .spawn((Transform::default(), GrowthProgress(0.0), GrowthRate(0.1)))
+//!         .id();
+//!     let mesh_map = LSystemMeshBuilder::new()
+//!         .with_growth_attributes(true)
+//!         .build(&skeleton);
+//!     for (_, mesh) in mesh_map {
+//!         commands.spawn((
+//!             Mesh3d(meshes.add(mesh)),
+//!             MeshMaterial3d(materials.add(GrowthMaterial::default())),
+//!             ChildOf(tree),
+//!         ));
+//!     }
+//! }
+//! ```
+//!
+//! Meshes rendered with a [`GrowthMaterial`] must carry [`ATTRIBUTE_BIRTH`],
+//! [`ATTRIBUTE_SPINE`] and [`ATTRIBUTE_PREVIOUS_SPINE`]; pipelines for meshes
+//! without them fail to specialize and are not drawn. Skinned and morphed
+//! meshes are not supported.
+//!
+//! [`LSystemMeshBuilder::with_growth_attributes`]: crate::LSystemMeshBuilder::with_growth_attributes
+
+use bevy::asset::embedded_asset;
+use bevy::mesh::MeshVertexBufferLayoutRef;
+use bevy::pbr::{
+    ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline,
+};
+use bevy::prelude::*;
+use bevy::render::render_resource::{
+    AsBindGroup, RenderPipelineDescriptor, ShaderType, SpecializedMeshPipelineError,
+};
+use bevy::shader::ShaderRef;
+
+use crate::mesher::{ATTRIBUTE_BIRTH, ATTRIBUTE_PREVIOUS_SPINE, ATTRIBUTE_SPINE};
+
+/// Shader location of [`ATTRIBUTE_BIRTH`] in the growth vertex shaders. The
+/// following two locations hold the spine and previous spine. Kept clear of
+/// the wind material's locations so a custom shader can bind both sets.
+const GROWTH_SHADER_LOCATION: u32 = 13;
+
+const GROWTH_SHADER_PATH: &str = "embedded://bevy_symbios/shaders/growth.wgsl";
+const GROWTH_PREPASS_SHADER_PATH: &str = "embedded://bevy_symbios/shaders/growth_prepass.wgsl";
+
+/// How far a plant has grown, from `0.0` (nothing visible) to `1.0` (full
+/// size). Values outside that range are clamped by the shader.
+///
+/// Place it on the mesh entity or on its parent; see the
+/// [module docs](self).
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct GrowthProgress(pub f32);
+
+/// Progress per second added to [`GrowthProgress`] by [`advance_growth`].
+/// Negative rates shrink the plant back.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct GrowthRate(pub f32);
+
+/// GPU layout of the growth parameters. Mirrors `GrowthUniform` in
+/// `shaders/growth_common.wgsl`.
+#[derive(Clone, Copy, Debug, PartialEq, ShaderType, Reflect)]
+pub struct GrowthUniform {
+    /// Current [`GrowthProgress`], written by [`apply_growth_progress`].
+    pub progress: f32,
+    /// Length of the taper behind the growth front, in the same normalized
+    /// units as [`ATTRIBUTE_BIRTH`]. Larger values give longer, softer tips.
+    pub ramp: f32,
+}
+
+impl Default for GrowthUniform {
+    fn default() -> Self {
+        Self {
+            progress: 1.0,
+            ramp: 0.1,
+        }
+    }
+}
+
+/// [`MaterialExtension`] that reveals a mesh along its baked birth times. Use
+/// through [`GrowthMaterial`].
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
+pub struct GrowthExtension {
+    #[uniform(100)]
+    pub growth: GrowthUniform,
+}
+
+/// [`StandardMaterial`] with growth animation.
+pub type GrowthMaterial = ExtendedMaterial<StandardMaterial, GrowthExtension>;
+
+impl MaterialExtension for GrowthExtension {
+    fn vertex_shader() -> ShaderRef {
+        GROWTH_SHADER_PATH.into()
+    }
+
+    fn prepass_vertex_shader() -> ShaderRef {
+        GROWTH_PREPASS_SHADER_PATH.into()
+    }
+
+    fn deferred_vertex_shader() -> ShaderRef {
+        GROWTH_PREPASS_SHADER_PATH.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let growth = layout.0.get_layout(&[
+            ATTRIBUTE_BIRTH.at_shader_location(GROWTH_SHADER_LOCATION),
+            ATTRIBUTE_SPINE.at_shader_location(GROWTH_SHADER_LOCATION + 1),
+            ATTRIBUTE_PREVIOUS_SPINE.at_shader_location(GROWTH_SHADER_LOCATION + 2),
+        ])?;
+        descriptor.vertex.buffers[0]
+            .attributes
+            .extend(growth.attributes);
+        Ok(())
+    }
+}
+
+/// Update system that adds [`GrowthRate`] × delta time to every
+/// [`GrowthProgress`], clamped to `0.0..=1.0`.
+pub fn advance_growth(time: Res<Time>, mut plants: Query<(&mut GrowthProgress, &GrowthRate)>) {
+    let dt = time.delta_secs();
+    for (mut progress, rate) in plants.iter_mut() {
+        if rate.0 == 0.0 {
+            continue;
+        }
+        let next = (progress.0 + rate.0 * dt).clamp(0.0, 1.0);
+        if next != progress.0 {
+            progress.0 = next;
+        }
+    }
+}
+
+/// Update system that copies each [`GrowthMaterial`] entity's
+/// [`GrowthProgress`] — its own, or else its parent's — into the material.
+///
+/// Materials are only touched when the value actually differs, so idle
+/// plants cost no GPU uploads.
+pub fn apply_growth_progress(
+    parts: Query<(
+        &MeshMaterial3d<GrowthMaterial>,
+        Option<&GrowthProgress>,
+        Option<&ChildOf>,
+    )>,
+    parents: Query<&GrowthProgress>,
+    mut materials: ResMut<Assets<GrowthMaterial>>,
+) {
+    for (material, own, child_of) in parts.iter() {
+        let Some(progress) = own
+            .or_else(|| child_of.and_then(|c| parents.get(c.parent()).ok()))
+            .map(|p| p.0)
+        else {
+            continue;
+        };
+        let stale = materials
+            .get(&material.0)
+            .is_some_and(|m| m.extension.growth.progress != progress);
+        if stale && let Some(m) = materials.get_mut(&material.0) {
+            m.extension.growth.progress = progress;
+        }
+    }
+}
+
+/// Plugin that registers the growth shaders and [`GrowthMaterial`], and runs
+/// [`advance_growth`] followed by [`apply_growth_progress`] in `Update`.
+///
+/// Requires the rendering plugins.
+pub struct GrowthPlugin;
+
+impl Plugin for GrowthPlugin {
+    fn build(&self, app: &mut App) {
+        bevy::shader::load_shader_library!(app, "shaders/growth_common.wgsl");
+        embedded_asset!(app, "shaders/growth.wgsl");
+        embedded_asset!(app, "shaders/growth_prepass.wgsl");
+
+        app.add_plugins(MaterialPlugin::<GrowthMaterial>::default())
+            .add_systems(Update, (advance_growth, apply_growth_progress).chain());
+    }
+}
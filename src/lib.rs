@@ -9,6 +9,42 @@
 //!   UV mapping, and multi-material support via [`LSystemMeshBuilder`].
 //! - **Material system**: Configurable PBR materials with procedural textures,
 //!   palette-first workflow, and automatic sync via [`materials`].
+//! - **Parallel and async meshing**: [`LSystemMeshBuilder::build_parallel`] spreads
+//!   strands across Bevy's compute pool; [`LSystemMeshBuilder::build_async`] plus
+//!   [`mesh_tasks`] mesh off-thread and spawn the result when ready.
+//! - **Build diagnostics**: [`LSystemMeshBuilder::try_build`] reports per-material counts,
+//!   dropped and non-finite input points, and enforces vertex/triangle budgets via
+//!   [`diagnostics`].
+//! - **UV tiling and atlases**: Per-material U repeat counts and texture-atlas rectangles
+//!   via [`MaterialUv`].
+//! - **Lightmap UVs**: [`LSystemMeshBuilder::with_lightmap_uvs`] adds a non-overlapping
+//!   `UV_1` chart layout for lightmap baking and texture-space decals.
+//! - **Low-poly shading**: Flat or angle-limited normals on un-shared vertices via
+//!   [`Shading`].
+//! - **Filtering**: Radius, material, branch-depth, and custom predicates on
+//!   [`LSystemMeshBuilder`] for trunk-only, foliage-only, or twig-free meshes.
+//! - **Picking**: [`LSystemMeshBuilder::build_mapped`] maps triangles back to strands and
+//!   points, and [`SkeletonPickingPlugin`] turns clicks into [`SkeletonPicked`] events.
+//! - **Incremental remeshing**: [`LSystemMeshBuilder::build_incremental`] keeps per-strand
+//!   vertex data in a [`StrandCache`] and re-meshes only added or changed strands.
+//! - **Surface relief**: Offset tube vertices by seeded noise or a heightfield derived
+//!   from a procedural texture via [`displacement`].
+//! - **Ambient occlusion**: Deterministic CPU bake of capsule occlusion into vertex
+//!   colours or a dedicated attribute via [`occlusion`].
+//! - **Level of detail**: Build LOD chains from one skeleton and spawn them with
+//!   Bevy visibility ranges via [`lod`].
+//! - **Shadow proxies**: Cast shadows from a coarse, twig-free companion mesh instead of
+//!   the full-resolution tree via [`shadow`].
+//! - **Skinning**: Build meshes bound to a joint hierarchy that follows the strands
+//!   and spawn them as a Bevy skinned mesh via [`skinning`].
+//! - **Single-draw output**: [`LSystemMeshBuilder::build_merged`] merges every material
+//!   into one mesh, shaded from a palette storage buffer via [`merged`].
+//! - **Forest batching**: Merge many placed trees into chunked per-material meshes and
+//!   rebuild only the chunks that change via [`forest`].
+//! - **Wind sway**: A [`StandardMaterial`](bevy::pbr::StandardMaterial) extension that
+//!   bends meshes by their baked hierarchy, opted into per palette slot via [`wind`].
+//! - **Growth animation**: A [`StandardMaterial`](bevy::pbr::StandardMaterial) extension
+//!   that reveals a mesh from the root outward by baked birth times via [`growth`].
 //! - **Export**: OBJ and GLB export utilities via [`export`].
 //! - **Physics colliders** (optional): Generate capsule colliders for physics simulation
 //!   via [`ColliderGenerator`]. Requires the `physics` feature.
@@ -64,9 +100,21 @@
 //! }
 //! ```
 
+pub mod diagnostics;
+pub mod displacement;
 pub mod export;
+pub mod forest;
+pub mod growth;
+pub mod lod;
 pub mod materials;
+pub mod merged;
+pub mod mesh_tasks;
 pub mod mesher;
+pub mod occlusion;
+pub mod picking;
+pub mod shadow;
+pub mod skinning;
+pub mod wind;
 
 #[cfg(feature = "physics")]
 pub mod collider;
@@ -77,7 +125,23 @@ pub mod ui;
 #[cfg(feature = "asset-loader")]
 pub mod loader;
 
-pub use mesher::{LSystemMeshBuilder, MeshCache, compute_skeleton_fingerprint};
+pub use diagnostics::{BuildError, BuildReport};
+pub use displacement::{Displacement, Heightfield};
+pub use forest::{ForestBatch, ForestTreeId};
+pub use growth::{GrowthMaterial, GrowthPlugin, GrowthProgress, GrowthRate};
+pub use lod::{LodChain, LodLevel};
+pub use merged::{PaletteMaterial, PalettePlugin};
+pub use mesher::{
+    ATTRIBUTE_BIRTH, ATTRIBUTE_BRANCH_DEPTH, ATTRIBUTE_BRANCH_RADIUS, ATTRIBUTE_MATERIAL_INDEX,
+    ATTRIBUTE_PREVIOUS_SPINE, ATTRIBUTE_ROOT_DISTANCE, ATTRIBUTE_SPINE, ATTRIBUTE_STRAND_INDEX,
+    ATTRIBUTE_STRAND_PROGRESS, CapStyle, CardStyle, CrossSection, JunctionStyle,
+    LSystemMeshBuilder, MAX_U_REPEAT, MaterialUv, MeshCache, RibbonFacing, Shading, StrandCache,
+    StrandSmoothing, compute_skeleton_fingerprint,
+};
+pub use occlusion::{AmbientOcclusion, OcclusionTarget, bake_ambient_occlusion};
+pub use picking::{SkeletonLocation, SkeletonMap, SkeletonPicked, SkeletonPickingPlugin};
+pub use shadow::{ShadowProxy, spawn_shadow_proxied};
+pub use wind::{Wind, WindMaterial, WindPlugin, WindResponse};
 
 #[cfg(feature = "physics")]
 pub use collider::{ColliderGenerator, PositionedCollider};
@@ -0,0 +1,414 @@
+//! Level-of-detail chains for L-System meshes.
+//!
+//! A [`LodChain`] describes several progressively cheaper versions of the same
+//! skeleton. [`LSystemMeshBuilder::build_lods`] meshes every level in one call,
+//! and [`spawn_lod_children`] attaches the result to an entity with Bevy
+//! [`VisibilityRange`]s so the renderer switches levels by camera distance.
+//!
+//! Each level can:
+//!
+//! - lower the ring resolution,
+//! - drop whole strands thinner than a radius threshold, and
+//! - merge nearly collinear segments within a strand.
+//!
+//! All other builder options (caps, junctions, …) carry over to every level.
+
+use std::collections::BTreeSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use bevy::camera::visibility::VisibilityRange;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use symbios_turtle_3d::{Skeleton, SkeletonPoint};
+
+use crate::materials::MaterialPalette;
+use crate::mesher::{LSystemMeshBuilder, MeshCache};
+
+/// Parameters for a single level of a [`LodChain`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LodLevel {
+    /// Vertices around each tube ring (clamped like
+    /// [`LSystemMeshBuilder::with_resolution`]).
+    pub resolution: u32,
+    /// Strands whose largest radius is below this are dropped entirely.
+    pub min_radius: f32,
+    /// Interior points whose incoming and outgoing directions differ by less
+    /// than this angle (radians) are removed. `None` keeps every point.
+    pub merge_angle: Option<f32>,
+    /// Camera distance at which this level hands over to the next one.
+    pub max_distance: f32,
+}
+
+impl LodLevel {
+    /// A level with the given ring resolution that is shown up to
+    /// `max_distance`. No strands are dropped and no points are merged.
+    pub fn new(resolution: u32, max_distance: f32) -> Self {
+        Self {
+            resolution,
+            min_radius: 0.0,
+            merge_angle: None,
+            max_distance,
+        }
+    }
+
+    /// Drops strands whose largest radius is below `min_radius`.
+    pub fn with_min_radius(mut self, min_radius: f32) -> Self {
+        self.min_radius = min_radius.max(0.0);
+        self
+    }
+
+    /// Merges interior points where the strand bends by less than `angle`
+    /// radians. Points at material boundaries and card points are always kept.
+    pub fn with_merge_collinear(mut self, angle: f32) -> Self {
+        self.merge_angle = Some(angle.max(0.0));
+        self
+    }
+}
+
+/// An ordered set of [`LodLevel`]s, nearest (most detailed) first.
+#[derive(Debug, Clone, Default)]
+pub struct LodChain {
+    levels: Vec<LodLevel>,
+    fade: f32,
+    final_level_unbounded: bool,
+}
+
+impl LodChain {
+    /// Creates an empty chain with abrupt (non-crossfaded) transitions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a level. Levels are kept sorted by [`LodLevel::max_distance`].
+    pub fn with_level(mut self, level: LodLevel) -> Self {
+        self.levels.push(level);
+        self.levels
+            .sort_by(|a, b| a.max_distance.total_cmp(&b.max_distance));
+        self
+    }
+
+    /// Sets the crossfade margin, in world units, between adjacent levels.
+    /// `0.0` (the default) switches abruptly.
+    pub fn with_fade(mut self, fade: f32) -> Self {
+        self.fade = fade.max(0.0);
+        self
+    }
+
+    /// Keeps the last level visible at any distance, ignoring its
+    /// [`LodLevel::max_distance`]. A last level with an infinite
+    /// `max_distance` behaves the same way.
+    pub fn with_final_level_unbounded(mut self) -> Self {
+        self.final_level_unbounded = true;
+        self
+    }
+
+    /// The levels, nearest first.
+    pub fn levels(&self) -> &[LodLevel] {
+        &self.levels
+    }
+
+    /// The [`VisibilityRange`] for level `index`, or `None` if the chain has
+    /// no such level. Adjacent levels share their transition margin so Bevy
+    /// can crossfade between them.
+    pub fn visibility_range(&self, index: usize) -> Option<VisibilityRange> {
+        let end = self.levels.get(index)?.max_distance;
+        let start_margin = match index.checked_sub(1) {
+            Some(prev) => {
+                let start = self.levels[prev].max_distance;
+                start..start + self.fade
+            }
+            None => 0.0..0.0,
+        };
+        let unbounded =
+            end == f32::INFINITY || (self.final_level_unbounded && index + 1 == self.levels.len());
+        let end_margin = if unbounded {
+            f32::INFINITY..f32::INFINITY
+        } else {
+            end..end + self.fade
+        };
+        Some(VisibilityRange {
+            start_margin,
+            end_margin,
+            use_aabb: false,
+        })
+    }
+
+    /// The range for a level index taken from [`Self::levels`].
+    fn level_range(&self, index: usize) -> VisibilityRange {
+        self.visibility_range(index)
+            .expect("index comes from levels()")
+    }
+}
+
+/// Meshes for one level of a chain, as produced by
+/// [`LSystemMeshBuilder::build_lods`].
+pub struct LodMeshes {
+    /// Index into [`LodChain::levels`].
+    pub level: usize,
+    /// Per-material meshes, as from [`LSystemMeshBuilder::build`].
+    pub meshes: HashMap<u16, Mesh>,
+    /// Distance range in which this level is visible.
+    pub visibility_range: VisibilityRange,
+}
+
+impl LodMeshes {
+    /// Moves the meshes into `assets`, returning handles ready for
+    /// [`spawn_lod_children`].
+    pub fn into_handles(self, assets: &mut Assets<Mesh>) -> LodHandles {
+        LodHandles {
+            level: self.level,
+            meshes: self
+                .meshes
+                .into_iter()
+                .map(|(id, mesh)| (id, assets.add(mesh)))
+                .collect(),
+            visibility_range: self.visibility_range,
+        }
+    }
+}
+
+/// Mesh handles for one level of a chain.
+#[derive(Clone)]
+pub struct LodHandles {
+    /// Index into [`LodChain::levels`].
+    pub level: usize,
+    /// Per-material mesh handles.
+    pub meshes: HashMap<u16, Handle<Mesh>>,
+    /// Distance range in which this level is visible.
+    pub visibility_range: VisibilityRange,
+}
+
+impl LSystemMeshBuilder {
+    /// Builds every level of `chain` from one skeleton, consuming the builder.
+    ///
+    /// The builder's own resolution is replaced by each level's; all other
+    /// options apply to every level.
+    pub fn build_lods(self, skeleton: &Skeleton, chain: &LodChain) -> Vec<LodMeshes> {
+        let card_materials = self.card_materials();
+        chain
+            .levels()
+            .iter()
+            .enumerate()
+            .map(|(index, level)| LodMeshes {
+                level: index,
+                meshes: self
+                    .clone()
+                    .with_resolution(level.resolution)
+                    .build(&simplify_skeleton(skeleton, level, &card_materials)),
+                visibility_range: chain.level_range(index),
+            })
+            .collect()
+    }
+
+    /// Like [`Self::build_lods`], but consults `cache` per level. Each level is
+    /// keyed by the skeleton, the builder options, and the level's parameters,
+    /// so levels are cached independently (see [`compute_lod_fingerprint`]).
+    pub fn build_lods_cached(
+        self,
+        skeleton: &Skeleton,
+        chain: &LodChain,
+        cache: &mut MeshCache,
+        meshes: &mut Assets<Mesh>,
+    ) -> Vec<LodHandles> {
+        let card_materials = self.card_materials();
+        chain
+            .levels()
+            .iter()
+            .enumerate()
+            .map(|(index, level)| {
+                let builder = self.clone().with_resolution(level.resolution);
+                let fingerprint = lod_fingerprint(&builder, skeleton, index, level);
+                let handles = cache.get_or_insert_with(fingerprint, || {
+                    builder
+                        .build(&simplify_skeleton(skeleton, level, &card_materials))
+                        .into_iter()
+                        .map(|(id, mesh)| (id, meshes.add(mesh)))
+                        .collect()
+                });
+                LodHandles {
+                    level: index,
+                    meshes: handles,
+                    visibility_range: chain.level_range(index),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns `true` if level `index` of a [`Self::build_lods_cached`] call
+    /// with this builder would hit `cache`. Does not bump the hit/miss
+    /// counters.
+    pub fn is_lod_cached(
+        &self,
+        cache: &MeshCache,
+        skeleton: &Skeleton,
+        index: usize,
+        level: &LodLevel,
+    ) -> bool {
+        let builder = self.clone().with_resolution(level.resolution);
+        cache.contains_fingerprint(lod_fingerprint(&builder, skeleton, index, level))
+    }
+}
+
+/// The [`MeshCache`] key [`LSystemMeshBuilder::build_lods_cached`] uses for
+/// level `index` of a chain when the builder is otherwise left at its defaults.
+pub fn compute_lod_fingerprint(skeleton: &Skeleton, index: usize, level: &LodLevel) -> u64 {
+    let builder = LSystemMeshBuilder::new().with_resolution(level.resolution);
+    lod_fingerprint(&builder, skeleton, index, level)
+}
+
+fn lod_fingerprint(
+    builder: &LSystemMeshBuilder,
+    skeleton: &Skeleton,
+    index: usize,
+    level: &LodLevel,
+) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    builder.fingerprint(skeleton).hash(&mut hasher);
+    "lod".hash(&mut hasher);
+    index.hash(&mut hasher);
+    level.min_radius.to_bits().hash(&mut hasher);
+    level.merge_angle.map(f32::to_bits).hash(&mut hasher);
+    hasher.finish()
+}
+
+impl MeshCache {
+    /// Returns `true` if level `index` of a default-configured LOD build of
+    /// `skeleton` is cached. Does not bump the hit/miss counters.
+    ///
+    /// Only default-configured builders match; for any other configuration
+    /// use [`LSystemMeshBuilder::is_lod_cached`].
+    pub fn contains_lod(&self, skeleton: &Skeleton, index: usize, level: &LodLevel) -> bool {
+        self.contains_fingerprint(compute_lod_fingerprint(skeleton, index, level))
+    }
+}
+
+/// Returns a copy of `skeleton` reduced according to `level`: thin strands are
+/// dropped and collinear points merged. Parent links of surviving strands are
+/// re-pointed at their nearest surviving ancestor.
+///
+/// Points whose material is in `keep_materials` are never merged away.
+/// Single-point strands produce no tube and are dropped unless their point is
+/// one of them, for callers that mesh lone points themselves. LOD builds pass
+/// the card materials (see [`LSystemMeshBuilder::with_cards`]).
+pub fn simplify_skeleton(
+    skeleton: &Skeleton,
+    level: &LodLevel,
+    keep_materials: &BTreeSet<u16>,
+) -> Skeleton {
+    let is_kept = |p: &SkeletonPoint| keep_materials.contains(&u16::from(p.material_id));
+    let keep: Vec<bool> = skeleton
+        .strands
+        .iter()
+        .map(|strand| {
+            (strand.len() >= 2 || strand.first().is_some_and(is_kept))
+                && strand
+                    .iter()
+                    .any(|p| p.radius >= level.min_radius || p.radius.is_nan())
+        })
+        .collect();
+
+    let mut remap = vec![None; skeleton.strands.len()];
+    let mut next = 0;
+    for (old, kept) in keep.iter().enumerate() {
+        if *kept {
+            remap[old] = Some(next);
+            next += 1;
+        }
+    }
+
+    let mut out = Skeleton {
+        props: skeleton.props.clone(),
+        warnings: skeleton.warnings.clone(),
+        ..Skeleton::default()
+    };
+    for (old, strand) in skeleton.strands.iter().enumerate() {
+        if !keep[old] {
+            continue;
+        }
+        let points = match level.merge_angle {
+            Some(angle) => merge_collinear(strand, angle, is_kept),
+            None => strand.clone(),
+        };
+        out.strands.push(points);
+        out.strand_parents
+            .push(surviving_ancestor(skeleton, old, &remap));
+    }
+    out
+}
+
+/// Walks up `strand_parents` from `strand` to the first ancestor that survives
+/// simplification. Guards against cycles in malformed parent data.
+fn surviving_ancestor(
+    skeleton: &Skeleton,
+    strand: usize,
+    remap: &[Option<usize>],
+) -> Option<usize> {
+    let mut current = skeleton.strand_parents.get(strand).copied().flatten();
+    for _ in 0..skeleton.strands.len() {
+        let idx = current?;
+        if let Some(new_idx) = remap.get(idx).copied().flatten() {
+            return Some(new_idx);
+        }
+        current = skeleton.strand_parents.get(idx).copied().flatten();
+    }
+    None
+}
+
+fn merge_collinear(
+    points: &[SkeletonPoint],
+    angle: f32,
+    is_kept: impl Fn(&SkeletonPoint) -> bool,
+) -> Vec<SkeletonPoint> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let min_dot = angle.cos();
+    let mut kept = vec![points[0]];
+    for i in 1..points.len() - 1 {
+        let prev = kept.last().expect("seeded with the first point");
+        let (cur, next) = (&points[i], &points[i + 1]);
+        let d_in = (cur.position - prev.position).normalize_or_zero();
+        let d_out = (next.position - cur.position).normalize_or_zero();
+        let straight = d_in == Vec3::ZERO || d_out == Vec3::ZERO || d_in.dot(d_out) >= min_dot;
+        if !straight || cur.material_id != prev.material_id || is_kept(cur) {
+            kept.push(*cur);
+        }
+    }
+    kept.push(points[points.len() - 1]);
+    kept
+}
+
+/// Spawns one child of `parent` per (level, material) pair, each with its
+/// level's [`VisibilityRange`]. Materials are looked up in `palette`, falling
+/// back to [`MaterialPalette::primary_material`]. Returns the spawned entities.
+///
+/// Note that [`VisibilityRange`] is not inherited, which is why every mesh
+/// entity carries its own.
+pub fn spawn_lod_children(
+    commands: &mut Commands,
+    parent: Entity,
+    lods: &[LodHandles],
+    palette: &MaterialPalette,
+) -> Vec<Entity> {
+    let mut spawned = Vec::new();
+    for lod in lods {
+        for (material_id, mesh) in &lod.meshes {
+            let material = palette
+                .materials
+                .get(material_id)
+                .unwrap_or(&palette.primary_material)
+                .clone();
+            spawned.push(
+                commands
+                    .spawn((
+                        Mesh3d(mesh.clone()),
+                        MeshMaterial3d(material),
+                        lod.visibility_range.clone(),
+                        ChildOf(parent),
+                    ))
+                    .id(),
+            );
+        }
+    }
+    spawned
+}
@@ -25,6 +25,8 @@ use bevy_symbios_texture::TextureConfig;
 use bevy_symbios_texture::generator::{TextureError, TextureMap};
 use bevy_symbios_texture::{GeneratedHandles, map_to_images, map_to_images_card};
 
+use crate::wind::WindResponse;
+
 pub use bevy_symbios_texture::TextureConfig as ProceduralTextureConfig;
 pub use bevy_symbios_texture::bark::BarkConfig as BarkTexConfig;
 pub use bevy_symbios_texture::leaf::LeafConfig as LeafTexConfig;
@@ -153,6 +155,8 @@ pub enum InlineTextureKey {
 /// Checker) or a `bevy_symbios_texture` generator config carried inline via
 /// [`TextureType::Procedural`]. There are no longer separate config fields for
 /// each generator — the active config lives inside the variant.
+///
+/// `wind` opts the slot into wind sway; see [`crate::wind`].
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 #[serde(default)]
 pub struct MaterialSettings {
@@ -163,6 +167,7 @@ pub struct MaterialSettings {
     pub metallic: f32,
     pub texture: TextureType,
     pub uv_scale: f32,
+    pub wind: Option<WindResponse>,
 }
 
 impl Default for MaterialSettings {
@@ -175,6 +180,7 @@ impl Default for MaterialSettings {
             metallic: 0.0,
             texture: TextureType::None,
             uv_scale: 1.0,
+            wind: None,
         }
     }
 }
@@ -199,6 +205,7 @@ impl Default for MaterialSettingsMap {
                 metallic: 0.8,
                 texture: TextureType::None,
                 uv_scale: 1.0,
+                wind: None,
             },
         );
 
@@ -212,6 +219,7 @@ impl Default for MaterialSettingsMap {
                 metallic: 0.0,
                 texture: TextureType::None,
                 uv_scale: 1.0,
+                wind: None,
             },
         );
 
@@ -225,6 +233,7 @@ impl Default for MaterialSettingsMap {
                 metallic: 0.0,
                 texture: TextureType::None,
                 uv_scale: 1.0,
+                wind: None,
             },
         );
 
@@ -0,0 +1,211 @@
+//! Single-draw output for L-System meshes.
+//!
+//! [`LSystemMeshBuilder::build_merged`] folds every material bucket into one
+//! mesh and records each vertex's material ID in [`ATTRIBUTE_MATERIAL_INDEX`].
+//! [`PaletteMaterial`] renders such a mesh in a single draw call: its fragment
+//! stage looks the ID up in a storage buffer of [`PaletteSlot`]s built from
+//! [`MaterialSettingsMap`], instead of binding one `StandardMaterial` per
+//! bucket.
+//!
+//! [`PalettePlugin`] keeps one shared [`PaletteMaterial`] in
+//! [`MergedPalette`] and refreshes its slots whenever the settings change.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use bevy::prelude::*;
+//! use bevy_symbios::LSystemMeshBuilder;
+//! use bevy_symbios::merged::{MergedPalette, PalettePlugin};
+//!
+//! app.add_plugins(PalettePlugin);
+//!
+//! fn spawn_tree(
+//!     mut commands: Commands,
+//!     mut meshes: ResMut<Assets<Mesh>>,
+//!     palette: Res<MergedPalette>,
+//!     skeleton: symbios_turtle_3d::Skeleton,
+//! ) {
+//!     let mesh = LSystemMeshBuilder::new().build_merged(&skeleton);
+//!     commands.spawn((
+//!         Mesh3d(meshes.add(mesh)),
+//!         MeshMaterial3d(palette.material.clone()),
+//!     ));
+//! }
+//! ```
+//!
+//! Only the scalar PBR parameters of each slot are used: base colour,
+//! emission, roughness and metallic. Textures, alpha modes and cull modes
+//! come from the shared base `StandardMaterial`, so slots that need their own
+//! textures (foliage cards, for instance) are better left as separate
+//! buckets. The palette lookup runs in the forward pass; deferred rendering
+//! shades with the base material.
+
+use bevy::asset::embedded_asset;
+use bevy::mesh::MeshVertexBufferLayoutRef;
+use bevy::pbr::{
+    ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline,
+};
+use bevy::prelude::*;
+use bevy::render::render_resource::{
+    AsBindGroup, RenderPipelineDescriptor, ShaderType, SpecializedMeshPipelineError,
+};
+use bevy::render::storage::ShaderStorageBuffer;
+use bevy::shader::ShaderRef;
+
+use crate::materials::{MaterialSettings, MaterialSettingsMap};
+use crate::mesher::ATTRIBUTE_MATERIAL_INDEX;
+
+/// Shader location of [`ATTRIBUTE_MATERIAL_INDEX`] in the palette vertex
+/// shader: the first one after Bevy's standard mesh attributes (0 to 7).
+/// Vertex attribute locations must stay below wgpu's default
+/// `max_vertex_attributes` of 16.
+pub const MATERIAL_INDEX_SHADER_LOCATION: u32 = 8;
+
+const PALETTE_SHADER_PATH: &str = "embedded://bevy_symbios/shaders/palette.wgsl";
+
+/// PBR parameters of one palette slot. Mirrors `PaletteSlot` in
+/// `shaders/palette.wgsl`.
+#[derive(Clone, Copy, Debug, PartialEq, ShaderType)]
+pub struct PaletteSlot {
+    /// Linear RGBA base colour, multiplied with the vertex colour.
+    pub base_color: Vec4,
+    /// Linear RGB emission, already scaled by its strength.
+    pub emissive: Vec4,
+    pub perceptual_roughness: f32,
+    pub metallic: f32,
+}
+
+impl Default for PaletteSlot {
+    fn default() -> Self {
+        Self::from_settings(&MaterialSettings::default())
+    }
+}
+
+impl PaletteSlot {
+    /// Converts one slot's settings the same way
+    /// [`on_material_settings_changed`](crate::materials::on_material_settings_changed)
+    /// does for its `StandardMaterial`.
+    pub fn from_settings(settings: &MaterialSettings) -> Self {
+        let emissive = Color::srgb_from_array(settings.emission_color).to_linear()
+            * settings.emission_strength;
+        Self {
+            base_color: Color::srgb_from_array(settings.base_color)
+                .to_linear()
+                .to_vec4(),
+            emissive: emissive.to_vec4(),
+            perceptual_roughness: settings.roughness,
+            metallic: settings.metallic,
+        }
+    }
+}
+
+/// Slots for every material ID up to the highest one in `settings`, indexed
+/// by ID. Gaps get [`PaletteSlot::default`]. Never empty, since storage
+/// buffers cannot be zero-sized.
+pub fn palette_slots(settings: &MaterialSettingsMap) -> Vec<PaletteSlot> {
+    let len = settings
+        .settings
+        .keys()
+        .max()
+        .map_or(1, |&max| usize::from(max) + 1);
+    let mut slots = vec![PaletteSlot::default(); len];
+    for (id, material_settings) in &settings.settings {
+        slots[usize::from(*id)] = PaletteSlot::from_settings(material_settings);
+    }
+    slots
+}
+
+/// [`MaterialExtension`] that shades each vertex with the palette slot named
+/// by its [`ATTRIBUTE_MATERIAL_INDEX`]. Use through [`PaletteMaterial`].
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
+pub struct PaletteExtension {
+    /// Buffer of [`PaletteSlot`]s, as from [`palette_slots`]. IDs past its
+    /// end use the last slot.
+    #[storage(100, read_only)]
+    pub slots: Handle<ShaderStorageBuffer>,
+}
+
+/// [`StandardMaterial`] with per-vertex palette lookup.
+pub type PaletteMaterial = ExtendedMaterial<StandardMaterial, PaletteExtension>;
+
+impl MaterialExtension for PaletteExtension {
+    fn vertex_shader() -> ShaderRef {
+        PALETTE_SHADER_PATH.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        PALETTE_SHADER_PATH.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let material_index = layout.0.get_layout(&[
+            ATTRIBUTE_MATERIAL_INDEX.at_shader_location(MATERIAL_INDEX_SHADER_LOCATION)
+        ])?;
+        descriptor.vertex.buffers[0]
+            .attributes
+            .extend(material_index.attributes);
+        Ok(())
+    }
+}
+
+/// The shared [`PaletteMaterial`] maintained by [`sync_palette_material`].
+#[derive(Resource, Default)]
+pub struct MergedPalette {
+    pub material: Handle<PaletteMaterial>,
+}
+
+/// Update system that creates the [`MergedPalette`] material on first run and
+/// rewrites its slot buffer whenever [`MaterialSettingsMap`] changes.
+pub fn sync_palette_material(
+    settings: Option<Res<MaterialSettingsMap>>,
+    mut merged: ResMut<MergedPalette>,
+    mut buffers: ResMut<Assets<ShaderStorageBuffer>>,
+    mut materials: ResMut<Assets<PaletteMaterial>>,
+) {
+    let Some(settings) = settings else {
+        return;
+    };
+    let existing = materials.contains(&merged.material);
+    if existing && !settings.is_changed() {
+        return;
+    }
+    let slots = ShaderStorageBuffer::from(palette_slots(&settings));
+
+    if !existing {
+        merged.material = materials.add(PaletteMaterial {
+            base: StandardMaterial::default(),
+            extension: PaletteExtension {
+                slots: buffers.add(slots),
+            },
+        });
+        return;
+    }
+    // Touch the material as well, so its bind group picks up the new buffer.
+    if let Some(material) = materials.get_mut(&merged.material) {
+        match buffers.get_mut(&material.extension.slots) {
+            Some(buffer) => *buffer = slots,
+            None => material.extension.slots = buffers.add(slots),
+        }
+    }
+}
+
+/// Plugin that registers the palette shader and [`PaletteMaterial`], inserts
+/// [`MergedPalette`], and runs [`sync_palette_material`] in `Update`.
+///
+/// Requires the rendering plugins and a [`MaterialSettingsMap`] resource.
+pub struct PalettePlugin;
+
+impl Plugin for PalettePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shaders/palette.wgsl");
+
+        app.add_plugins(MaterialPlugin::<PaletteMaterial>::default())
+            .init_resource::<MergedPalette>()
+            .add_systems(Update, sync_palette_material);
+    }
+}
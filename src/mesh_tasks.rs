@@ -0,0 +1,94 @@
+//! Off-thread mesh generation for L-System skeletons.
+//!
+//! Deep derivations can take long enough to mesh that doing it inside a system
+//! stalls the frame. [`LSystemMeshBuilder::build_async`] moves the work onto
+//! the [`AsyncComputeTaskPool`]; this module provides the component that holds
+//! the in-flight task and the system that collects it, mirroring how
+//! [`FoliageTextureTasks`](crate::materials::FoliageTextureTasks) and
+//! [`apply_foliage_textures`](crate::materials::apply_foliage_textures) handle
+//! procedural textures.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use bevy::prelude::*;
+//! use bevy_symbios::LSystemMeshBuilder;
+//! use bevy_symbios::mesh_tasks::{LSystemMeshTask, apply_lsystem_mesh_tasks};
+//!
+//! app.add_systems(Update, apply_lsystem_mesh_tasks);
+//!
+//! fn spawn_tree(mut commands: Commands, skeleton: symbios_turtle_3d::Skeleton) {
+//!     let task = LSystemMeshBuilder::new().with_resolution(12).build_async(skeleton);
+//!     commands.spawn((Transform::default(), LSystemMeshTask(task)));
+//! }
+//! ```
+//!
+//! [`LSystemMeshBuilder::build_async`]: crate::LSystemMeshBuilder::build_async
+//! [`AsyncComputeTaskPool`]: bevy::tasks::AsyncComputeTaskPool
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::tasks::{Task, block_on, futures_lite::future};
+
+use crate::materials::MaterialPalette;
+use crate::mesher::ATTRIBUTE_ROOT_DISTANCE;
+use crate::wind::WindPalette;
+
+/// In-flight mesh build attached to the entity that should own the result.
+///
+/// Polled by [`apply_lsystem_mesh_tasks`]. Once the task finishes, one child
+/// entity per material bucket is spawned under this entity and the component
+/// is removed. Existing children are left untouched.
+#[derive(Component)]
+pub struct LSystemMeshTask(pub Task<HashMap<u16, Mesh>>);
+
+/// Marker on the child entities spawned by [`apply_lsystem_mesh_tasks`],
+/// recording which material bucket each one renders.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LSystemMeshPart {
+    pub material_id: u16,
+}
+
+/// Update system that polls every [`LSystemMeshTask`] and, for each finished
+/// one, spawns `Mesh3d` + `MeshMaterial3d` children using the
+/// [`MaterialPalette`] (falling back to its primary material for unknown IDs).
+///
+/// When a [`WindPalette`] is present, buckets whose slot has a wind material
+/// and whose mesh carries the hierarchy attributes use that material instead.
+pub fn apply_lsystem_mesh_tasks(
+    mut commands: Commands,
+    mut tasks: Query<(Entity, &mut LSystemMeshTask)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    palette: Res<MaterialPalette>,
+    wind_palette: Option<Res<WindPalette>>,
+) {
+    for (entity, mut task) in tasks.iter_mut() {
+        let Some(mesh_map) = block_on(future::poll_once(&mut task.0)) else {
+            continue;
+        };
+
+        commands.entity(entity).remove::<LSystemMeshTask>();
+        for (material_id, mesh) in mesh_map {
+            let wind_material = wind_palette
+                .as_ref()
+                .and_then(|wind| wind.materials.get(&material_id))
+                .filter(|_| mesh.attribute(ATTRIBUTE_ROOT_DISTANCE).is_some())
+                .cloned();
+            let mut part = commands.spawn((
+                Mesh3d(meshes.add(mesh)),
+                LSystemMeshPart { material_id },
+                ChildOf(entity),
+            ));
+            match wind_material {
+                Some(material) => part.insert(MeshMaterial3d(material)),
+                None => part.insert(MeshMaterial3d(
+                    palette
+                        .materials
+                        .get(&material_id)
+                        .unwrap_or(&palette.primary_material)
+                        .clone(),
+                )),
+            };
+        }
+    }
+}
@@ -5,23 +5,519 @@
 //! twist-free geometry.
 
 use bevy::asset::RenderAssetUsages;
-use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::mesh::{
+    Indices, MeshVertexAttribute, PrimitiveTopology, VertexAttributeValues, VertexFormat,
+};
 use bevy::platform::collections::HashMap;
+use bevy::platform::collections::hash_map::Entry;
 use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, ComputeTaskPool, Task, TaskPool};
+use std::collections::{BTreeMap, BTreeSet};
 use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::Range;
+use std::sync::Arc;
 use symbios_turtle_3d::{Skeleton, SkeletonPoint};
 
+use crate::diagnostics::{BuildError, BuildReport, ClampedOption, MaterialStats};
+use crate::displacement::Displacement;
+use crate::picking::{SkeletonLocation, SkeletonMap};
+
+/// Index of the skeleton strand a vertex was generated from.
+///
+/// This and the other hierarchy attributes are only present when
+/// [`LSystemMeshBuilder::with_hierarchy_attributes`] is enabled. Bind them in a
+/// custom material's `specialize` with
+/// [`MeshVertexAttribute::at_shader_location`].
+pub const ATTRIBUTE_STRAND_INDEX: MeshVertexAttribute =
+    MeshVertexAttribute::new("Vertex_StrandIndex", 1_406_201_517, VertexFormat::Uint32);
+
+/// Number of [`Skeleton::strand_parents`] links between a vertex's strand and
+/// its root strand (0 for the trunk).
+pub const ATTRIBUTE_BRANCH_DEPTH: MeshVertexAttribute =
+    MeshVertexAttribute::new("Vertex_BranchDepth", 1_406_201_518, VertexFormat::Uint32);
+
+/// Arc length along the vertex's strand, normalized to 0 at its base and 1 at
+/// its tip.
+pub const ATTRIBUTE_STRAND_PROGRESS: MeshVertexAttribute = MeshVertexAttribute::new(
+    "Vertex_StrandProgress",
+    1_406_201_519,
+    VertexFormat::Float32,
+);
+
+/// Arc length from the skeleton's root to the vertex, following
+/// [`Skeleton::strand_parents`] down to the trunk.
+pub const ATTRIBUTE_ROOT_DISTANCE: MeshVertexAttribute =
+    MeshVertexAttribute::new("Vertex_RootDistance", 1_406_201_520, VertexFormat::Float32);
+
+/// Skeleton radius at the vertex's ring (or card point).
+pub const ATTRIBUTE_BRANCH_RADIUS: MeshVertexAttribute =
+    MeshVertexAttribute::new("Vertex_BranchRadius", 1_406_201_521, VertexFormat::Float32);
+
+/// When the vertex appears during growth: its root distance divided by the
+/// longest root distance in the skeleton, so 0 at the root and 1 at the
+/// farthest tip.
+///
+/// This and the other growth attributes are only present when
+/// [`LSystemMeshBuilder::with_growth_attributes`] is enabled; see
+/// [`crate::growth`] for the material that consumes them.
+pub const ATTRIBUTE_BIRTH: MeshVertexAttribute =
+    MeshVertexAttribute::new("Vertex_Birth", 1_406_201_522, VertexFormat::Float32);
+
+/// Skeleton point at the center of the vertex's ring (or the base of its card),
+/// in mesh space.
+pub const ATTRIBUTE_SPINE: MeshVertexAttribute =
+    MeshVertexAttribute::new("Vertex_Spine", 1_406_201_523, VertexFormat::Float32x3);
+
+/// The preceding point along the strand in `xyz` and its [`ATTRIBUTE_BIRTH`]
+/// in `w`. Equal to the spine and birth for a strand's first point and for
+/// cards, so the growth front can be placed between two points.
+pub const ATTRIBUTE_PREVIOUS_SPINE: MeshVertexAttribute = MeshVertexAttribute::new(
+    "Vertex_PreviousSpine",
+    1_406_201_524,
+    VertexFormat::Float32x4,
+);
+
+/// Material ID of the bucket the vertex came from. Only present on meshes
+/// from [`LSystemMeshBuilder::build_merged`]; see [`crate::merged`] for the
+/// material that consumes it.
+pub const ATTRIBUTE_MATERIAL_INDEX: MeshVertexAttribute =
+    MeshVertexAttribute::new("Vertex_MaterialIndex", 1_406_201_525, VertexFormat::Uint32);
+
 // Helper struct to build a single mesh
-#[derive(Default)]
+#[derive(Debug, Default, Clone)]
 struct MeshData {
     positions: Vec<Vec3>,
     normals: Vec<Vec3>,
     colors: Vec<[f32; 4]>,
     uvs: Vec<[f32; 2]>,
     indices: Vec<u32>,
+    /// Per-vertex hierarchy attributes; left empty unless enabled.
+    hierarchy: Vec<VertexHierarchy>,
+    /// Per-vertex growth attributes; left empty unless enabled.
+    growth: Vec<VertexGrowth>,
+    /// Per-vertex joint indices and weights; left empty unless skinning.
+    joints: Vec<VertexJoints>,
+    /// Per-vertex material IDs; left empty unless merging buckets.
+    material_ids: Vec<u32>,
+    /// Skeleton point behind each run of triangles.
+    skeleton_map: SkeletonMap,
+    /// First triangle of every lightmap chart, ascending.
+    charts: Vec<u32>,
+    /// Per-vertex lightmap UVs; left empty unless enabled.
+    lightmap_uvs: Vec<[f32; 2]>,
+}
+
+/// Values written to the hierarchy attributes of one vertex.
+#[derive(Debug, Clone, Copy)]
+struct VertexHierarchy {
+    strand: u32,
+    depth: u32,
+    progress: f32,
+    root_distance: f32,
+    radius: f32,
+}
+
+/// Values written to the growth attributes of one vertex.
+#[derive(Debug, Clone, Copy)]
+struct VertexGrowth {
+    birth: f32,
+    spine: Vec3,
+    previous: Vec4,
+}
+
+/// Up to four skinning joints and their weights for one vertex.
+#[derive(Debug, Clone, Copy, Default)]
+struct VertexJoints {
+    indices: [u16; 4],
+    weights: [f32; 4],
+}
+
+/// Joint layout of one strand for skinned builds: the global index of its
+/// first joint and the arc length (along the unprocessed strand) at which each
+/// of its joints starts. The first joint always starts at 0.
+///
+/// `build_skinned` keeps the total joint count within
+/// [`MAX_JOINTS`](crate::skinning::MAX_JOINTS), so `first + origins.len()`
+/// always fits in a `u16`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StrandJoints {
+    pub(crate) first: u16,
+    pub(crate) origins: Vec<f32>,
+    pub(crate) length: f32,
+}
+
+impl StrandJoints {
+    /// Joint weights at `arc` along the strand. Each vertex is bound to its
+    /// joint, blending linearly towards the neighbouring joint over the half
+    /// of the span closest to it; at a joint origin the two share 50/50.
+    fn weights(&self, arc: f32) -> VertexJoints {
+        let last = self.origins.len().saturating_sub(1);
+        let j = self.origins.iter().rposition(|&o| o <= arc).unwrap_or(0);
+        let start = self.origins[j];
+        let end = self.origins.get(j + 1).copied().unwrap_or(self.length);
+        let t = if end > start {
+            ((arc - start) / (end - start)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let own = self.first + j as u16;
+        let (other, blend) = if t < 0.5 && j > 0 {
+            (own - 1, 0.5 - t)
+        } else if t > 0.5 && j < last {
+            (own + 1, t - 0.5)
+        } else {
+            (own, 0.0)
+        };
+        VertexJoints {
+            indices: [own, other, 0, 0],
+            weights: [1.0 - blend, blend, 0.0, 0.0],
+        }
+    }
+}
+
+/// Per-strand inputs for [`VertexHierarchy`] and [`VertexGrowth`]: the
+/// strand's index, its depth, the root distance of its base point, and the
+/// longest root distance in the whole skeleton.
+#[derive(Debug, Clone, Copy, Default)]
+struct StrandHierarchy {
+    index: u32,
+    depth: u32,
+    base_distance: f32,
+    reach: f32,
+}
+
+impl StrandHierarchy {
+    /// Normalized birth time of a point `arc` along this strand.
+    fn birth(&self, arc: f32) -> f32 {
+        if self.reach > 0.0 {
+            ((self.base_distance + arc) / self.reach).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
 }
 
 impl MeshData {
+    /// Appends `other`'s vertices and triangles, re-basing its indices.
+    fn append(&mut self, other: MeshData) {
+        let offset = self.positions.len() as u32;
+        let triangles = (self.indices.len() / 3) as u32;
+        self.skeleton_map.append(other.skeleton_map, triangles);
+        self.charts
+            .extend(other.charts.into_iter().map(|start| start + triangles));
+        self.positions.extend(other.positions);
+        self.normals.extend(other.normals);
+        self.colors.extend(other.colors);
+        self.uvs.extend(other.uvs);
+        self.hierarchy.extend(other.hierarchy);
+        self.growth.extend(other.growth);
+        self.joints.extend(other.joints);
+        self.material_ids.extend(other.material_ids);
+        self.lightmap_uvs.extend(other.lightmap_uvs);
+        self.indices
+            .extend(other.indices.into_iter().map(|i| i + offset));
+    }
+
+    /// Starts a new lightmap chart with the triangles added from now on.
+    fn chart(&mut self) {
+        let start = (self.indices.len() / 3) as u32;
+        if self.charts.last() != Some(&start) {
+            self.charts.push(start);
+        }
+    }
+
+    /// Attributes the triangles added from now on to `point` of `strand`.
+    fn mark(&mut self, strand: usize, point: usize) {
+        self.skeleton_map.push(
+            (self.indices.len() / 3) as u32,
+            SkeletonLocation {
+                strand: strand as u32,
+                point: point as u32,
+            },
+        );
+    }
+
+    /// The finished [`SkeletonMap`] for this bucket's triangles.
+    fn skeleton_map(&self) -> SkeletonMap {
+        self.skeleton_map
+            .clone()
+            .finish((self.indices.len() / 3) as u32)
+    }
+
+    /// Gives every triangle its own vertices with normals per `shading`,
+    /// keeping triangle order and every other attribute.
+    fn facet(&mut self, shading: Shading) {
+        let min_cos = match shading {
+            Shading::Smooth => return,
+            Shading::Flat => 1.0,
+            Shading::AutoSmooth { angle } => angle.cos(),
+        };
+        let corners = std::mem::take(&mut self.indices);
+        let corner_position = |corner: usize| self.positions[corners[corner] as usize];
+        let faces: Vec<Vec3> = corners
+            .chunks_exact(3)
+            .map(|t| {
+                let [a, b, c] = [t[0], t[1], t[2]].map(|i| self.positions[i as usize]);
+                (b - a).cross(c - a).normalize_or_zero()
+            })
+            .collect();
+        // Neighbouring faces are weighted by their angle at the shared corner,
+        // so how a quad was split into triangles does not bias the result.
+        let key = |p: Vec3| (p * 1e5).round().to_array().map(|v| v as i64);
+        let mut around: HashMap<[i64; 3], Vec<(usize, f32)>> = HashMap::new();
+        if min_cos < 1.0 {
+            for corner in 0..corners.len() {
+                let first = corner - corner % 3;
+                let p = corner_position(corner);
+                let next = corner_position(first + (corner + 1) % 3);
+                let prev = corner_position(first + (corner + 2) % 3);
+                around
+                    .entry(key(p))
+                    .or_default()
+                    .push((corner / 3, (next - p).angle_between(prev - p)));
+            }
+        }
+
+        let normals = (0..corners.len())
+            .map(|corner| {
+                let own = faces[corner / 3];
+                if own == Vec3::ZERO {
+                    return self.normals[corners[corner] as usize];
+                }
+                let Some(neighbours) = around.get(&key(corner_position(corner))) else {
+                    return own;
+                };
+                neighbours
+                    .iter()
+                    .filter(|&&(face, angle)| angle > 0.0 && faces[face].dot(own) >= min_cos - 1e-6)
+                    .map(|&(face, angle)| faces[face] * angle)
+                    .sum::<Vec3>()
+                    .normalize_or(own)
+            })
+            .collect();
+
+        fn gather<T: Copy>(values: &mut Vec<T>, corners: &[u32]) {
+            if !values.is_empty() {
+                *values = corners.iter().map(|&v| values[v as usize]).collect();
+            }
+        }
+        gather(&mut self.positions, &corners);
+        self.normals = normals;
+        gather(&mut self.colors, &corners);
+        gather(&mut self.uvs, &corners);
+        gather(&mut self.hierarchy, &corners);
+        gather(&mut self.growth, &corners);
+        gather(&mut self.joints, &corners);
+        gather(&mut self.material_ids, &corners);
+        gather(&mut self.lightmap_uvs, &corners);
+        self.indices = (0..corners.len() as u32).collect();
+    }
+
+    /// Maps the UVs of every vertex with an `atlases` rectangle into it. U is
+    /// first wrapped into the repeat its triangles lie in and clamped to
+    /// `0..1`; V is scaled but left unwrapped.
+    fn map_to_atlas(&mut self, atlases: &[Option<Rect>]) {
+        let mut repeats: Vec<Option<f32>> = vec![None; self.uvs.len()];
+        for t in self.indices.chunks_exact(3) {
+            let centre = t.iter().map(|&v| self.uvs[v as usize][0]).sum::<f32>() / 3.0;
+            for &v in t {
+                repeats[v as usize].get_or_insert(centre.floor());
+            }
+        }
+        for ((uv, rect), repeat) in self.uvs.iter_mut().zip(atlases).zip(repeats) {
+            let Some(rect) = rect else {
+                continue;
+            };
+            let u = (uv[0] - repeat.unwrap_or(uv[0].floor())).clamp(0.0, 1.0);
+            *uv = [
+                rect.min.x + u * rect.width(),
+                rect.min.y + uv[1] * rect.height(),
+            ];
+        }
+    }
+
+    /// Appends a copy of vertex `v` with all its attributes.
+    fn duplicate_vertex(&mut self, v: u32) -> u32 {
+        fn copy<T: Copy>(values: &mut Vec<T>, v: u32) {
+            if let Some(&value) = values.get(v as usize) {
+                values.push(value);
+            }
+        }
+        copy(&mut self.positions, v);
+        copy(&mut self.normals, v);
+        copy(&mut self.colors, v);
+        copy(&mut self.uvs, v);
+        copy(&mut self.hierarchy, v);
+        copy(&mut self.growth, v);
+        copy(&mut self.joints, v);
+        copy(&mut self.material_ids, v);
+        copy(&mut self.lightmap_uvs, v);
+        self.positions.len() as u32 - 1
+    }
+
+    /// Lays every chart out without overlap in `0..1` and writes the result
+    /// to [`Self::lightmap_uvs`], keeping at least `padding` between charts
+    /// and to the border.
+    ///
+    /// Each chart keeps the shape of its `uvs`, scaled to its world-space
+    /// area so texel density is uniform, and is turned so its long side runs
+    /// along U. Vertices shared by several charts are duplicated first.
+    fn unwrap_lightmap(&mut self, padding: f32) {
+        let triangles = (self.indices.len() / 3) as u32;
+        let mut starts = self.charts.clone();
+        if starts.first() != Some(&0) {
+            starts.insert(0, 0);
+        }
+        starts.retain(|&start| start < triangles);
+        let ends: Vec<u32> = starts.iter().skip(1).copied().chain([triangles]).collect();
+
+        // Split vertices shared between charts.
+        let mut owner: Vec<Option<usize>> = vec![None; self.positions.len()];
+        for (chart, (&start, &end)) in starts.iter().zip(&ends).enumerate() {
+            let mut copies: HashMap<u32, u32> = HashMap::new();
+            for corner in start as usize * 3..end as usize * 3 {
+                let v = self.indices[corner];
+                match owner[v as usize] {
+                    None => owner[v as usize] = Some(chart),
+                    Some(c) if c == chart => {}
+                    Some(_) => {
+                        let copy = match copies.get(&v) {
+                            Some(&copy) => copy,
+                            None => {
+                                let copy = self.duplicate_vertex(v);
+                                owner.push(Some(chart));
+                                copies.insert(v, copy);
+                                copy
+                            }
+                        };
+                        self.indices[corner] = copy;
+                    }
+                }
+            }
+        }
+
+        // Size every chart in world units.
+        struct Chart {
+            vertices: Vec<u32>,
+            min: Vec2,
+            scale: f32,
+            size: Vec2,
+            turned: bool,
+            origin: Vec2,
+        }
+        let mut charts: Vec<Chart> = starts
+            .iter()
+            .zip(&ends)
+            .map(|(&start, &end)| {
+                let corners = &self.indices[start as usize * 3..end as usize * 3];
+                let mut vertices = corners.to_vec();
+                vertices.sort_unstable();
+                vertices.dedup();
+                let uv = |v: u32| Vec2::from(self.uvs[v as usize]);
+                let (mut world_area, mut uv_area) = (0.0, 0.0);
+                for t in corners.chunks_exact(3) {
+                    let [a, b, c] = [t[0], t[1], t[2]].map(|i| self.positions[i as usize]);
+                    world_area += (b - a).cross(c - a).length() * 0.5;
+                    let [a, b, c] = [t[0], t[1], t[2]].map(uv);
+                    uv_area += (b - a).perp_dot(c - a).abs() * 0.5;
+                }
+                let (min, max) = vertices
+                    .iter()
+                    .map(|&v| uv(v))
+                    .filter(|uv| uv.is_finite())
+                    .fold((Vec2::MAX, Vec2::MIN), |(lo, hi), uv| {
+                        (lo.min(uv), hi.max(uv))
+                    });
+                let scale = if uv_area > 1e-12 && world_area.is_finite() {
+                    (world_area / uv_area).sqrt()
+                } else {
+                    0.0
+                };
+                let size = if min.x <= max.x {
+                    (max - min) * scale
+                } else {
+                    Vec2::ZERO
+                };
+                let turned = size.y > size.x;
+                Chart {
+                    vertices,
+                    min: if min.x <= max.x { min } else { Vec2::ZERO },
+                    scale,
+                    size: if turned { size.yx() } else { size },
+                    turned,
+                    origin: Vec2::ZERO,
+                }
+            })
+            .collect();
+
+        // Shelf-pack tallest first, growing the gap until it is at least
+        // `padding` of the finished layout.
+        let mut order: Vec<usize> = (0..charts.len()).collect();
+        order.sort_by(|&a, &b| charts[b].size.y.total_cmp(&charts[a].size.y));
+        let pack = |charts: &mut [Chart], gap: f32| -> f32 {
+            let area: f32 = charts
+                .iter()
+                .map(|c| (c.size.x + gap) * (c.size.y + gap))
+                .sum();
+            let widest = charts.iter().map(|c| c.size.x).fold(0.0, f32::max);
+            let width = area.sqrt().max(widest + gap);
+            let mut cursor = Vec2::splat(gap);
+            let mut shelf = 0.0f32;
+            let mut right = 0.0f32;
+            for &i in &order {
+                let size = charts[i].size;
+                if cursor.x > gap && cursor.x + size.x > width {
+                    cursor = Vec2::new(gap, cursor.y + shelf + gap);
+                    shelf = 0.0;
+                }
+                charts[i].origin = cursor;
+                cursor.x += size.x + gap;
+                shelf = shelf.max(size.y);
+                right = right.max(cursor.x);
+            }
+            right.max(cursor.y + shelf + gap)
+        };
+        let mut gap = 0.0;
+        let mut side = pack(&mut charts, gap);
+        for _ in 0..16 {
+            if gap >= padding * side {
+                break;
+            }
+            gap = padding * side;
+            side = pack(&mut charts, gap);
+        }
+        let side = if side > 0.0 { side } else { 1.0 };
+
+        self.lightmap_uvs = vec![[0.0, 0.0]; self.positions.len()];
+        for chart in &charts {
+            for &v in &chart.vertices {
+                let local = (Vec2::from(self.uvs[v as usize]) - chart.min) * chart.scale;
+                let local = if chart.turned { local.yx() } else { local };
+                let uv = ((chart.origin + local) / side).clamp(Vec2::ZERO, Vec2::ONE);
+                self.lightmap_uvs[v as usize] = if uv.is_finite() {
+                    uv.to_array()
+                } else {
+                    [0.0, 0.0]
+                };
+            }
+        }
+    }
+
+    /// Assigns `values` to every vertex added since the last call.
+    fn tag(&mut self, values: VertexHierarchy) {
+        self.hierarchy.resize(self.positions.len(), values);
+    }
+
+    /// Assigns growth `values` to every vertex added since the last call.
+    fn tag_growth(&mut self, values: VertexGrowth) {
+        self.growth.resize(self.positions.len(), values);
+    }
+
+    /// Assigns skinning `values` to every vertex added since the last call.
+    fn tag_joints(&mut self, values: VertexJoints) {
+        self.joints.resize(self.positions.len(), values);
+    }
+
     fn to_mesh(&self) -> Mesh {
         let mut mesh = Mesh::new(
             PrimitiveTopology::TriangleList,
@@ -31,79 +527,1447 @@ impl MeshData {
         mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals.clone());
         mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, self.colors.clone());
         mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs.clone());
+        if !self.hierarchy.is_empty() {
+            let column = |f: fn(&VertexHierarchy) -> f32| -> Vec<f32> {
+                self.hierarchy.iter().map(f).collect()
+            };
+            mesh.insert_attribute(
+                ATTRIBUTE_STRAND_INDEX,
+                self.hierarchy
+                    .iter()
+                    .map(|h| h.strand)
+                    .collect::<Vec<u32>>(),
+            );
+            mesh.insert_attribute(
+                ATTRIBUTE_BRANCH_DEPTH,
+                self.hierarchy.iter().map(|h| h.depth).collect::<Vec<u32>>(),
+            );
+            mesh.insert_attribute(ATTRIBUTE_STRAND_PROGRESS, column(|h| h.progress));
+            mesh.insert_attribute(ATTRIBUTE_ROOT_DISTANCE, column(|h| h.root_distance));
+            mesh.insert_attribute(ATTRIBUTE_BRANCH_RADIUS, column(|h| h.radius));
+        }
+        if !self.growth.is_empty() {
+            mesh.insert_attribute(
+                ATTRIBUTE_BIRTH,
+                self.growth.iter().map(|g| g.birth).collect::<Vec<f32>>(),
+            );
+            mesh.insert_attribute(
+                ATTRIBUTE_SPINE,
+                self.growth.iter().map(|g| g.spine).collect::<Vec<Vec3>>(),
+            );
+            mesh.insert_attribute(
+                ATTRIBUTE_PREVIOUS_SPINE,
+                self.growth
+                    .iter()
+                    .map(|g| g.previous.to_array())
+                    .collect::<Vec<[f32; 4]>>(),
+            );
+        }
+        if !self.joints.is_empty() {
+            mesh.insert_attribute(
+                Mesh::ATTRIBUTE_JOINT_INDEX,
+                VertexAttributeValues::Uint16x4(self.joints.iter().map(|j| j.indices).collect()),
+            );
+            mesh.insert_attribute(
+                Mesh::ATTRIBUTE_JOINT_WEIGHT,
+                self.joints
+                    .iter()
+                    .map(|j| j.weights)
+                    .collect::<Vec<[f32; 4]>>(),
+            );
+        }
+        if !self.material_ids.is_empty() {
+            mesh.insert_attribute(ATTRIBUTE_MATERIAL_INDEX, self.material_ids.clone());
+        }
+        if !self.lightmap_uvs.is_empty() {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_UV_1, self.lightmap_uvs.clone());
+        }
         mesh.insert_indices(Indices::U32(self.indices.clone()));
         let _ = mesh.generate_tangents();
         mesh
     }
-}
+}
+
+/// How the open end of a strand's tube is closed off.
+///
+/// Selected independently for the first and last ring of every strand via
+/// [`LSystemMeshBuilder::with_start_cap`] / [`LSystemMeshBuilder::with_end_cap`].
+/// Cap geometry lands in the same material bucket as the segment it closes and
+/// inherits that end point's color.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CapStyle {
+    /// Leave the tube open (no extra geometry).
+    #[default]
+    Open,
+    /// A flat disc with a hard edge. UVs are a planar projection at the same
+    /// texel density as the tube wall.
+    Flat,
+    /// A hemispherical dome sharing the tube's end ring, so shading stays
+    /// smooth across the seam. V keeps increasing with arc length over the dome.
+    Dome,
+    /// A cone tapering to a point. `length` is the apex distance beyond the
+    /// end ring, as a multiple of the end radius.
+    Point { length: f32 },
+}
+
+/// How a child strand's base is joined to the parent strand it sprouts from.
+///
+/// Uses [`Skeleton::strand_parents`] to find the parent tube. Strands that
+/// continue roughly parallel to their parent (e.g. the trunk resuming after a
+/// `]`) and strands whose base lies outside the parent tube are left as-is.
+/// Forks are seated, not stitched: the parent tube is not perforated, welding
+/// only seats the child's base ring on the parent surface.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum JunctionStyle {
+    /// Mesh every strand independently; child tubes start on the parent's axis.
+    #[default]
+    Disjoint,
+    /// Slide each vertex of the child's base ring along the child tangent until
+    /// it meets the parent tube surface.
+    Weld,
+    /// Like [`Weld`](Self::Weld), but the base ring is first widened by
+    /// `1.0 + flare` and an extra collar ring restores the child's own radius
+    /// just outside the parent, giving a smooth fillet at the fork.
+    Blend { flare: f32 },
+}
+
+/// Local approximation of a parent tube near a child's base: a straight axis
+/// through `origin` along `dir` with constant `radius`. `sin_angle` is the sine
+/// of the angle between the child's first segment and that axis.
+#[derive(Debug, Clone, Copy)]
+struct ParentTube {
+    origin: Vec3,
+    dir: Vec3,
+    radius: f32,
+    sin_angle: f32,
+}
+
+/// Child tangents closer than this (squared sine of the angle) to the parent
+/// axis are treated as continuations rather than branches.
+const JUNCTION_MIN_SIN_SQ: f32 = 0.01;
+
+/// Curve used to refine strands before meshing.
+///
+/// Set with [`LSystemMeshBuilder::with_smoothing`]. Both curves insert
+/// `subdivisions` extra points per original segment and interpolate radius,
+/// color, and `uv_scale` alongside position. Material boundaries stay on the
+/// original points' segments.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum StrandSmoothing {
+    /// Mesh the raw skeleton points with straight segments.
+    #[default]
+    None,
+    /// Centripetal Catmull-Rom spline through every original point. Attributes
+    /// are interpolated linearly so radii never overshoot.
+    CatmullRom { subdivisions: u32 },
+    /// Quadratic Bézier pieces between segment midpoints, using each interior
+    /// point as the control point. Smoother than Catmull-Rom, but only the two
+    /// strand endpoints are interpolated exactly.
+    Bezier { subdivisions: u32 },
+}
+
+/// Maximum number of points inserted per segment by [`StrandSmoothing`].
+const MAX_SUBDIVISIONS: u32 = 16;
+
+/// Shape of the rings swept along each strand.
+///
+/// Profiles are defined at unit radius in the ring's local XZ plane and scaled
+/// by each point's radius. Select one for every strand with
+/// [`LSystemMeshBuilder::with_cross_section`], or per material with
+/// [`LSystemMeshBuilder::with_material_cross_section`]. U runs around the
+/// ring proportionally to perimeter length, so texels stay evenly spaced on
+/// uneven shapes.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum CrossSection {
+    /// A smooth circle with the builder's ring resolution.
+    #[default]
+    Circle,
+    /// A regular polygon with `sides` corners on the unit circle and flat
+    /// (hard-edged) faces. Ignores the ring resolution.
+    Polygon { sides: u32 },
+    /// A star with `points` tips on the unit circle and inner corners at
+    /// `inner_radius`. Every corner is a hard edge.
+    Star { points: u32, inner_radius: f32 },
+    /// A closed polyline (the last point connects back to the first). Either
+    /// winding is accepted. Corners where the outline turns by more than
+    /// `crease_angle` radians get hard edges; gentler corners are smoothed.
+    Custom {
+        points: Vec<Vec2>,
+        crease_angle: f32,
+    },
+}
+
+impl CrossSection {
+    /// Clamps parameters into a usable range. Degenerate custom outlines fall
+    /// back to [`CrossSection::Circle`] with a warning.
+    fn sanitized(self) -> Self {
+        match self {
+            Self::Polygon { sides } => {
+                if sides > MAX_RESOLUTION {
+                    warn!(
+                        "Polygon sides {} exceed maximum of {}; clamping to {}",
+                        sides, MAX_RESOLUTION, MAX_RESOLUTION
+                    );
+                }
+                Self::Polygon {
+                    sides: sides.clamp(3, MAX_RESOLUTION),
+                }
+            }
+            Self::Star {
+                points,
+                inner_radius,
+            } => {
+                let max_points = MAX_RESOLUTION / 2;
+                if points > max_points {
+                    warn!(
+                        "Star points {} exceed maximum of {}; clamping to {}",
+                        points, max_points, max_points
+                    );
+                }
+                Self::Star {
+                    points: points.clamp(3, max_points),
+                    inner_radius: if inner_radius.is_finite() {
+                        inner_radius.clamp(0.01, 1.0)
+                    } else {
+                        0.5
+                    },
+                }
+            }
+            Self::Custom {
+                points,
+                crease_angle,
+            } => {
+                let mut outline: Vec<Vec2> = Vec::with_capacity(points.len());
+                for p in points.into_iter().filter(|p| p.is_finite()) {
+                    if outline
+                        .last()
+                        .is_none_or(|last| last.distance_squared(p) > 1e-10)
+                    {
+                        outline.push(p);
+                    }
+                }
+                while outline.len() > 1
+                    && outline[0].distance_squared(outline[outline.len() - 1]) <= 1e-10
+                {
+                    outline.pop();
+                }
+                let area = signed_area(&outline);
+                if outline.len() < 3 || area.abs() <= 1e-6 {
+                    warn!("Custom cross-section is degenerate; falling back to a circle");
+                    return Self::Circle;
+                }
+                if outline.len() > MAX_RESOLUTION as usize {
+                    warn!(
+                        "Custom cross-section has {} points, exceeding maximum of {}; falling back to a circle",
+                        outline.len(),
+                        MAX_RESOLUTION
+                    );
+                    return Self::Circle;
+                }
+                if area < 0.0 {
+                    outline.reverse();
+                }
+                Self::Custom {
+                    points: outline,
+                    crease_angle: if crease_angle.is_finite() {
+                        crease_angle.clamp(0.0, std::f32::consts::PI)
+                    } else {
+                        0.0
+                    },
+                }
+            }
+            Self::Circle => Self::Circle,
+        }
+    }
+
+    /// Numeric parameters by name, for clamp reports.
+    fn params(&self) -> Vec<(&'static str, f64)> {
+        match self {
+            Self::Circle => Vec::new(),
+            Self::Polygon { sides } => vec![("sides", f64::from(*sides))],
+            Self::Star {
+                points,
+                inner_radius,
+            } => vec![
+                ("points", f64::from(*points)),
+                ("inner_radius", f64::from(*inner_radius)),
+            ],
+            Self::Custom { crease_angle, .. } => vec![("crease_angle", f64::from(*crease_angle))],
+        }
+    }
+
+    /// Resolves the profile into ring vertices. `res` is only used by circles.
+    fn ring_profile(&self, res: u32) -> RingProfile {
+        match self {
+            Self::Circle => RingProfile::circle(res),
+            Self::Polygon { sides } => {
+                let corners: Vec<Vec2> = (0..*sides)
+                    .map(|i| Vec2::from_angle(i as f32 / *sides as f32 * std::f32::consts::TAU))
+                    .collect();
+                RingProfile::polyline(&corners, 0.0)
+            }
+            Self::Star {
+                points,
+                inner_radius,
+            } => {
+                let count = points * 2;
+                let corners: Vec<Vec2> = (0..count)
+                    .map(|i| {
+                        let r = if i % 2 == 0 { 1.0 } else { *inner_radius };
+                        Vec2::from_angle(i as f32 / count as f32 * std::f32::consts::TAU) * r
+                    })
+                    .collect();
+                RingProfile::polyline(&corners, 0.0)
+            }
+            Self::Custom {
+                points,
+                crease_angle,
+            } => RingProfile::polyline(points, *crease_angle),
+        }
+    }
+
+    fn hash_into<H: Hasher>(&self, hasher: &mut H) {
+        match self {
+            Self::Circle => 0u8.hash(hasher),
+            Self::Polygon { sides } => {
+                1u8.hash(hasher);
+                sides.hash(hasher);
+            }
+            Self::Star {
+                points,
+                inner_radius,
+            } => {
+                2u8.hash(hasher);
+                points.hash(hasher);
+                inner_radius.to_bits().hash(hasher);
+            }
+            Self::Custom {
+                points,
+                crease_angle,
+            } => {
+                3u8.hash(hasher);
+                points.len().hash(hasher);
+                for p in points {
+                    p.x.to_bits().hash(hasher);
+                    p.y.to_bits().hash(hasher);
+                }
+                crease_angle.to_bits().hash(hasher);
+            }
+        }
+    }
+}
+
+/// Shoelace area of a closed outline; positive when counter-clockwise.
+fn signed_area(points: &[Vec2]) -> f32 {
+    let n = points.len();
+    (0..n)
+        .map(|i| points[i].perp_dot(points[(i + 1) % n]))
+        .sum::<f32>()
+        * 0.5
+}
+
+/// One vertex of a [`RingProfile`] at unit radius.
+#[derive(Debug, Clone, Copy)]
+struct ProfileVertex {
+    position: Vec2,
+    normal: Vec2,
+    u: f32,
+}
+
+/// A [`CrossSection`] resolved into the concrete vertices of one ring.
+///
+/// The first and last vertex coincide at the U seam. Hard corners are split
+/// into two vertices with the normals of their adjacent faces, so a face is
+/// always the span between `faces[i]` and the vertex after it.
+#[derive(Debug, Clone)]
+struct RingProfile {
+    vertices: Vec<ProfileVertex>,
+    faces: Vec<u32>,
+    /// Outline length at unit radius (`TAU` for a circle).
+    perimeter: f32,
+}
+
+impl RingProfile {
+    fn circle(res: u32) -> Self {
+        let vertices = (0..=res)
+            .map(|i| {
+                let u = i as f32 / res as f32;
+                let (sin, cos) = (u * std::f32::consts::TAU).sin_cos();
+                ProfileVertex {
+                    position: Vec2::new(cos, sin),
+                    normal: Vec2::new(cos, sin),
+                    u,
+                }
+            })
+            .collect();
+        Self {
+            vertices,
+            faces: (0..res).collect(),
+            perimeter: std::f32::consts::TAU,
+        }
+    }
+
+    /// Builds a ring from a counter-clockwise outline.
+    fn polyline(corners: &[Vec2], crease_angle: f32) -> Self {
+        let k = corners.len();
+        let edge = |j: usize| corners[(j + 1) % k] - corners[j % k];
+        let outward = |j: usize| {
+            let d = edge(j);
+            Vec2::new(d.y, -d.x).normalize_or_zero()
+        };
+        let perimeter: f32 = (0..k).map(|j| edge(j).length()).sum();
+        let min_dot = crease_angle.cos();
+
+        let mut vertices = Vec::with_capacity(2 * k + 1);
+        let mut faces = Vec::with_capacity(k);
+        let mut travelled = 0.0;
+        for j in 0..=k {
+            let position = corners[j % k];
+            let n_in = outward(j + k - 1);
+            let n_out = outward(j);
+            let hard = n_in.dot(n_out) < min_dot;
+            let smooth = (n_in + n_out).normalize_or(n_out);
+            let u = if j == k { 1.0 } else { travelled / perimeter };
+
+            if j > 0 {
+                let normal = if hard { n_in } else { smooth };
+                vertices.push(ProfileVertex {
+                    position,
+                    normal,
+                    u,
+                });
+            }
+            if j < k {
+                if j == 0 || hard {
+                    let normal = if hard { n_out } else { smooth };
+                    vertices.push(ProfileVertex {
+                        position,
+                        normal,
+                        u,
+                    });
+                }
+                faces.push(vertices.len() as u32 - 1);
+                travelled += edge(j).length();
+            }
+        }
+
+        Self {
+            vertices,
+            faces,
+            perimeter,
+        }
+    }
+
+    /// A flat strip two units wide along the local X axis, facing +Z. The
+    /// vertex order gives front faces on +Z with U running from -X to +X.
+    fn ribbon() -> Self {
+        let normal = Vec2::new(0.0, 1.0);
+        Self {
+            vertices: vec![
+                ProfileVertex {
+                    position: Vec2::new(1.0, 0.0),
+                    normal,
+                    u: 1.0,
+                },
+                ProfileVertex {
+                    position: Vec2::new(-1.0, 0.0),
+                    normal,
+                    u: 0.0,
+                },
+            ],
+            faces: vec![0],
+            perimeter: 2.0,
+        }
+    }
+
+    /// Repeats U `repeats` times around the ring. With `split`, a seam is
+    /// added at every repeat boundary so each repeat can be mapped into an
+    /// atlas rectangle on its own. The perimeter becomes the outline length
+    /// per repeat, which keeps V in step.
+    fn tiled(&self, repeats: u32, split: bool) -> Self {
+        let n = repeats as f32;
+        if !split {
+            let mut tiled = self.clone();
+            for vertex in &mut tiled.vertices {
+                vertex.u *= n;
+            }
+            tiled.perimeter /= n;
+            return tiled;
+        }
+
+        let mut vertices: Vec<ProfileVertex> = Vec::new();
+        let mut faces = Vec::with_capacity(self.faces.len());
+        for &face in &self.faces {
+            let (a, b) = (
+                self.vertices[face as usize],
+                self.vertices[face as usize + 1],
+            );
+            let (ga, gb) = (a.u * n, b.u * n);
+            // Split points as (fraction along the face, global U).
+            let mut cuts = vec![(0.0, ga), (1.0, gb)];
+            let mut k = ga.min(gb).floor() + 1.0;
+            while k < ga.max(gb) {
+                cuts.push(((k - ga) / (gb - ga), k));
+                k += 1.0;
+            }
+            cuts.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+            let at = |(t, u): (f32, f32)| ProfileVertex {
+                position: a.position.lerp(b.position, t),
+                normal: a.normal.lerp(b.normal, t).normalize_or(a.normal),
+                u,
+            };
+            for span in cuts.windows(2) {
+                let (start, end) = (at(span[0]), at(span[1]));
+                // U stays global here and is wrapped per triangle once the
+                // mesh is finished, so repeat boundaries (integer U) always
+                // get a vertex on each side.
+                let shared = vertices.last().is_some_and(|v| {
+                    v.position == start.position
+                        && v.normal == start.normal
+                        && v.u == start.u
+                        && start.u.fract() != 0.0
+                });
+                if !shared {
+                    vertices.push(start);
+                }
+                faces.push(vertices.len() as u32 - 1);
+                vertices.push(end);
+            }
+        }
+        Self {
+            vertices,
+            faces,
+            perimeter: self.perimeter / n,
+        }
+    }
+
+    /// Distance from the axis to the tangent line at `v` (1 on a circle).
+    /// Used to tilt cone-cap normals so flat faces stay flat.
+    fn support(v: &ProfileVertex) -> f32 {
+        v.position.dot(v.normal).max(0.0)
+    }
+}
+
+/// Per-strand ring resolution derived from the strand's radius. The upper
+/// bound is the builder's regular resolution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AdaptiveResolution {
+    min: u32,
+    edge_length: f32,
+}
+
+/// Geometry emitted for skeleton points whose material is registered with
+/// [`LSystemMeshBuilder::with_cards`], for use with alpha-tested foliage
+/// textures such as `Leaf`, `Twig`, or `Flower` cards.
+///
+/// Each card is a square `size` times the point's radius on a side. It grows
+/// from the point along the turtle's up (+Y) axis and spans the turtle's X
+/// axis, with U running left to right and V from the tip (0) to the base (1).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CardStyle {
+    /// A single quad in the turtle's XY plane, facing +Z. Pair it with a
+    /// double-sided material.
+    Quad { size: f32 },
+    /// Two quads crossed at right angles around the turtle's up axis, so the
+    /// card reads from every viewing direction.
+    Crossed { size: f32 },
+}
+
+impl CardStyle {
+    fn size(&self) -> f32 {
+        match *self {
+            Self::Quad { size } | Self::Crossed { size } => size,
+        }
+    }
+
+    fn sanitized(self) -> Self {
+        let clamp = |size: f32| if size.is_finite() { size.max(0.0) } else { 0.0 };
+        match self {
+            Self::Quad { size } => Self::Quad { size: clamp(size) },
+            Self::Crossed { size } => Self::Crossed { size: clamp(size) },
+        }
+    }
+
+    fn hash_into<H: Hasher>(&self, hasher: &mut H) {
+        match self {
+            Self::Quad { size } => {
+                0u8.hash(hasher);
+                size.to_bits().hash(hasher);
+            }
+            Self::Crossed { size } => {
+                1u8.hash(hasher);
+                size.to_bits().hash(hasher);
+            }
+        }
+    }
+}
+
+/// Which way the strips of [`LSystemMeshBuilder::with_ribbons`] face.
+///
+/// Strips run along the strand and are as wide as its diameter. Like
+/// [`CardStyle::Quad`], they are single-sided: pair them with a double-sided
+/// material.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RibbonFacing {
+    /// Face each point's turtle +Z axis and span its X axis, so the strip
+    /// follows the turtle's roll. Strands drawn in the XY plane, as with
+    /// classic 2D L-systems, face +Z.
+    #[default]
+    Turtle,
+    /// Turn every strip as far towards a fixed direction as its heading
+    /// allows, e.g. towards a static or orthographic camera.
+    Direction(Vec3),
+}
+
+impl RibbonFacing {
+    fn sanitized(self) -> Self {
+        match self {
+            Self::Direction(direction) if direction.normalize_or_zero() == Vec3::ZERO => {
+                warn!(
+                    "Ribbon facing direction {} has no length; using the turtle orientation",
+                    direction
+                );
+                Self::Turtle
+            }
+            Self::Direction(direction) => Self::Direction(direction.normalize()),
+            Self::Turtle => Self::Turtle,
+        }
+    }
+
+    fn hash_into<H: Hasher>(&self, hasher: &mut H) {
+        match self {
+            Self::Turtle => 0u8.hash(hasher),
+            Self::Direction(direction) => {
+                1u8.hash(hasher);
+                direction.to_array().map(f32::to_bits).hash(hasher);
+            }
+        }
+    }
+}
+
+/// Most texture repeats [`MaterialUv`] allows around a tube. With an atlas,
+/// every repeat boundary adds a seam to each ring.
+pub const MAX_U_REPEAT: u32 = 64;
+
+/// Texture-coordinate layout of one material, set with
+/// [`LSystemMeshBuilder::with_material_uv`].
+///
+/// `u_repeat` tiles the texture around tubes (across ribbons) and scales V by
+/// the same factor, so texels stay square. `atlas` maps the material's UVs
+/// into a sub-rectangle of a shared texture: U, and card UVs entirely, land
+/// inside the rectangle, while tube V keeps running along the strand. Give
+/// tube materials rectangles that span the full atlas height (a column
+/// layout) so the sampler's repeat mode tiles them along the strand.
+///
+/// Atlas UVs are final, so leave the material's
+/// [`uv_scale`](crate::materials::MaterialSettings::uv_scale) at `1.0`.
+///
+/// Values are validated when passed to `with_material_uv`, so struct literals
+/// are checked the same way as the `with_*` methods.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaterialUv {
+    pub u_repeat: u32,
+    pub atlas: Option<Rect>,
+}
+
+impl Default for MaterialUv {
+    fn default() -> Self {
+        Self {
+            u_repeat: 1,
+            atlas: None,
+        }
+    }
+}
+
+impl MaterialUv {
+    /// One repeat around the tube, no atlas.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Repeats the texture `repeats` times around the tube.
+    /// [`LSystemMeshBuilder::with_material_uv`] clamps it to between 1 and
+    /// [`MAX_U_REPEAT`] with a warning.
+    pub fn with_u_repeat(mut self, repeats: u32) -> Self {
+        self.u_repeat = repeats;
+        self
+    }
+
+    /// Maps UVs into `rect` of a texture atlas, in UV space.
+    /// [`LSystemMeshBuilder::with_material_uv`] ignores non-finite rectangles
+    /// with a warning and reorders inverted corners.
+    pub fn with_atlas_rect(mut self, rect: Rect) -> Self {
+        self.atlas = Some(rect);
+        self
+    }
+
+    fn sanitized(self) -> Self {
+        let u_repeat = self.u_repeat.clamp(1, MAX_U_REPEAT);
+        if u_repeat != self.u_repeat {
+            warn!(
+                "U repeat count {} out of range; clamping to {}",
+                self.u_repeat, u_repeat
+            );
+        }
+        let atlas = self.atlas.and_then(|rect| {
+            if rect.min.is_finite() && rect.max.is_finite() {
+                Some(Rect::from_corners(rect.min, rect.max))
+            } else {
+                warn!("Atlas rectangle {rect:?} is not finite; ignoring it");
+                None
+            }
+        });
+        Self { u_repeat, atlas }
+    }
+
+    /// Numeric parameters by name, for clamp reports.
+    fn params(&self) -> Vec<(&'static str, f64)> {
+        vec![("u_repeat", f64::from(self.u_repeat))]
+    }
+
+    fn hash_into<H: Hasher>(&self, hasher: &mut H) {
+        self.u_repeat.hash(hasher);
+        self.atlas
+            .map(|rect| [rect.min, rect.max].map(|v| v.to_array().map(f32::to_bits)))
+            .hash(hasher);
+    }
+}
+
+/// How [`LSystemMeshBuilder`] shades tubes, caps, and cards.
+///
+/// The faceted modes give every triangle its own three vertices, so they
+/// roughly triple the vertex count. Colours, UVs, material buckets, and all
+/// optional attributes are carried over unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Shading {
+    /// Shared vertices with smooth radial normals.
+    #[default]
+    Smooth,
+    /// Per-face normals for a low-poly look.
+    Flat,
+    /// Per-face vertices whose normals are averaged with those of neighbouring
+    /// faces at most `angle` radians away, like a DCC "auto smooth". Edges
+    /// between segments of a gently curving branch stay soft while sharp
+    /// bends and caps get hard edges.
+    AutoSmooth { angle: f32 },
+}
+
+impl Shading {
+    fn sanitized(self) -> Self {
+        match self {
+            Self::AutoSmooth { angle } if !angle.is_finite() => {
+                warn!(
+                    "Auto-smooth angle {} is not finite; using flat shading",
+                    angle
+                );
+                Self::Flat
+            }
+            Self::AutoSmooth { angle } => Self::AutoSmooth {
+                angle: angle.clamp(0.0, std::f32::consts::PI),
+            },
+            other => other,
+        }
+    }
+
+    /// Numeric parameters by name, for clamp reports.
+    fn params(&self) -> Vec<(&'static str, f64)> {
+        match self {
+            Self::AutoSmooth { angle } => vec![("angle", f64::from(*angle))],
+            _ => Vec::new(),
+        }
+    }
+
+    fn hash_into<H: Hasher>(&self, hasher: &mut H) {
+        match self {
+            Self::Smooth => 0u8.hash(hasher),
+            Self::Flat => 1u8.hash(hasher),
+            Self::AutoSmooth { angle } => {
+                2u8.hash(hasher);
+                angle.to_bits().hash(hasher);
+            }
+        }
+    }
+}
+
+/// Predicate over `(strand_index, point)` set with
+/// [`LSystemMeshBuilder::with_point_filter`]; `false` skips the point.
+type PointFilter = Arc<dyn Fn(usize, &SkeletonPoint) -> bool + Send + Sync>;
+
+/// Which strands, segments, and cards get meshed. A segment is judged by the
+/// skeleton point it starts at, the same point that picks its material.
+#[derive(Clone, Default)]
+struct StrandFilter {
+    min_radius: f32,
+    max_radius: Option<f32>,
+    include_materials: Option<BTreeSet<u16>>,
+    exclude_materials: BTreeSet<u16>,
+    max_depth: Option<u32>,
+    /// The caller's cache key and the predicate it names.
+    predicate: Option<(u64, PointFilter)>,
+}
+
+impl StrandFilter {
+    fn is_active(&self) -> bool {
+        self.min_radius > 0.0
+            || self.max_radius.is_some()
+            || self.include_materials.is_some()
+            || !self.exclude_materials.is_empty()
+            || self.max_depth.is_some()
+            || self.predicate.is_some()
+    }
+
+    fn keeps_strand(&self, tags: Option<&StrandHierarchy>) -> bool {
+        match (self.max_depth, tags) {
+            (Some(max_depth), Some(tags)) => tags.depth <= max_depth,
+            _ => true,
+        }
+    }
+
+    fn keeps_point(&self, strand_idx: usize, point: &SkeletonPoint) -> bool {
+        let material_id = point.material_id as u16;
+        point.radius >= self.min_radius
+            && self.max_radius.is_none_or(|max| point.radius <= max)
+            && self
+                .include_materials
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&material_id))
+            && !self.exclude_materials.contains(&material_id)
+            && self
+                .predicate
+                .as_ref()
+                .is_none_or(|(_, keep)| keep(strand_idx, point))
+    }
+
+    /// Hashes the settings; a predicate only by its caller-supplied key.
+    fn hash_into<H: Hasher>(&self, hasher: &mut H) {
+        self.min_radius.to_bits().hash(hasher);
+        self.max_radius.map(f32::to_bits).hash(hasher);
+        self.include_materials.hash(hasher);
+        self.exclude_materials.hash(hasher);
+        self.max_depth.hash(hasher);
+        self.predicate.as_ref().map(|(key, _)| key).hash(hasher);
+    }
+}
+
+/// Maximum allowed tube resolution to prevent memory exhaustion.
+/// 128 vertices per ring is more than sufficient for smooth tubes.
+const MAX_RESOLUTION: u32 = 128;
+
+/// Converts L-System skeletons into Bevy meshes.
+///
+/// Generates smooth tube geometry from [`Skeleton`] strands using parallel transport
+/// to avoid twisting artifacts. Segments are bucketed by material ID, producing
+/// separate meshes for each material.
+///
+/// # Features
+///
+/// - **Multi-material support**: Segments with different `material_id` values produce
+///   separate meshes, allowing different Bevy materials to be applied.
+/// - **Vertex colors**: Per-vertex colors from [`SkeletonPoint::color`] are included.
+/// - **UV mapping**: Arc-length parameterized UVs with aspect-ratio preservation.
+///   U wraps around the tube (0.0 to 1.0), V increases along the strand.
+///   V is scaled by each point's [`SkeletonPoint::uv_scale`] factor.
+/// - **UV layouts**: Optional per-material U repeat count and texture-atlas
+///   rectangle (see [`MaterialUv`]).
+/// - **Lightmap UVs**: Optional non-overlapping second UV channel packed
+///   into `0..1` (see [`LSystemMeshBuilder::with_lightmap_uvs`]).
+/// - **Smooth geometry**: Parallel transport prevents tube twisting at bends.
+/// - **End caps**: Optional flat, dome, or pointed caps close strand ends
+///   (see [`CapStyle`]).
+/// - **Spline smoothing**: Optional Catmull-Rom or Bézier subdivision of strands
+///   before meshing (see [`StrandSmoothing`]).
+/// - **Cross-sections**: Circular, polygonal, star, or custom ring profiles,
+///   selectable per material (see [`CrossSection`]).
+/// - **Displacement**: Optional noise or heightfield relief on tube surfaces,
+///   selectable per material (see [`Displacement`]).
+/// - **Foliage cards**: Quads or crossed quads for leaf/flower materials,
+///   emitted in the same build as the bark (see [`CardStyle`]).
+/// - **Ribbons**: Optional flat strips instead of tubes for grass and 2D
+///   L-systems (see [`RibbonFacing`]).
+/// - **Diagnostics**: [`LSystemMeshBuilder::try_build`] reports what was built
+///   and what was wrong with the input, and enforces geometry budgets.
+/// - **Faceted shading**: Optional flat or angle-limited normals on
+///   un-shared vertices for low-poly styles (see [`Shading`]).
+/// - **Filtering**: Optional radius, material, depth, and custom predicates
+///   that skip segments, cards, or whole strands.
+/// - **Shadow proxies**: A coarse, attribute-free companion mesh for
+///   shadow passes (see [`ShadowProxy`](crate::shadow::ShadowProxy)).
+/// - **Hierarchy attributes**: Optional per-vertex strand index, branch depth,
+///   progress, root distance, and radius for custom shaders.
+/// - **Growth attributes**: Optional per-vertex birth time and spine points so
+///   a single mesh can animate from seedling to full plant.
+/// - **Adaptive resolution**: Optional per-strand ring resolution derived from
+///   radius, so twigs don't spend the trunk's vertex budget.
+/// - **Junctions**: Optional welding of child strands onto their parent tube
+///   via [`Skeleton::strand_parents`] (see [`JunctionStyle`]).
+///
+/// # Example
+///
+/// ```ignore
+/// use bevy_symbios::LSystemMeshBuilder;
+///
+/// let skeleton = /* ... generate skeleton ... */;
+/// let meshes = LSystemMeshBuilder::new()
+///     .with_resolution(12)
+///     .build(&skeleton);
+///
+/// for (material_id, mesh) in meshes {
+///     // Spawn each mesh with appropriate material
+/// }
+/// ```
+#[derive(Clone)]
+pub struct LSystemMeshBuilder {
+    resolution: u32,
+    start_cap: CapStyle,
+    end_cap: CapStyle,
+    junctions: JunctionStyle,
+    adaptive: Option<AdaptiveResolution>,
+    smoothing: StrandSmoothing,
+    cross_section: CrossSection,
+    material_cross_sections: BTreeMap<u16, CrossSection>,
+    displacement: Displacement,
+    material_displacements: BTreeMap<u16, Displacement>,
+    cards: BTreeMap<u16, CardStyle>,
+    material_uvs: BTreeMap<u16, MaterialUv>,
+    ribbons: Option<RibbonFacing>,
+    shading: Shading,
+    filter: StrandFilter,
+    vertex_budget: Option<usize>,
+    triangle_budget: Option<usize>,
+    hierarchy_attributes: bool,
+    growth_attributes: bool,
+    lightmap_padding: Option<f32>,
+    clamped_options: Vec<ClampedOption>,
+}
+
+impl Default for LSystemMeshBuilder {
+    fn default() -> Self {
+        Self {
+            resolution: 8,
+            start_cap: CapStyle::Open,
+            end_cap: CapStyle::Open,
+            junctions: JunctionStyle::Disjoint,
+            adaptive: None,
+            smoothing: StrandSmoothing::None,
+            cross_section: CrossSection::Circle,
+            material_cross_sections: BTreeMap::new(),
+            displacement: Displacement::None,
+            material_displacements: BTreeMap::new(),
+            cards: BTreeMap::new(),
+            material_uvs: BTreeMap::new(),
+            ribbons: None,
+            shading: Shading::Smooth,
+            filter: StrandFilter::default(),
+            vertex_budget: None,
+            triangle_budget: None,
+            hierarchy_attributes: false,
+            growth_attributes: false,
+            lightmap_padding: None,
+            clamped_options: Vec::new(),
+        }
+    }
+}
+
+impl CapStyle {
+    /// Clamps parameters into a usable range (non-negative, finite cone length).
+    fn sanitized(self) -> Self {
+        match self {
+            Self::Point { length } if length.is_finite() => Self::Point {
+                length: length.max(0.0),
+            },
+            Self::Point { .. } => Self::Point { length: 0.0 },
+            other => other,
+        }
+    }
+
+    /// Numeric parameters by name, for clamp reports.
+    fn params(&self) -> Vec<(&'static str, f64)> {
+        match self {
+            Self::Point { length } => vec![("length", f64::from(*length))],
+            _ => Vec::new(),
+        }
+    }
+
+    fn hash_into<H: Hasher>(&self, hasher: &mut H) {
+        match self {
+            Self::Open => 0u8.hash(hasher),
+            Self::Flat => 1u8.hash(hasher),
+            Self::Dome => 2u8.hash(hasher),
+            Self::Point { length } => {
+                3u8.hash(hasher);
+                length.to_bits().hash(hasher);
+            }
+        }
+    }
+}
+
+impl LSystemMeshBuilder {
+    /// Creates a new mesh builder with default settings (resolution = 8).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of vertices around each ring of the tube.
+    ///
+    /// Higher values produce smoother tubes but increase vertex count.
+    /// Minimum value is 3 (triangular cross-section), maximum is 128.
+    /// Values outside this range are clamped with a warning and reported in
+    /// [`BuildReport::clamped_options`]. Default is 8.
+    pub fn with_resolution(mut self, res: u32) -> Self {
+        if res > MAX_RESOLUTION {
+            warn!(
+                "Mesh resolution {} exceeds maximum of {}; clamping to {}",
+                res, MAX_RESOLUTION, MAX_RESOLUTION
+            );
+        }
+        self.resolution = res.clamp(3, MAX_RESOLUTION);
+        self.note_clamps(
+            "with_resolution",
+            None,
+            &[("resolution", f64::from(res))],
+            &[("resolution", f64::from(self.resolution))],
+        );
+        self
+    }
+
+    /// Picks each strand's ring resolution from its radius instead of using one
+    /// global value.
+    ///
+    /// A strand gets enough vertices that ring edges are roughly
+    /// `target_edge_length` long at its widest point, clamped between
+    /// `min_resolution` and the regular [`Self::with_resolution`] value, which
+    /// becomes the upper bound. Every ring of a strand shares the same count,
+    /// so vertex sharing and UV continuity along the strand are unaffected.
+    ///
+    /// A non-positive or non-finite `target_edge_length` disables the mode.
+    pub fn with_adaptive_resolution(
+        mut self,
+        min_resolution: u32,
+        target_edge_length: f32,
+    ) -> Self {
+        let enabled = target_edge_length.is_finite() && target_edge_length > 0.0;
+        let min = min_resolution.clamp(3, MAX_RESOLUTION);
+        let param = |min: u32| {
+            enabled
+                .then_some(("min_resolution", f64::from(min)))
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+        self.note_clamps(
+            "with_adaptive_resolution",
+            None,
+            &param(min_resolution),
+            &param(min),
+        );
+        self.adaptive = enabled.then_some(AdaptiveResolution {
+            min,
+            edge_length: target_edge_length,
+        });
+        self
+    }
+
+    /// Refines every strand with a spline before meshing, so low-iteration
+    /// L-systems get curved branches without deriving more generations.
+    ///
+    /// Subdivision counts above 16 are clamped with a warning; `0` disables
+    /// smoothing. Default is [`StrandSmoothing::None`].
+    pub fn with_smoothing(mut self, smoothing: StrandSmoothing) -> Self {
+        if let StrandSmoothing::CatmullRom { subdivisions }
+        | StrandSmoothing::Bezier { subdivisions } = smoothing
+        {
+            self.note_clamps(
+                "with_smoothing",
+                None,
+                &[("subdivisions", f64::from(subdivisions))],
+                &[(
+                    "subdivisions",
+                    f64::from(subdivisions.min(MAX_SUBDIVISIONS)),
+                )],
+            );
+        }
+        let clamp = |subdivisions: u32| {
+            if subdivisions > MAX_SUBDIVISIONS {
+                warn!(
+                    "Strand subdivisions {} exceed maximum of {}; clamping to {}",
+                    subdivisions, MAX_SUBDIVISIONS, MAX_SUBDIVISIONS
+                );
+            }
+            subdivisions.min(MAX_SUBDIVISIONS)
+        };
+        self.smoothing = match smoothing {
+            StrandSmoothing::CatmullRom { subdivisions: 0 }
+            | StrandSmoothing::Bezier { subdivisions: 0 } => StrandSmoothing::None,
+            StrandSmoothing::CatmullRom { subdivisions } => StrandSmoothing::CatmullRom {
+                subdivisions: clamp(subdivisions),
+            },
+            StrandSmoothing::Bezier { subdivisions } => StrandSmoothing::Bezier {
+                subdivisions: clamp(subdivisions),
+            },
+            StrandSmoothing::None => StrandSmoothing::None,
+        };
+        self
+    }
+
+    /// Sets the ring shape used for every material without its own
+    /// [`Self::with_material_cross_section`]. Default is
+    /// [`CrossSection::Circle`].
+    ///
+    /// Degenerate custom outlines fall back to a circle with a warning.
+    pub fn with_cross_section(mut self, cross_section: CrossSection) -> Self {
+        let requested = cross_section.params();
+        self.cross_section = cross_section.sanitized();
+        self.note_clamps(
+            "with_cross_section",
+            None,
+            &requested,
+            &self.cross_section.params(),
+        );
+        self
+    }
+
+    /// Sets the ring shape for segments with the given material ID, e.g. a
+    /// fluted profile for columns while bark stays round.
+    pub fn with_material_cross_section(
+        mut self,
+        material_id: u16,
+        cross_section: CrossSection,
+    ) -> Self {
+        let requested = cross_section.params();
+        let cross_section = cross_section.sanitized();
+        self.note_clamps(
+            "with_material_cross_section",
+            Some(material_id),
+            &requested,
+            &cross_section.params(),
+        );
+        self.material_cross_sections
+            .insert(material_id, cross_section);
+        self
+    }
+
+    /// Sets the surface relief applied to every material without its own
+    /// [`Self::with_material_displacement`]. Default is [`Displacement::None`].
+    ///
+    /// Amplitudes beyond ±1 are clamped and non-finite parameters disable
+    /// displacement, both with a warning.
+    pub fn with_displacement(mut self, displacement: Displacement) -> Self {
+        let requested = displacement.params();
+        self.displacement = displacement.sanitized();
+        self.note_clamps(
+            "with_displacement",
+            None,
+            &requested,
+            &self.displacement.params(),
+        );
+        self
+    }
+
+    /// Sets the surface relief for segments with the given material ID, e.g.
+    /// a bark heightfield on the trunk while stems stay smooth. Pass
+    /// [`Displacement::None`] to exempt a material from the global setting.
+    pub fn with_material_displacement(
+        mut self,
+        material_id: u16,
+        displacement: Displacement,
+    ) -> Self {
+        let requested = displacement.params();
+        let displacement = displacement.sanitized();
+        self.note_clamps(
+            "with_material_displacement",
+            Some(material_id),
+            &requested,
+            &displacement.params(),
+        );
+        self.material_displacements
+            .insert(material_id, displacement);
+        self
+    }
+
+    /// Emits foliage cards instead of tubes for the given material ID.
+    ///
+    /// Every skeleton point with this material gets a card (see
+    /// [`CardStyle`]) in that material's mesh, including points of
+    /// single-point strands. Segments starting at such a point produce no
+    /// tube geometry, so a leaf at the tip of a branch is just
+    /// `... ,(leaf) F` in the L-system.
+    pub fn with_cards(mut self, material_id: u16, style: CardStyle) -> Self {
+        let sanitized = style.sanitized();
+        self.note_clamps(
+            "with_cards",
+            Some(material_id),
+            &[("size", f64::from(style.size()))],
+            &[("size", f64::from(sanitized.size()))],
+        );
+        self.cards.insert(material_id, sanitized);
+        self
+    }
+
+    /// Sets the U repeat count and atlas rectangle for one material ID (see
+    /// [`MaterialUv`]). Default is one repeat and no atlas for every material.
+    ///
+    /// Cards ignore the repeat count; their `0..1` UVs map straight into the
+    /// atlas rectangle.
+    pub fn with_material_uv(mut self, material_id: u16, uv: MaterialUv) -> Self {
+        let sanitized = uv.sanitized();
+        self.note_clamps(
+            "with_material_uv",
+            Some(material_id),
+            &uv.params(),
+            &sanitized.params(),
+        );
+        self.material_uvs.insert(material_id, sanitized);
+        self
+    }
+
+    /// Extrudes every strand as a flat strip instead of a tube: two vertices
+    /// per point, as wide as the point's diameter, facing per `facing`.
+    /// Colours, material buckets, and arc-length UVs work as for tubes, with U
+    /// running across the strip. Default is `None` (tubes).
+    ///
+    /// Cross-sections, caps, and junction welding do not apply to ribbons;
+    /// card materials still emit cards.
+    pub fn with_ribbons(mut self, facing: Option<RibbonFacing>) -> Self {
+        self.ribbons = facing.map(RibbonFacing::sanitized);
+        self
+    }
+
+    /// Sets how surfaces are shaded. [`Shading::Flat`] and
+    /// [`Shading::AutoSmooth`] un-share vertices so neighbouring triangles
+    /// can have different normals. Default is [`Shading::Smooth`].
+    pub fn with_shading(mut self, shading: Shading) -> Self {
+        self.shading = shading.sanitized();
+        self.note_clamps(
+            "with_shading",
+            None,
+            &shading.params(),
+            &self.shading.params(),
+        );
+        self
+    }
+
+    /// Skips segments and cards whose point is thinner than `min_radius`,
+    /// like [`ColliderGenerator::with_min_radius`](crate::ColliderGenerator)
+    /// does for physics. Default is 0 (keep everything).
+    ///
+    /// Filters judge each tube segment by the point it starts at, the same
+    /// point that picks its material. Skipped segments leave their
+    /// neighbours' ends open, as card materials do.
+    pub fn with_min_radius(mut self, min_radius: f32) -> Self {
+        self.filter.min_radius = min_radius.max(0.0);
+        self.note_clamps(
+            "with_min_radius",
+            None,
+            &[("min_radius", f64::from(min_radius))],
+            &[("min_radius", f64::from(self.filter.min_radius))],
+        );
+        self
+    }
+
+    /// Skips segments and cards whose point is thicker than `max_radius`,
+    /// e.g. to strip the trunk from a foliage-only mesh. Default is no limit.
+    pub fn with_max_radius(mut self, max_radius: f32) -> Self {
+        if max_radius.is_nan() {
+            warn!("Maximum mesh radius is NaN; ignoring it");
+            self.filter.max_radius = None;
+        } else {
+            self.filter.max_radius = Some(max_radius);
+        }
+        self
+    }
+
+    /// Meshes only segments and cards with one of these material IDs.
+    /// Default is every material.
+    pub fn with_included_materials(mut self, material_ids: impl IntoIterator<Item = u16>) -> Self {
+        self.filter.include_materials = Some(material_ids.into_iter().collect());
+        self
+    }
+
+    /// Skips segments and cards with any of these material IDs, on top of
+    /// [`Self::with_included_materials`].
+    pub fn with_excluded_materials(mut self, material_ids: impl IntoIterator<Item = u16>) -> Self {
+        self.filter.exclude_materials = material_ids.into_iter().collect();
+        self
+    }
+
+    /// Skips whole strands more than `max_depth` branchings away from a root
+    /// strand, following [`Skeleton::strand_parents`]. Roots have depth 0.
+    pub fn with_max_depth(mut self, max_depth: u32) -> Self {
+        self.filter.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Skips segments and cards whose `(strand_index, point)` the predicate
+    /// rejects, after the other filters.
+    ///
+    /// Closures can't be compared, so cache fingerprints use `key` in place of
+    /// the predicate: give every distinct predicate its own key, and reuse a
+    /// key only for predicates that keep the same points.
+    pub fn with_point_filter(
+        mut self,
+        key: u64,
+        keep: impl Fn(usize, &SkeletonPoint) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.filter.predicate = Some((key, Arc::new(keep)));
+        self
+    }
+
+    /// Adds per-vertex hierarchy attributes for wind, growth, and damage
+    /// shaders: [`ATTRIBUTE_STRAND_INDEX`], [`ATTRIBUTE_BRANCH_DEPTH`],
+    /// [`ATTRIBUTE_STRAND_PROGRESS`], [`ATTRIBUTE_ROOT_DISTANCE`], and
+    /// [`ATTRIBUTE_BRANCH_RADIUS`]. Default is `false`.
+    ///
+    /// `StandardMaterial` ignores these, so enabling them only costs memory
+    /// unless a custom shader reads them.
+    pub fn with_hierarchy_attributes(mut self, enabled: bool) -> Self {
+        self.hierarchy_attributes = enabled;
+        self
+    }
+
+    /// Adds the per-vertex growth attributes [`ATTRIBUTE_BIRTH`],
+    /// [`ATTRIBUTE_SPINE`], and [`ATTRIBUTE_PREVIOUS_SPINE`] used by
+    /// [`GrowthMaterial`](crate::growth::GrowthMaterial). Default is `false`.
+    ///
+    /// Birth is the root distance normalized by the longest one in the
+    /// skeleton: skeletons carry no derivation step, so growth follows the
+    /// branching structure outward from the root.
+    pub fn with_growth_attributes(mut self, enabled: bool) -> Self {
+        self.growth_attributes = enabled;
+        self
+    }
+
+    /// Adds [`Mesh::ATTRIBUTE_UV_1`] with a lightmap layout: every tube run,
+    /// cap, and card becomes its own chart, scaled to its world-space size
+    /// and packed without overlap into `0..1`, at least `padding` apart and
+    /// from the border. `None` (the default) adds no second UV channel.
+    ///
+    /// Padding is a fraction of the layout, so for a lightmap of `n` texels
+    /// use `Some(k / n)` to keep `k` texels between charts. It is clamped to
+    /// `0.0..=0.25` with a warning.
+    ///
+    /// Each material mesh gets its own layout. Vertices on a chart boundary
+    /// are duplicated, so meshes with this enabled have more vertices.
+    pub fn with_lightmap_uvs(mut self, padding: Option<f32>) -> Self {
+        self.lightmap_padding = padding.map(|padding| {
+            if !padding.is_finite() {
+                warn!("Lightmap padding {padding} is not finite; using 0");
+                0.0
+            } else if !(0.0..=0.25).contains(&padding) {
+                let clamped = padding.clamp(0.0, 0.25);
+                warn!("Lightmap padding {padding} clamped to {clamped}");
+                clamped
+            } else {
+                padding
+            }
+        });
+        let param = |padding: Option<f32>| padding.map(|p| ("padding", f64::from(p)));
+        self.note_clamps(
+            "with_lightmap_uvs",
+            None,
+            &param(padding).into_iter().collect::<Vec<_>>(),
+            &param(self.lightmap_padding).into_iter().collect::<Vec<_>>(),
+        );
+        self
+    }
+
+    /// Sets how the first ring of every strand is closed. Default is
+    /// [`CapStyle::Open`].
+    pub fn with_start_cap(mut self, cap: CapStyle) -> Self {
+        self.start_cap = cap.sanitized();
+        self.note_clamps(
+            "with_start_cap",
+            None,
+            &cap.params(),
+            &self.start_cap.params(),
+        );
+        self
+    }
 
-/// Maximum allowed tube resolution to prevent memory exhaustion.
-/// 128 vertices per ring is more than sufficient for smooth tubes.
-const MAX_RESOLUTION: u32 = 128;
+    /// Sets how the last ring of every strand is closed. Default is
+    /// [`CapStyle::Open`].
+    pub fn with_end_cap(mut self, cap: CapStyle) -> Self {
+        self.end_cap = cap.sanitized();
+        self.note_clamps("with_end_cap", None, &cap.params(), &self.end_cap.params());
+        self
+    }
 
-/// Converts L-System skeletons into Bevy meshes.
-///
-/// Generates smooth tube geometry from [`Skeleton`] strands using parallel transport
-/// to avoid twisting artifacts. Segments are bucketed by material ID, producing
-/// separate meshes for each material.
-///
-/// # Features
-///
-/// - **Multi-material support**: Segments with different `material_id` values produce
-///   separate meshes, allowing different Bevy materials to be applied.
-/// - **Vertex colors**: Per-vertex colors from [`SkeletonPoint::color`] are included.
-/// - **UV mapping**: Arc-length parameterized UVs with aspect-ratio preservation.
-///   U wraps around the tube (0.0 to 1.0), V increases along the strand.
-///   V is scaled by each point's [`SkeletonPoint::uv_scale`] factor.
-/// - **Smooth geometry**: Parallel transport prevents tube twisting at bends.
-///
-/// # Example
-///
-/// ```ignore
-/// use bevy_symbios::LSystemMeshBuilder;
-///
-/// let skeleton = /* ... generate skeleton ... */;
-/// let meshes = LSystemMeshBuilder::new()
-///     .with_resolution(12)
-///     .build(&skeleton);
-///
-/// for (material_id, mesh) in meshes {
-///     // Spawn each mesh with appropriate material
-/// }
-/// ```
-pub struct LSystemMeshBuilder {
-    buckets: HashMap<u16, MeshData>,
-    resolution: u32,
-}
+    /// Uses the same [`CapStyle`] for both ends of every strand.
+    ///
+    /// `with_caps(CapStyle::Flat)` is the quickest way to get a closed,
+    /// watertight mesh suitable for 3D printing or boolean operations.
+    pub fn with_caps(self, cap: CapStyle) -> Self {
+        self.with_start_cap(cap).with_end_cap(cap)
+    }
 
-impl Default for LSystemMeshBuilder {
-    fn default() -> Self {
-        Self {
-            buckets: HashMap::new(),
-            resolution: 8,
+    /// Sets how child strands join their parent. Default is
+    /// [`JunctionStyle::Disjoint`].
+    ///
+    /// Forks are seated, not stitched: a welded child's base ring is moved
+    /// onto the parent surface, but the parent tube is not cut open, so the
+    /// two tubes overlap rather than forming one surface. Unless the start cap
+    /// is [`CapStyle::Open`], welded strands close their base with a flat cap
+    /// hidden under the parent surface, so capped meshes stay watertight.
+    pub fn with_junctions(mut self, style: JunctionStyle) -> Self {
+        self.junctions = match style {
+            JunctionStyle::Blend { flare } if flare.is_finite() => JunctionStyle::Blend {
+                flare: flare.max(0.0),
+            },
+            JunctionStyle::Blend { .. } => JunctionStyle::Weld,
+            other => other,
+        };
+        let flare = |style: JunctionStyle| match style {
+            JunctionStyle::Blend { flare } => vec![("flare", f64::from(flare))],
+            _ => Vec::new(),
+        };
+        self.note_clamps(
+            "with_junctions",
+            None,
+            &flare(style),
+            &flare(self.junctions),
+        );
+        self
+    }
+
+    /// Records every parameter `requested` had that ended up different in
+    /// `effective`, for [`BuildReport::clamped_options`], replacing earlier
+    /// entries of the same method and material. Parameters missing from
+    /// `effective` were replaced outright, not clamped, and are skipped.
+    fn note_clamps(
+        &mut self,
+        method: &'static str,
+        material_id: Option<u16>,
+        requested: &[(&'static str, f64)],
+        effective: &[(&'static str, f64)],
+    ) {
+        self.clamped_options
+            .retain(|c| (c.method, c.material_id) != (method, material_id));
+        for &(parameter, requested) in requested {
+            let Some(&(_, effective)) = effective.iter().find(|(p, _)| *p == parameter) else {
+                continue;
+            };
+            if requested != effective && !(requested.is_nan() && effective.is_nan()) {
+                self.clamped_options.push(ClampedOption {
+                    method,
+                    parameter,
+                    material_id,
+                    requested,
+                    effective,
+                });
+            }
         }
     }
-}
 
-impl LSystemMeshBuilder {
-    /// Creates a new mesh builder with default settings (resolution = 8).
-    pub fn new() -> Self {
-        Self::default()
+    /// Caps the total vertex count of [`Self::try_build`], which fails once
+    /// the meshed strands exceed it. Other build methods ignore budgets.
+    pub fn with_vertex_budget(mut self, max_vertices: usize) -> Self {
+        self.vertex_budget = Some(max_vertices);
+        self
     }
 
-    /// Sets the number of vertices around each ring of the tube.
-    ///
-    /// Higher values produce smoother tubes but increase vertex count.
-    /// Minimum value is 3 (triangular cross-section), maximum is 128.
-    /// Values outside this range are clamped with a warning. Default is 8.
-    pub fn with_resolution(mut self, res: u32) -> Self {
-        if res > MAX_RESOLUTION {
-            warn!(
-                "Mesh resolution {} exceeds maximum of {}; clamping to {}",
-                res, MAX_RESOLUTION, MAX_RESOLUTION
-            );
-        }
-        self.resolution = res.clamp(3, MAX_RESOLUTION);
+    /// Caps the total triangle count of [`Self::try_build`], which fails
+    /// once the meshed strands exceed it. Other build methods ignore budgets.
+    pub fn with_triangle_budget(mut self, max_triangles: usize) -> Self {
+        self.triangle_budget = Some(max_triangles);
         self
     }
 
@@ -113,40 +1977,515 @@ impl LSystemMeshBuilder {
     /// that share the same `material_id` from their starting [`SkeletonPoint`].
     ///
     /// Empty skeletons or strands with fewer than 2 points produce no output.
-    pub fn build(mut self, skeleton: &Skeleton) -> HashMap<u16, Mesh> {
-        for strand in &skeleton.strands {
-            if strand.len() < 2 {
-                continue;
+    pub fn build(self, skeleton: &Skeleton) -> HashMap<u16, Mesh> {
+        self.build_with_joints(skeleton, &[])
+    }
+
+    /// Like [`Self::build`], but also returns a [`BuildReport`] with per-material
+    /// vertex and triangle counts, the number of dropped degenerate points,
+    /// and the strand and point index of every non-finite position or radius.
+    ///
+    /// Fails with a [`BuildError`] as soon as the strands meshed so far exceed
+    /// [`Self::with_vertex_budget`] or [`Self::with_triangle_budget`], without
+    /// meshing the rest. The finished meshes are checked again, since lightmap
+    /// seams add vertices after meshing.
+    pub fn try_build(
+        self,
+        skeleton: &Skeleton,
+    ) -> Result<(HashMap<u16, Mesh>, BuildReport), BuildError> {
+        let mut report = BuildReport {
+            clamped_options: self.clamped_options.clone(),
+            ..BuildReport::default()
+        };
+        report.scan_inputs(skeleton);
+
+        let hierarchy = self.strand_hierarchy(skeleton);
+        let mut buckets: HashMap<u16, MeshData> = HashMap::new();
+        for (strand_idx, strand) in skeleton.strands.iter().enumerate() {
+            if strand.len() >= 2 {
+                report.dropped_points += strand.len() - dedup_indices(strand).len();
             }
-            self.process_strand(strand);
+            self.mesh_strand(&mut buckets, skeleton, strand_idx, &hierarchy, &[]);
+
+            self.check_budgets(buckets.values(), Some(strand_idx))?;
+        }
+
+        let mut finished = Vec::with_capacity(buckets.len());
+        for (material_id, mut data) in buckets {
+            self.finish_bucket(&mut data, material_id);
+            finished.push((material_id, data));
+        }
+        self.check_budgets(finished.iter().map(|(_, data)| data), None)?;
+
+        let mut meshes = HashMap::with_capacity(finished.len());
+        for (material_id, data) in finished {
+            report.non_finite_vertices += data.positions.iter().filter(|p| !p.is_finite()).count();
+            report.materials.insert(
+                material_id,
+                MaterialStats {
+                    vertices: data.positions.len(),
+                    triangles: data.indices.len() / 3,
+                },
+            );
+            meshes.insert(material_id, data.to_mesh());
+        }
+        Ok((meshes, report))
+    }
+
+    /// Fails if `buckets` together exceed the vertex or triangle budget.
+    /// `strand` is the last strand meshed, or `None` for finished meshes.
+    fn check_budgets<'a>(
+        &self,
+        buckets: impl Iterator<Item = &'a MeshData> + Clone,
+        strand: Option<usize>,
+    ) -> Result<(), BuildError> {
+        let vertices: usize = buckets.clone().map(|b| b.positions.len()).sum();
+        let triangles: usize = buckets.map(|b| b.indices.len() / 3).sum();
+        if let Some(budget) = self.vertex_budget.filter(|&budget| vertices > budget) {
+            return Err(BuildError::VertexBudgetExceeded {
+                budget,
+                vertices,
+                strand,
+            });
+        }
+        if let Some(budget) = self.triangle_budget.filter(|&budget| triangles > budget) {
+            return Err(BuildError::TriangleBudgetExceeded {
+                budget,
+                triangles,
+                strand,
+            });
+        }
+        Ok(())
+    }
+
+    /// Like [`Self::build`], but pairs every material mesh with a
+    /// [`SkeletonMap`] from its triangles back to the skeleton points they
+    /// were built from. See [`crate::picking`].
+    pub fn build_mapped(self, skeleton: &Skeleton) -> HashMap<u16, (Mesh, SkeletonMap)> {
+        let hierarchy = self.strand_hierarchy(skeleton);
+        self.mesh_strands(skeleton, 0..skeleton.strands.len(), &hierarchy, &[])
+            .into_iter()
+            .map(|(k, v)| {
+                let map = v.skeleton_map();
+                (k, (self.finish_mesh(v, k), map))
+            })
+            .collect()
+    }
+
+    /// Builds a single mesh holding every material bucket, consuming the
+    /// builder. Each vertex records its material ID in
+    /// [`ATTRIBUTE_MATERIAL_INDEX`], so a whole plant renders in one draw call
+    /// with a palette-aware material such as
+    /// [`PaletteMaterial`](crate::merged::PaletteMaterial).
+    ///
+    /// Buckets are concatenated in ascending material ID order; each keeps the
+    /// vertices and triangles [`Self::build`] would give it.
+    pub fn build_merged(self, skeleton: &Skeleton) -> Mesh {
+        let hierarchy = self.strand_hierarchy(skeleton);
+        let mut buckets: Vec<(u16, MeshData)> = self
+            .mesh_strands(skeleton, 0..skeleton.strands.len(), &hierarchy, &[])
+            .into_iter()
+            .collect();
+        buckets.sort_by_key(|(mat_id, _)| *mat_id);
+
+        let mut merged = MeshData::default();
+        for (mat_id, mut data) in buckets {
+            data.material_ids = vec![u32::from(mat_id); data.positions.len()];
+            merged.append(data);
         }
+        self.finish_mesh(merged, 0)
+    }
 
-        self.buckets
+    /// [`Self::build`] with skinning joints assigned per strand by `joints`
+    /// (indexed by strand; empty for no skinning attributes).
+    pub(crate) fn build_with_joints(
+        self,
+        skeleton: &Skeleton,
+        joints: &[StrandJoints],
+    ) -> HashMap<u16, Mesh> {
+        let hierarchy = self.strand_hierarchy(skeleton);
+        self.mesh_strands(skeleton, 0..skeleton.strands.len(), &hierarchy, joints)
             .into_iter()
-            .map(|(k, v)| (k, v.to_mesh()))
+            .map(|(k, v)| (k, self.finish_mesh(v, k)))
             .collect()
     }
 
-    fn process_strand(&mut self, points: &[SkeletonPoint]) {
-        // Filter out duplicate adjacent points (zero-length segments) to prevent NaNs.
-        // Build a list by keeping only points whose position differs from the last kept point.
-        let filtered: Vec<&SkeletonPoint> = {
-            let mut result = vec![&points[0]];
-            for point in &points[1..] {
-                let last = result.last().unwrap();
-                if last.position.distance_squared(point.position) > 0.000001 {
-                    result.push(point);
-                }
+    /// Adds the lightmap layout, if enabled, to a finished bucket of
+    /// `material_id`, then maps its UVs into their atlas rectangles. Merged
+    /// buckets use their per-vertex material IDs instead.
+    fn finish_bucket(&self, data: &mut MeshData, material_id: u16) {
+        if let Some(padding) = self.lightmap_padding {
+            data.unwrap_lightmap(padding);
+        }
+        if self.material_uvs.values().any(|uv| uv.atlas.is_some()) {
+            let atlas = |v: usize| {
+                let id = data
+                    .material_ids
+                    .get(v)
+                    .map_or(material_id, |&id| id as u16);
+                self.material_uvs.get(&id).and_then(|uv| uv.atlas)
+            };
+            let atlases: Vec<Option<Rect>> = (0..data.uvs.len()).map(atlas).collect();
+            data.map_to_atlas(&atlases);
+        }
+    }
+
+    /// Converts a finished bucket of `material_id` into a [`Mesh`].
+    fn finish_mesh(&self, mut data: MeshData, material_id: u16) -> Mesh {
+        self.finish_bucket(&mut data, material_id);
+        data.to_mesh()
+    }
+
+    /// Material IDs registered with [`Self::with_cards`].
+    pub(crate) fn card_materials(&self) -> BTreeSet<u16> {
+        self.cards.keys().copied().collect()
+    }
+
+    /// Skips card materials entirely instead of emitting their cards.
+    pub(crate) fn without_cards(mut self) -> Self {
+        let card_materials = self.card_materials();
+        self.filter.exclude_materials.extend(card_materials);
+        self.cards.clear();
+        self
+    }
+
+    /// Like [`Self::build`], but splits the strands into chunks meshed in
+    /// parallel on Bevy's [`ComputeTaskPool`], then converts each material
+    /// bucket to a [`Mesh`] in parallel as well.
+    ///
+    /// Chunks are merged back in strand order, so the output is identical to
+    /// [`Self::build`]. Falls back to a default pool if none is initialized.
+    pub fn build_parallel(self, skeleton: &Skeleton) -> HashMap<u16, Mesh> {
+        let pool = ComputeTaskPool::get_or_init(TaskPool::default);
+        let strand_count = skeleton.strands.len();
+        let chunk_size = strand_count.div_ceil(pool.thread_num().max(1) * 4).max(1);
+
+        let this = &self;
+        let hierarchy = &self.strand_hierarchy(skeleton);
+        let chunks = pool.scope(|scope| {
+            for start in (0..strand_count).step_by(chunk_size) {
+                let end = (start + chunk_size).min(strand_count);
+                scope.spawn(async move { this.mesh_strands(skeleton, start..end, hierarchy, &[]) });
+            }
+        });
+
+        let mut merged: HashMap<u16, MeshData> = HashMap::new();
+        for chunk in chunks {
+            for (mat_id, data) in chunk {
+                merged.entry(mat_id).or_default().append(data);
+            }
+        }
+
+        pool.scope(|scope| {
+            for (mat_id, data) in merged {
+                scope.spawn(async move { (mat_id, this.finish_mesh(data, mat_id)) });
             }
-            result
+        })
+        .into_iter()
+        .collect()
+    }
+
+    /// Builds meshes on the [`AsyncComputeTaskPool`] without blocking the
+    /// calling system. The skeleton is moved into the task.
+    ///
+    /// Insert the returned task into an
+    /// [`LSystemMeshTask`](crate::mesh_tasks::LSystemMeshTask) component and add
+    /// [`apply_lsystem_mesh_tasks`](crate::mesh_tasks::apply_lsystem_mesh_tasks)
+    /// to your `Update` schedule to spawn the finished meshes as children of
+    /// that entity.
+    pub fn build_async(self, skeleton: Skeleton) -> Task<HashMap<u16, Mesh>> {
+        AsyncComputeTaskPool::get_or_init(TaskPool::default)
+            .spawn(async move { self.build(&skeleton) })
+    }
+
+    /// Meshes the strands in `range` into fresh per-material buckets.
+    /// `hierarchy` is indexed by strand and empty when neither hierarchy nor
+    /// growth attributes are enabled; `joints` likewise, when not skinning.
+    fn mesh_strands(
+        &self,
+        skeleton: &Skeleton,
+        range: Range<usize>,
+        hierarchy: &[StrandHierarchy],
+        joints: &[StrandJoints],
+    ) -> HashMap<u16, MeshData> {
+        let mut buckets = HashMap::new();
+        for strand_idx in range {
+            self.mesh_strand(&mut buckets, skeleton, strand_idx, hierarchy, joints);
+        }
+        buckets
+    }
+
+    /// Meshes one strand's cards and tube into `buckets`.
+    fn mesh_strand(
+        &self,
+        buckets: &mut HashMap<u16, MeshData>,
+        skeleton: &Skeleton,
+        strand_idx: usize,
+        hierarchy: &[StrandHierarchy],
+        joints: &[StrandJoints],
+    ) {
+        let strand = &skeleton.strands[strand_idx];
+        let tags = hierarchy.get(strand_idx);
+        if !self.filter.keeps_strand(tags) {
+            return;
+        }
+        let strand_joints = joints.get(strand_idx);
+        // Faceted shading works on this strand's geometry alone, so later
+        // strands and cached ones never change it.
+        let mut faceted = HashMap::new();
+        let target = match self.shading {
+            Shading::Smooth => &mut *buckets,
+            _ => &mut faceted,
+        };
+        if !self.cards.is_empty() {
+            self.add_cards(target, strand_idx, strand, tags, strand_joints);
+        }
+        if strand.len() >= 2 {
+            let parent = self.junction_parent(skeleton, strand_idx);
+            self.process_strand(target, strand_idx, strand, parent, tags, strand_joints);
+        }
+        for (mat_id, mut data) in faceted {
+            data.facet(self.shading);
+            buckets.entry(mat_id).or_default().append(data);
+        }
+    }
+
+    /// Depth and base root distance of every strand, or an empty list when
+    /// neither hierarchy nor growth attributes nor a depth filter are enabled.
+    ///
+    /// A strand's base distance is its parent's base distance plus the arc
+    /// length along the parent to the point nearest the strand's base.
+    /// Missing, self-referencing, or cyclic parent links make a strand a root.
+    fn strand_hierarchy(&self, skeleton: &Skeleton) -> Vec<StrandHierarchy> {
+        if !self.hierarchy_attributes && !self.growth_attributes && self.filter.max_depth.is_none()
+        {
+            return Vec::new();
+        }
+        let count = skeleton.strands.len();
+        let parent_of = |idx: usize| {
+            skeleton
+                .strand_parents
+                .get(idx)
+                .copied()
+                .flatten()
+                .filter(|&p| p != idx && p < count)
         };
 
-        if filtered.len() < 2 {
+        let mut resolved: Vec<Option<StrandHierarchy>> = vec![None; count];
+        let mut on_chain = vec![false; count];
+        for start in 0..count {
+            // Walk up to the first resolved ancestor (or a root), then resolve
+            // the chain top-down.
+            let mut chain = Vec::new();
+            let mut current = Some(start);
+            while let Some(idx) = current {
+                if resolved[idx].is_some() || on_chain[idx] {
+                    break;
+                }
+                on_chain[idx] = true;
+                chain.push(idx);
+                current = parent_of(idx);
+            }
+            for &idx in chain.iter().rev() {
+                let info = match parent_of(idx).and_then(|p| resolved[p].map(|info| (p, info))) {
+                    Some((p, parent)) => StrandHierarchy {
+                        index: idx as u32,
+                        depth: parent.depth + 1,
+                        base_distance: parent.base_distance
+                            + skeleton.strands[idx].first().map_or(0.0, |base| {
+                                arc_length_to(&skeleton.strands[p], base.position)
+                            }),
+                        ..default()
+                    },
+                    None => StrandHierarchy {
+                        index: idx as u32,
+                        ..default()
+                    },
+                };
+                resolved[idx] = Some(info);
+                on_chain[idx] = false;
+            }
+        }
+        let mut strands: Vec<StrandHierarchy> = resolved
+            .into_iter()
+            .map(Option::unwrap_or_default)
+            .collect();
+        let reach = strands
+            .iter()
+            .zip(&skeleton.strands)
+            .map(|(info, points)| {
+                let positions: Vec<Vec3> = points.iter().map(|p| p.position).collect();
+                info.base_distance + cumulative_arc_lengths(&positions).last().unwrap_or(&0.0)
+            })
+            .fold(0.0f32, f32::max);
+        for info in &mut strands {
+            info.reach = reach;
+        }
+        strands
+    }
+
+    /// Returns the parent strand's points when junction handling is enabled
+    /// and `strand_idx` has a usable parent link.
+    fn junction_parent<'a>(
+        &self,
+        skeleton: &'a Skeleton,
+        strand_idx: usize,
+    ) -> Option<&'a [SkeletonPoint]> {
+        if self.junctions == JunctionStyle::Disjoint {
+            return None;
+        }
+        let parent_idx = (*skeleton.strand_parents.get(strand_idx)?)?;
+        if parent_idx == strand_idx {
+            return None;
+        }
+        skeleton
+            .strands
+            .get(parent_idx)
+            .map(Vec::as_slice)
+            .filter(|p| p.len() >= 2)
+    }
+
+    fn process_strand(
+        &self,
+        buckets: &mut HashMap<u16, MeshData>,
+        strand_idx: usize,
+        points: &[SkeletonPoint],
+        parent: Option<&[SkeletonPoint]>,
+        tags: Option<&StrandHierarchy>,
+        joints: Option<&StrandJoints>,
+    ) {
+        // `sources` holds the index into the unprocessed strand of the point
+        // that starts each processed point's segment, which is also the
+        // point the filters judge that segment by.
+        let keep: Vec<bool> = points
+            .iter()
+            .map(|point| self.filter.keeps_point(strand_idx, point))
+            .collect();
+        let mut sources = dedup_indices(points);
+        let mut points: Vec<SkeletonPoint> = sources.iter().map(|&i| points[i]).collect();
+
+        if points.len() < 2 {
             return;
         }
 
-        let points = filtered;
+        // Optional spline subdivision. Everything downstream (frames, UVs,
+        // junctions) works on the refined point list.
+        if self.smoothing != StrandSmoothing::None {
+            let (smoothed, segments) = smooth_strand(&points, self.smoothing);
+            points = smoothed;
+            sources = segments.iter().map(|&j| sources[j]).collect();
+        }
+
+        // Phase 0: Locate the parent tube this strand sprouts from, if any.
+        // Blended junctions get an extra collar point just outside the parent
+        // so the flare resolves back to the strand's own radius quickly.
+        let junction = parent
+            .filter(|_| self.ribbons.is_none())
+            .and_then(|parent| Self::find_parent_tube(parent, &points));
+        let mut weld_limit = 0.0;
+        if let Some(tube) = junction {
+            let seg_len = points[0].position.distance(points[1].position);
+            weld_limit = seg_len * 0.9;
+            if let JunctionStyle::Blend { flare } = self.junctions {
+                // Far enough out that even the far side of the flared ring,
+                // sliding at a shallow angle, exits the parent before it.
+                let flared = points[0].radius * (1.0 + flare);
+                let collar =
+                    ((tube.radius + flared) / tube.sin_angle + points[0].radius).min(seg_len * 0.5);
+                let t = collar / seg_len;
+                let collar_point = SkeletonPoint {
+                    position: points[0].position.lerp(points[1].position, t),
+                    radius: points[0].radius + (points[1].radius - points[0].radius) * t,
+                    color: points[0].color.lerp(points[1].color, t),
+                    ..points[0]
+                };
+                points.insert(1, collar_point);
+                sources.insert(1, sources[0]);
+                weld_limit = collar * 0.9;
+            }
+        }
+
         let n = points.len();
+        let res = self.strand_resolution(&points);
+
+        // Resolve the ring profile of every material used by this strand once.
+        let mut profiles: HashMap<u16, RingProfile> = HashMap::new();
+        for point in &points {
+            let mat_id = point.material_id as u16;
+            profiles.entry(mat_id).or_insert_with(|| {
+                let profile = match self.ribbons {
+                    Some(_) => RingProfile::ribbon(),
+                    None => self.material_cross_section(mat_id).ring_profile(res),
+                };
+                match self.material_uvs.get(&mat_id) {
+                    Some(uv) if uv.u_repeat > 1 => profile.tiled(uv.u_repeat, uv.atlas.is_some()),
+                    _ => profile,
+                }
+            });
+        }
+
+        // Hierarchy and growth attribute values for every point, if enabled.
+        let arcs = {
+            let positions: Vec<Vec3> = points.iter().map(|p| p.position).collect();
+            cumulative_arc_lengths(&positions)
+        };
+        let point_tags: Vec<VertexHierarchy> = match tags {
+            Some(strand) if self.hierarchy_attributes => {
+                let total = arcs[n - 1];
+                points
+                    .iter()
+                    .zip(&arcs)
+                    .map(|(point, &arc)| VertexHierarchy {
+                        strand: strand.index,
+                        depth: strand.depth,
+                        progress: if total > 0.0 { arc / total } else { 0.0 },
+                        root_distance: strand.base_distance + arc,
+                        radius: point.radius,
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        let point_growth: Vec<VertexGrowth> = match tags {
+            Some(strand) if self.growth_attributes => (0..n)
+                .map(|i| {
+                    let prev = i.saturating_sub(1);
+                    VertexGrowth {
+                        birth: strand.birth(arcs[i]),
+                        spine: points[i].position,
+                        previous: points[prev].position.extend(strand.birth(arcs[prev])),
+                    }
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        // Joint layouts are measured on the unprocessed strand; map each
+        // point onto it by its fraction of the processed length.
+        let point_joints: Vec<VertexJoints> = match joints {
+            Some(layout) => arcs
+                .iter()
+                .map(|&arc| {
+                    let fraction = if arcs[n - 1] > 0.0 {
+                        arc / arcs[n - 1]
+                    } else {
+                        0.0
+                    };
+                    layout.weights(fraction * layout.length)
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let tag = |data: &mut MeshData, i: usize| {
+            if let Some(values) = point_tags.get(i) {
+                data.tag(*values);
+            }
+            if let Some(values) = point_growth.get(i) {
+                data.tag_growth(*values);
+            }
+            if let Some(values) = point_joints.get(i) {
+                data.tag_joints(*values);
+            }
+        };
 
         // Phase 1: Compute per-point rotations via parallel transport.
         // Each point gets its own rotation based on its miter tangent, enabling
@@ -182,6 +2521,27 @@ impl LSystemMeshBuilder {
                 rots.push(rot);
             }
 
+            // Ribbons turn each transported frame about the tangent so its +Z
+            // faces the requested way.
+            if let Some(facing) = self.ribbons {
+                for (rot, point) in rots.iter_mut().zip(&points) {
+                    let tangent = *rot * Vec3::Y;
+                    let wanted = match facing {
+                        RibbonFacing::Turtle => point.rotation * Vec3::Z,
+                        RibbonFacing::Direction(direction) => direction,
+                    };
+                    let normal = wanted - tangent * tangent.dot(wanted);
+                    if normal.length_squared() > 1e-6 {
+                        let normal = normal.normalize();
+                        *rot = Quat::from_mat3(&Mat3::from_cols(
+                            tangent.cross(normal),
+                            tangent,
+                            normal,
+                        ));
+                    }
+                }
+            }
+
             rots
         };
 
@@ -195,7 +2555,8 @@ impl LSystemMeshBuilder {
             for i in 0..n - 1 {
                 let seg_len = points[i].position.distance(points[i + 1].position);
                 let avg_radius = (points[i].radius + points[i + 1].radius) * 0.5;
-                let circumference = avg_radius * std::f32::consts::TAU;
+                let perimeter = profiles[&(points[i].material_id as u16)].perimeter;
+                let circumference = avg_radius * perimeter;
                 let v_scale = if circumference > 0.0001 {
                     1.0 / circumference
                 } else {
@@ -205,49 +2566,358 @@ impl LSystemMeshBuilder {
                 coords.push(cumulative_v);
             }
 
-            coords
+            coords
+        };
+
+        // Phase 3: Generate rings and connect, with vertex sharing.
+        // When consecutive segments share the same material ID, the top ring of
+        // segment N is reused as the bottom ring of segment N+1.
+        // Displaced rings are recorded per bucket, along with where this
+        // strand's tube triangles start, so their normals can be rebuilt.
+        let mut ring_cache: Vec<Option<(u16, u32)>> = vec![None; n];
+        let mut first_ring = None;
+        let mut displaced: HashMap<u16, (usize, Vec<u32>)> = HashMap::new();
+
+        for i in 0..n - 1 {
+            let curr = points[i];
+            let next = points[i + 1];
+            let mat_id = curr.material_id as u16;
+            if self.cards.contains_key(&mat_id) || !keep[sources[i]] {
+                continue;
+            }
+            let profile = &profiles[&mat_id];
+            let displacement = self.material_displacement(mat_id);
+            let bucket = buckets.entry(mat_id).or_default();
+            let mut rings = displacement.is_active().then(|| {
+                &mut displaced
+                    .entry(mat_id)
+                    .or_insert_with(|| (bucket.indices.len(), Vec::new()))
+                    .1
+            });
+            let mut add_ring = |bucket: &mut MeshData, point: &SkeletonPoint, j: usize| {
+                let idx = Self::add_ring(
+                    bucket,
+                    point.position,
+                    rotations[j],
+                    point.radius,
+                    point.color,
+                    v_coords[j],
+                    profile,
+                );
+                if let Some(rings) = rings.as_mut() {
+                    let ring = idx as usize..idx as usize + profile.vertices.len();
+                    displacement.displace_ring(
+                        &mut bucket.positions[ring.clone()],
+                        &bucket.uvs[ring],
+                        point.position,
+                        point.radius,
+                    );
+                    rings.push(idx);
+                }
+                idx
+            };
+
+            // Bottom ring: reuse cached ring if same material bucket already has
+            // one. A fresh ring starts a new run, and with it a lightmap chart.
+            let bottom_idx = match ring_cache[i] {
+                Some((cached_mat, idx)) if cached_mat == mat_id => idx,
+                _ => {
+                    bucket.chart();
+                    add_ring(bucket, &curr, i)
+                }
+            };
+            tag(bucket, i);
+
+            // Top ring: always generate fresh
+            let top_idx = add_ring(bucket, &next, i + 1);
+            tag(bucket, i + 1);
+
+            bucket.mark(strand_idx, sources[i]);
+            if i == 0 {
+                first_ring = Some((mat_id, bottom_idx, bucket.indices.len()));
+            }
+            Self::connect_rings(bucket, bottom_idx, top_idx, profile);
+
+            // Cache the top ring for potential reuse by the next segment
+            ring_cache[i + 1] = Some((mat_id, top_idx));
+        }
+
+        // Phase 4: Seat the base ring on the parent surface, then rebuild the normals of every ring that moved, welded base
+        // included, so the junction shades from its final shape.
+        if let (Some(tube), Some((mat_id, ring_idx, first_index))) = (junction, first_ring) {
+            let flare = match self.junctions {
+                JunctionStyle::Blend { flare } => flare,
+                _ => 0.0,
+            };
+            Self::weld_ring(
+                buckets.entry(mat_id).or_default(),
+                ring_idx,
+                &points[0],
+                rotations[0] * Vec3::Y,
+                &tube,
+                flare,
+                weld_limit,
+                &profiles[&mat_id],
+            );
+            let rings = &mut displaced
+                .entry(mat_id)
+                .or_insert_with(|| (first_index, Vec::new()))
+                .1;
+            if !rings.contains(&ring_idx) {
+                rings.push(ring_idx);
+            }
+        }
+        for (mat_id, (first_index, rings)) in &displaced {
+            if let Some(bucket) = buckets.get_mut(mat_id) {
+                Self::recompute_ring_normals(bucket, *first_index, rings, &profiles[mat_id]);
+            }
+        }
+
+        // Phase 5: Close the strand ends. Each cap joins the bucket of the
+        // segment it terminates. Ribbons stay open. A welded base is closed
+        // flat: its center is inside the parent, so the cap stays hidden under
+        // the parent surface where a dome or point could poke through.
+        let (start_cap, end_cap) = match self.ribbons {
+            Some(_) => (CapStyle::Open, CapStyle::Open),
+            None => (self.start_cap, self.end_cap),
+        };
+        let start_cap = match (junction, start_cap) {
+            (Some(_), CapStyle::Dome | CapStyle::Point { .. }) => CapStyle::Flat,
+            _ => start_cap,
+        };
+        if let Some((mat_id, ring_idx, _)) = first_ring {
+            let bucket = buckets.entry(mat_id).or_default();
+            bucket.mark(strand_idx, sources[0]);
+            Self::add_cap(
+                bucket,
+                start_cap,
+                ring_idx,
+                &points[0],
+                rotations[0],
+                v_coords[0],
+                &profiles[&mat_id],
+                false,
+            );
+            tag(bucket, 0);
+        }
+        if let Some((mat_id, ring_idx)) = ring_cache[n - 1] {
+            let bucket = buckets.entry(mat_id).or_default();
+            bucket.mark(strand_idx, sources[n - 1]);
+            Self::add_cap(
+                bucket,
+                end_cap,
+                ring_idx,
+                &points[n - 1],
+                rotations[n - 1],
+                v_coords[n - 1],
+                &profiles[&mat_id],
+                true,
+            );
+            tag(bucket, n - 1);
+        }
+    }
+
+    /// Emits a card for every point of `points` whose material is a card
+    /// material.
+    fn add_cards(
+        &self,
+        buckets: &mut HashMap<u16, MeshData>,
+        strand_idx: usize,
+        points: &[SkeletonPoint],
+        tags: Option<&StrandHierarchy>,
+        joints: Option<&StrandJoints>,
+    ) {
+        let positions: Vec<Vec3> = points.iter().map(|p| p.position).collect();
+        let arcs = cumulative_arc_lengths(&positions);
+        let total = arcs.last().copied().unwrap_or(0.0);
+        for (point_idx, (point, &arc)) in points.iter().zip(&arcs).enumerate() {
+            let mat_id = point.material_id as u16;
+            let Some(style) = self.cards.get(&mat_id) else {
+                continue;
+            };
+            if !self.filter.keeps_point(strand_idx, point) {
+                continue;
+            }
+            let side = point.radius * style.size();
+            if !(side.is_finite() && side > 0.0 && point.position.is_finite()) {
+                continue;
+            }
+            let bucket = buckets.entry(mat_id).or_default();
+            let up = point.rotation * Vec3::Y * side;
+            let across = point.rotation * Vec3::X * side;
+            let facing = point.rotation * Vec3::Z;
+            bucket.mark(strand_idx, point_idx);
+            Self::add_card_quad(bucket, point, up, across, facing);
+            if let CardStyle::Crossed { .. } = style {
+                let across = point.rotation * Vec3::NEG_Z * side;
+                Self::add_card_quad(bucket, point, up, across, point.rotation * Vec3::X);
+            }
+            if let Some(layout) = joints {
+                bucket.tag_joints(layout.weights(arc));
+            }
+            let Some(strand) = tags else {
+                continue;
+            };
+            if self.hierarchy_attributes {
+                bucket.tag(VertexHierarchy {
+                    strand: strand.index,
+                    depth: strand.depth,
+                    progress: if total > 0.0 { arc / total } else { 0.0 },
+                    root_distance: strand.base_distance + arc,
+                    radius: point.radius,
+                });
+            }
+            if self.growth_attributes {
+                let birth = strand.birth(arc);
+                bucket.tag_growth(VertexGrowth {
+                    birth,
+                    spine: point.position,
+                    previous: point.position.extend(birth),
+                });
+            }
+        }
+    }
+
+    /// Adds one quad with its base centred on `point`, spanning `across`
+    /// horizontally and `up` vertically, wound to face `facing`.
+    fn add_card_quad(
+        data: &mut MeshData,
+        point: &SkeletonPoint,
+        up: Vec3,
+        across: Vec3,
+        facing: Vec3,
+    ) {
+        data.chart();
+        let start = data.positions.len() as u32;
+        let color = point.color.to_array();
+        let base_left = point.position - across * 0.5;
+        let corners = [
+            (base_left, [0.0, 1.0]),
+            (base_left + across, [1.0, 1.0]),
+            (base_left + across + up, [1.0, 0.0]),
+            (base_left + up, [0.0, 0.0]),
+        ];
+        for (position, uv) in corners {
+            data.positions.push(position);
+            data.normals.push(facing);
+            data.colors.push(color);
+            data.uvs.push(uv);
+        }
+        data.indices
+            .extend_from_slice(&[start, start + 1, start + 2, start, start + 2, start + 3]);
+    }
+
+    /// The cross-section used for segments of the given material.
+    fn material_cross_section(&self, material_id: u16) -> &CrossSection {
+        self.material_cross_sections
+            .get(&material_id)
+            .unwrap_or(&self.cross_section)
+    }
+
+    /// The displacement used for segments of the given material.
+    fn material_displacement(&self, material_id: u16) -> &Displacement {
+        self.material_displacements
+            .get(&material_id)
+            .unwrap_or(&self.displacement)
+    }
+
+    /// Ring resolution for one strand: the global resolution, or the
+    /// radius-derived value when adaptive resolution is enabled.
+    fn strand_resolution(&self, points: &[SkeletonPoint]) -> u32 {
+        let Some(adaptive) = self.adaptive else {
+            return self.resolution;
         };
+        let max_radius = points.iter().map(|p| p.radius).fold(0.0f32, f32::max);
+        let ideal = (max_radius * std::f32::consts::TAU / adaptive.edge_length).ceil();
+        if !ideal.is_finite() {
+            return self.resolution;
+        }
+        (ideal as u32).clamp(adaptive.min.min(self.resolution), self.resolution)
+    }
 
-        // Phase 3: Generate rings and connect, with vertex sharing.
-        // When consecutive segments share the same material ID, the top ring of
-        // segment N is reused as the bottom ring of segment N+1.
-        let mut ring_cache: Vec<Option<(u16, u32)>> = vec![None; n];
+    /// Approximates the parent tube around the child's base by the parent
+    /// segment closest to it. Returns `None` when the base lies outside the
+    /// parent tube or the child merely continues along the parent's axis.
+    fn find_parent_tube(parent: &[SkeletonPoint], child: &[SkeletonPoint]) -> Option<ParentTube> {
+        let base = child[0].position;
+        let mut best: Option<(f32, ParentTube)> = None;
 
-        for i in 0..n - 1 {
-            let curr = points[i];
-            let next = points[i + 1];
-            let mat_id = curr.material_id as u16;
-            let bucket = self.buckets.entry(mat_id).or_default();
+        for seg in parent.windows(2) {
+            let (a, b) = (&seg[0], &seg[1]);
+            let ab = b.position - a.position;
+            let len_sq = ab.length_squared();
+            if len_sq <= 0.000001 {
+                continue;
+            }
+            let t = ((base - a.position).dot(ab) / len_sq).clamp(0.0, 1.0);
+            let closest = a.position + ab * t;
+            let dist_sq = closest.distance_squared(base);
+            if best.is_none_or(|(d, _)| dist_sq < d) {
+                let tube = ParentTube {
+                    origin: a.position,
+                    dir: ab / len_sq.sqrt(),
+                    radius: a.radius + (b.radius - a.radius) * t,
+                    sin_angle: 1.0,
+                };
+                best = Some((dist_sq, tube));
+            }
+        }
 
-            // Bottom ring: reuse cached ring if same material bucket already has one
-            let bottom_idx = match ring_cache[i] {
-                Some((cached_mat, idx)) if cached_mat == mat_id => idx,
-                _ => Self::add_ring(
-                    bucket,
-                    curr.position,
-                    rotations[i],
-                    curr.radius,
-                    curr.color,
-                    v_coords[i],
-                    self.resolution,
-                ),
-            };
+        let (dist_sq, mut tube) = best?;
+        if dist_sq >= tube.radius * tube.radius || !tube.radius.is_finite() {
+            return None;
+        }
+        let tangent = (child[1].position - base).normalize_or_zero();
+        let along = tangent.dot(tube.dir);
+        let sin_sq = 1.0 - along * along;
+        if sin_sq < JUNCTION_MIN_SIN_SQ {
+            return None;
+        }
+        tube.sin_angle = sin_sq.sqrt();
+        Some(tube)
+    }
 
-            // Top ring: always generate fresh
-            let top_idx = Self::add_ring(
-                bucket,
-                next.position,
-                rotations[i + 1],
-                next.radius,
-                next.color,
-                v_coords[i + 1],
-                self.resolution,
-            );
+    /// Slides every vertex of the ring at `ring_start` along `tangent` until it
+    /// reaches the parent tube surface, moving at most `limit`. The ring is
+    /// widened by `1.0 + flare` first. V is advanced by the distance moved so
+    /// the bark texture is not stretched.
+    #[allow(clippy::too_many_arguments)]
+    fn weld_ring(
+        data: &mut MeshData,
+        ring_start: u32,
+        point: &SkeletonPoint,
+        tangent: Vec3,
+        tube: &ParentTube,
+        flare: f32,
+        limit: f32,
+        profile: &RingProfile,
+    ) {
+        let circumference = point.radius * profile.perimeter;
+        let v_per_unit = if circumference > 0.0001 {
+            point.uv_scale / circumference
+        } else {
+            point.uv_scale
+        };
+        let t_perp = tangent - tube.dir * tangent.dot(tube.dir);
+        let a = t_perp.length_squared();
 
-            Self::connect_rings(bucket, bottom_idx, top_idx, self.resolution);
+        for i in 0..profile.vertices.len() {
+            let idx = ring_start as usize + i;
+            let pos = point.position + (data.positions[idx] - point.position) * (1.0 + flare);
+            let w = pos - tube.origin;
+            let w_perp = w - tube.dir * w.dot(tube.dir);
+            // Solve |w_perp + s * t_perp| = radius for the exit root s >= 0.
+            let b = 2.0 * w_perp.dot(t_perp);
+            let c = w_perp.length_squared() - tube.radius * tube.radius;
+            let s = if c < 0.0 {
+                let disc = (b * b - 4.0 * a * c).max(0.0);
+                ((-b + disc.sqrt()) / (2.0 * a)).clamp(0.0, limit)
+            } else {
+                0.0
+            };
 
-            // Cache the top ring for potential reuse by the next segment
-            ring_cache[i + 1] = Some((mat_id, top_idx));
+            data.positions[idx] = pos + tangent * s;
+            data.uvs[idx][1] += s * v_per_unit;
         }
     }
 
@@ -274,33 +2944,274 @@ impl LSystemMeshBuilder {
         radius: f32,
         color: Vec4,
         v_coord: f32,
-        res: u32,
+        profile: &RingProfile,
     ) -> u32 {
         let start_index = data.positions.len() as u32;
         let color_array = color.to_array();
 
-        for i in 0..=res {
-            let u = i as f32 / res as f32;
-            let theta = u * std::f32::consts::TAU;
-            let (sin, cos) = theta.sin_cos();
-
-            let local_pos = Vec3::new(cos * radius, 0.0, sin * radius);
-            let local_normal = Vec3::new(cos, 0.0, sin);
+        for vertex in &profile.vertices {
+            let local_pos = Vec3::new(vertex.position.x, 0.0, vertex.position.y) * radius;
+            let local_normal = Vec3::new(vertex.normal.x, 0.0, vertex.normal.y);
 
             data.positions.push(center + (rotation * local_pos));
             data.normals.push(rotation * local_normal);
             data.colors.push(color_array);
-            data.uvs.push([u, v_coord]);
+            data.uvs.push([vertex.u, v_coord]);
+        }
+        start_index
+    }
+
+    /// Rebuilds the normals of displaced tube `rings` from the triangles added
+    /// to `data` since `first_index`, weighting each face by its area.
+    /// Coincident profile vertices with matching normals (the U seam, and
+    /// smooth corners) share one normal; hard corners keep theirs separate.
+    fn recompute_ring_normals(
+        data: &mut MeshData,
+        first_index: usize,
+        rings: &[u32],
+        profile: &RingProfile,
+    ) {
+        let Some(&base) = rings.iter().min() else {
+            return;
+        };
+        let base = base as usize;
+        let mut sums = vec![Vec3::ZERO; data.positions.len() - base];
+        for tri in data.indices[first_index..].chunks_exact(3) {
+            let [a, b, c] = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
+            if a < base || b < base || c < base {
+                continue;
+            }
+            let (pa, pb, pc) = (data.positions[a], data.positions[b], data.positions[c]);
+            let face = (pb - pa).cross(pc - pa);
+            for v in [a, b, c] {
+                sums[v - base] += face;
+            }
+        }
+
+        // Each profile vertex's twin: the first vertex at the same spot with
+        // the same normal, into which its sum is pooled.
+        let twins: Vec<usize> = profile
+            .vertices
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                profile.vertices[..i]
+                    .iter()
+                    .position(|w| {
+                        w.position.distance_squared(v.position) < 1e-8
+                            && w.normal.dot(v.normal) > 0.999
+                    })
+                    .unwrap_or(i)
+            })
+            .collect();
+
+        for &ring in rings {
+            let ring = ring as usize - base;
+            for (i, &twin) in twins.iter().enumerate() {
+                if twin != i {
+                    let sum = sums[ring + i];
+                    sums[ring + twin] += sum;
+                }
+            }
+            for (i, &twin) in twins.iter().enumerate() {
+                let v = base + ring + i;
+                data.normals[v] = sums[ring + twin].normalize_or(data.normals[v]);
+            }
+        }
+    }
+
+    /// Closes a strand end. `ring_start` is the tube ring at that end; `at_end`
+    /// selects whether the cap faces along (+) or against (-) the strand tangent.
+    #[allow(clippy::too_many_arguments)]
+    fn add_cap(
+        data: &mut MeshData,
+        style: CapStyle,
+        ring_start: u32,
+        point: &SkeletonPoint,
+        rotation: Quat,
+        v_coord: f32,
+        profile: &RingProfile,
+        at_end: bool,
+    ) {
+        if style == CapStyle::Open || point.radius <= f32::EPSILON {
+            return;
+        }
+        data.chart();
+
+        let sign = if at_end { 1.0 } else { -1.0 };
+        let axis = rotation * Vec3::Y * sign;
+        let radius = point.radius;
+        let color = point.color.to_array();
+        // V advances by arc length in units of the perimeter, matching the
+        // tube wall's aspect-ratio preserving parameterization.
+        let v_per_unit = point.uv_scale / (radius * profile.perimeter);
+
+        match style {
+            CapStyle::Open => {}
+            CapStyle::Flat => {
+                let center = data.positions.len() as u32;
+                data.positions.push(point.position);
+                data.normals.push(axis);
+                data.colors.push(color);
+                data.uvs.push([0.5, v_coord]);
+
+                // The rim copies the tube ring so displaced tubes stay closed.
+                let rim = data.positions.len() as u32;
+                for (i, vertex) in profile.vertices.iter().enumerate() {
+                    let local = vertex.position * radius;
+                    let position = data.positions[ring_start as usize + i];
+                    data.positions.push(position);
+                    data.normals.push(axis);
+                    data.colors.push(color);
+                    data.uvs
+                        .push([0.5 + local.x * v_per_unit, v_coord + local.y * v_per_unit]);
+                }
+                Self::connect_fan(data, rim, center, 0, profile, !at_end);
+            }
+            CapStyle::Dome => {
+                let segments = (profile.faces.len() as u32 / 4).max(2);
+                let mut prev = ring_start;
+                for k in 1..segments {
+                    let phi = k as f32 / segments as f32 * std::f32::consts::FRAC_PI_2;
+                    let (sin_phi, cos_phi) = phi.sin_cos();
+                    let ring = Self::add_tilted_ring(
+                        data,
+                        point.position + axis * radius * sin_phi,
+                        rotation,
+                        radius * cos_phi,
+                        axis,
+                        |_| phi,
+                        color,
+                        v_coord + sign * radius * phi * v_per_unit,
+                        profile,
+                    );
+                    if at_end {
+                        Self::connect_rings(data, prev, ring, profile);
+                    } else {
+                        Self::connect_rings(data, ring, prev, profile);
+                    }
+                    prev = ring;
+                }
+
+                let pole = data.positions.len() as u32;
+                let pole_v = v_coord + sign * radius * std::f32::consts::FRAC_PI_2 * v_per_unit;
+                for &face in &profile.faces {
+                    let (a, b) = Self::face_vertices(profile, face);
+                    data.positions.push(point.position + axis * radius);
+                    data.normals.push(axis);
+                    data.colors.push(color);
+                    data.uvs.push([(a.u + b.u) * 0.5, pole_v]);
+                }
+                Self::connect_fan(data, prev, pole, 1, profile, !at_end);
+            }
+            CapStyle::Point { length } => {
+                // Tilting each normal by its own support distance keeps the
+                // faces of polygonal profiles planar.
+                let tilt = |support: f32| support.atan2(length);
+                let ring = Self::add_tilted_ring(
+                    data,
+                    point.position,
+                    rotation,
+                    radius,
+                    axis,
+                    |vertex| tilt(RingProfile::support(vertex)),
+                    color,
+                    v_coord,
+                    profile,
+                );
+                let len = profile.vertices.len();
+                let (tube, cone) = data.positions.split_at_mut(ring as usize);
+                cone[..len].copy_from_slice(&tube[ring_start as usize..ring_start as usize + len]);
+
+                let apex = data.positions.len() as u32;
+                let slant = radius * (1.0 + length * length).sqrt();
+                for &face in &profile.faces {
+                    let (a, b) = Self::face_vertices(profile, face);
+                    let normal = (a.normal + b.normal).normalize_or(a.normal);
+                    let support = (RingProfile::support(a) + RingProfile::support(b)) * 0.5;
+                    let (sin_tilt, cos_tilt) = tilt(support).sin_cos();
+                    let radial = rotation * Vec3::new(normal.x, 0.0, normal.y);
+                    data.positions.push(point.position + axis * radius * length);
+                    data.normals.push(radial * cos_tilt + axis * sin_tilt);
+                    data.colors.push(color);
+                    data.uvs
+                        .push([(a.u + b.u) * 0.5, v_coord + sign * slant * v_per_unit]);
+                }
+                Self::connect_fan(data, ring, apex, 1, profile, !at_end);
+            }
+        }
+    }
+
+    /// The two profile vertices spanning the face starting at `face`.
+    fn face_vertices(profile: &RingProfile, face: u32) -> (&ProfileVertex, &ProfileVertex) {
+        let i = face as usize;
+        (&profile.vertices[i], &profile.vertices[i + 1])
+    }
+
+    /// Like [`Self::add_ring`], but each normal is tilted `tilt(vertex)` radians
+    /// from the profile normal towards `axis` (used for dome and cone caps).
+    #[allow(clippy::too_many_arguments)]
+    fn add_tilted_ring(
+        data: &mut MeshData,
+        center: Vec3,
+        rotation: Quat,
+        radius: f32,
+        axis: Vec3,
+        tilt: impl Fn(&ProfileVertex) -> f32,
+        color: [f32; 4],
+        v_coord: f32,
+        profile: &RingProfile,
+    ) -> u32 {
+        let start_index = data.positions.len() as u32;
+
+        for vertex in &profile.vertices {
+            let (sin_tilt, cos_tilt) = tilt(vertex).sin_cos();
+            let local = vertex.position * radius;
+            let normal = rotation * Vec3::new(vertex.normal.x, 0.0, vertex.normal.y);
+
+            data.positions
+                .push(center + rotation * Vec3::new(local.x, 0.0, local.y));
+            data.normals.push(normal * cos_tilt + axis * sin_tilt);
+            data.colors.push(color);
+            data.uvs.push([vertex.u, v_coord]);
         }
         start_index
     }
 
-    fn connect_rings(data: &mut MeshData, bottom_start: u32, top_start: u32, res: u32) {
-        for i in 0..res {
-            let bottom_curr = bottom_start + i;
-            let bottom_next = bottom_start + i + 1;
-            let top_curr = top_start + i;
-            let top_next = top_start + i + 1;
+    /// Connects a ring to apex vertex(es). `apex_stride` is 0 for a single
+    /// shared apex or 1 for one apex per ring face. `reverse` flips the
+    /// winding for caps that face against the strand tangent.
+    fn connect_fan(
+        data: &mut MeshData,
+        ring_start: u32,
+        apex_start: u32,
+        apex_stride: u32,
+        profile: &RingProfile,
+        reverse: bool,
+    ) {
+        for (i, &face) in profile.faces.iter().enumerate() {
+            let curr = ring_start + face;
+            let next = curr + 1;
+            let apex = apex_start + i as u32 * apex_stride;
+            if reverse {
+                data.indices.extend_from_slice(&[curr, next, apex]);
+            } else {
+                data.indices.extend_from_slice(&[curr, apex, next]);
+            }
+        }
+    }
+
+    fn connect_rings(
+        data: &mut MeshData,
+        bottom_start: u32,
+        top_start: u32,
+        profile: &RingProfile,
+    ) {
+        for &face in &profile.faces {
+            let bottom_curr = bottom_start + face;
+            let bottom_next = bottom_curr + 1;
+            let top_curr = top_start + face;
+            let top_next = top_curr + 1;
 
             data.indices.push(bottom_curr);
             data.indices.push(top_curr);
@@ -313,6 +3224,195 @@ impl LSystemMeshBuilder {
     }
 }
 
+/// Filters out duplicate adjacent points (zero-length segments) to prevent
+/// NaNs, returning the indices of the points whose position differs from the
+/// last kept one. Non-finite positions never differ, so they are dropped too.
+fn dedup_indices(points: &[SkeletonPoint]) -> Vec<usize> {
+    let mut result = vec![0];
+    for (i, point) in points.iter().enumerate().skip(1) {
+        let last = points[*result.last().unwrap()];
+        if last.position.distance_squared(point.position) > 0.000001 {
+            result.push(i);
+        }
+    }
+    result
+}
+
+/// Running arc length at every point of a polyline, starting at 0.
+pub(crate) fn cumulative_arc_lengths(positions: &[Vec3]) -> Vec<f32> {
+    let mut total = 0.0;
+    let mut arcs = Vec::with_capacity(positions.len());
+    for (i, p) in positions.iter().enumerate() {
+        if i > 0 {
+            total += positions[i - 1].distance(*p);
+        }
+        arcs.push(total);
+    }
+    arcs
+}
+
+/// Arc length along `strand` to the point on it closest to `target`.
+pub(crate) fn arc_length_to(strand: &[SkeletonPoint], target: Vec3) -> f32 {
+    let mut travelled = 0.0;
+    let mut best = (f32::INFINITY, 0.0);
+    if let Some(first) = strand.first() {
+        best = (first.position.distance_squared(target), 0.0);
+    }
+    for seg in strand.windows(2) {
+        let (a, b) = (seg[0].position, seg[1].position);
+        let ab = b - a;
+        let len_sq = ab.length_squared();
+        let t = if len_sq > 0.0 {
+            ((target - a).dot(ab) / len_sq).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let dist_sq = (a + ab * t).distance_squared(target);
+        let len = len_sq.sqrt();
+        if dist_sq < best.0 {
+            best = (dist_sq, travelled + len * t);
+        }
+        travelled += len;
+    }
+    best.1
+}
+
+// ---------------------------------------------------------------------------
+// Strand smoothing
+// ---------------------------------------------------------------------------
+
+/// Refines a de-duplicated strand (at least 2 points) with the given curve.
+/// Also returns, for every refined point, the index of the input point that
+/// starts the segment it was sampled from.
+fn smooth_strand(
+    points: &[SkeletonPoint],
+    smoothing: StrandSmoothing,
+) -> (Vec<SkeletonPoint>, Vec<usize>) {
+    let refined = match smoothing {
+        StrandSmoothing::None => return (points.to_vec(), (0..points.len()).collect()),
+        StrandSmoothing::CatmullRom { subdivisions } => catmull_rom_strand(points, subdivisions),
+        StrandSmoothing::Bezier { subdivisions } => bezier_strand(points, subdivisions),
+    };
+
+    // Curves can bunch samples together near sharp corners; drop any that
+    // would form zero-length segments.
+    let mut result: Vec<SkeletonPoint> = Vec::with_capacity(refined.len());
+    let mut segments = Vec::with_capacity(refined.len());
+    for (point, segment) in refined {
+        match result.last() {
+            Some(last) if last.position.distance_squared(point.position) <= 0.000001 => {}
+            _ => {
+                result.push(point);
+                segments.push(segment);
+            }
+        }
+    }
+    (result, segments)
+}
+
+/// Blends every interpolated attribute of `a` and `b`. Position and rotation
+/// are supplied by the caller; the material comes from `a`.
+fn blend_point(a: &SkeletonPoint, b: &SkeletonPoint, t: f32, position: Vec3) -> SkeletonPoint {
+    SkeletonPoint {
+        position,
+        rotation: a.rotation.slerp(b.rotation, t),
+        radius: a.radius + (b.radius - a.radius) * t,
+        color: a.color.lerp(b.color, t),
+        material_id: a.material_id,
+        uv_scale: a.uv_scale + (b.uv_scale - a.uv_scale) * t,
+    }
+}
+
+fn catmull_rom_strand(points: &[SkeletonPoint], subdivisions: u32) -> Vec<(SkeletonPoint, usize)> {
+    let n = points.len();
+    let steps = subdivisions + 1;
+    let mut out = Vec::with_capacity((n - 1) * steps as usize + 1);
+
+    for i in 0..n - 1 {
+        let p1 = points[i].position;
+        let p2 = points[i + 1].position;
+        // Reflect the neighbours at the strand ends so the tangent there
+        // follows the end segment.
+        let p0 = if i > 0 {
+            points[i - 1].position
+        } else {
+            2.0 * p1 - p2
+        };
+        let p3 = if i + 2 < n {
+            points[i + 2].position
+        } else {
+            2.0 * p2 - p1
+        };
+
+        out.push((points[i], i));
+        for step in 1..steps {
+            let t = step as f32 / steps as f32;
+            let position = centripetal_catmull_rom(p0, p1, p2, p3, t);
+            out.push((blend_point(&points[i], &points[i + 1], t, position), i));
+        }
+    }
+    out.push((points[n - 1], n - 1));
+    out
+}
+
+/// Evaluates the centripetal (alpha = 0.5) Catmull-Rom segment between `p1`
+/// and `p2` at `t` in `0..=1` (Barry-Goldman pyramid form).
+fn centripetal_catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let knot = |a: Vec3, b: Vec3| a.distance(b).sqrt().max(1e-6);
+    let t0 = 0.0;
+    let t1 = t0 + knot(p0, p1);
+    let t2 = t1 + knot(p1, p2);
+    let t3 = t2 + knot(p2, p3);
+    let t = t1 + (t2 - t1) * t;
+
+    let a1 = p0 * ((t1 - t) / (t1 - t0)) + p1 * ((t - t0) / (t1 - t0));
+    let a2 = p1 * ((t2 - t) / (t2 - t1)) + p2 * ((t - t1) / (t2 - t1));
+    let a3 = p2 * ((t3 - t) / (t3 - t2)) + p3 * ((t - t2) / (t3 - t2));
+    let b1 = a1 * ((t2 - t) / (t2 - t0)) + a2 * ((t - t0) / (t2 - t0));
+    let b2 = a2 * ((t3 - t) / (t3 - t1)) + a3 * ((t - t1) / (t3 - t1));
+    b1 * ((t2 - t) / (t2 - t1)) + b2 * ((t - t1) / (t2 - t1))
+}
+
+fn bezier_strand(points: &[SkeletonPoint], subdivisions: u32) -> Vec<(SkeletonPoint, usize)> {
+    let n = points.len();
+    if n < 3 {
+        return catmull_rom_strand(points, subdivisions);
+    }
+    let steps = subdivisions + 1;
+    let midpoint = |a: &SkeletonPoint, b: &SkeletonPoint| {
+        blend_point(a, b, 0.5, a.position.lerp(b.position, 0.5))
+    };
+
+    // Straight half-segment from the first point to the first midpoint.
+    let mut out = vec![(points[0], 0)];
+
+    for i in 1..n - 1 {
+        let (prev, ctrl, next) = (&points[i - 1], &points[i], &points[i + 1]);
+        let start = midpoint(prev, ctrl);
+        let end = midpoint(ctrl, next);
+        // Sample the quadratic piece start -> end with `ctrl` as control
+        // point. Samples before the corner belong to the incoming segment's
+        // material, later ones to the outgoing segment's.
+        for step in 0..steps {
+            let t = step as f32 / steps as f32;
+            let (w0, w1, w2) = ((1.0 - t) * (1.0 - t), 2.0 * (1.0 - t) * t, t * t);
+            let position = start.position * w0 + ctrl.position * w1 + end.position * w2;
+            let mut point = blend_point(&start, &end, t, position);
+            point.radius = start.radius * w0 + ctrl.radius * w1 + end.radius * w2;
+            point.color = start.color * w0 + ctrl.color * w1 + end.color * w2;
+            point.uv_scale = start.uv_scale * w0 + ctrl.uv_scale * w1 + end.uv_scale * w2;
+            let segment = if t < 0.5 { i - 1 } else { i };
+            point.material_id = points[segment].material_id;
+            out.push((point, segment));
+        }
+    }
+
+    // Closing half-segment from the last midpoint to the final point.
+    out.push((midpoint(&points[n - 2], &points[n - 1]), n - 2));
+    out.push((points[n - 1], n - 1));
+    out
+}
+
 // ---------------------------------------------------------------------------
 // Mesh cache
 // ---------------------------------------------------------------------------
@@ -324,8 +3424,9 @@ impl LSystemMeshBuilder {
 ///
 /// Auto-invalidation: each call to [`LSystemMeshBuilder::build_cached`]
 /// recomputes a fingerprint over the skeleton's strands (positions, rotations,
-/// radii, colors, material IDs, UV scales) plus the builder's resolution. If
-/// any of those change, the fingerprint changes and a fresh mesh is built. The
+/// radii, colors, material IDs, UV scales) plus the builder's resolution and
+/// any non-default geometry options (e.g. [`CapStyle`]). If any of those
+/// change, the fingerprint changes and a fresh mesh is built. The
 /// previous entry remains in the cache until [`MeshCache::clear`] is called —
 /// the cache does not LRU-evict on its own.
 ///
@@ -376,9 +3477,18 @@ impl MeshCache {
     /// Returns `true` if a fingerprint matching the given skeleton+resolution
     /// is already cached. Useful for tests and instrumentation. This does
     /// not bump the hit/miss counters.
+    ///
+    /// Only builds from a default-configured builder at `resolution` match.
+    /// For builders with any other option set, use
+    /// [`LSystemMeshBuilder::is_cached`].
     pub fn contains(&self, skeleton: &Skeleton, resolution: u32) -> bool {
-        self.entries
-            .contains_key(&compute_fingerprint(skeleton, resolution))
+        self.contains_fingerprint(compute_fingerprint(skeleton, resolution))
+    }
+
+    /// Returns `true` if an entry exists under an explicit fingerprint, as
+    /// passed to [`Self::get_or_insert_with`]. Does not bump the counters.
+    pub fn contains_fingerprint(&self, fingerprint: u64) -> bool {
+        self.entries.contains_key(&fingerprint)
     }
 
     /// Cumulative cache hits since construction (or last [`Self::reset_stats`]).
@@ -451,7 +3561,7 @@ impl LSystemMeshBuilder {
         cache: &mut MeshCache,
         meshes: &mut Assets<Mesh>,
     ) -> HashMap<u16, Handle<Mesh>> {
-        let fingerprint = compute_fingerprint(skeleton, self.resolution);
+        let fingerprint = self.fingerprint(skeleton);
         if let Some(handles) = cache.entries.get(&fingerprint) {
             cache.hits += 1;
             return handles.clone();
@@ -465,9 +3575,310 @@ impl LSystemMeshBuilder {
         cache.entries.insert(fingerprint, handles.clone());
         handles
     }
+
+    /// Returns `true` if [`Self::build_cached`] would hit `cache` for
+    /// `skeleton` under this builder's configuration. Does not bump the
+    /// hit/miss counters.
+    pub fn is_cached(&self, cache: &MeshCache, skeleton: &Skeleton) -> bool {
+        cache.contains_fingerprint(self.fingerprint(skeleton))
+    }
+
+    /// Fingerprint of `skeleton` under this builder's configuration. Options
+    /// left at their defaults contribute nothing, so a default builder agrees
+    /// with [`compute_skeleton_fingerprint`] at the same resolution.
+    pub(crate) fn fingerprint(&self, skeleton: &Skeleton) -> u64 {
+        let mut hasher = fingerprint_hasher(skeleton, self.resolution);
+        self.hash_options(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Hashes every non-default geometry option, each behind its own tag.
+    fn hash_options<H: Hasher>(&self, hasher: &mut H) {
+        if self.start_cap != CapStyle::Open || self.end_cap != CapStyle::Open {
+            "caps".hash(hasher);
+            self.start_cap.hash_into(hasher);
+            self.end_cap.hash_into(hasher);
+        }
+        match self.smoothing {
+            StrandSmoothing::None => {}
+            StrandSmoothing::CatmullRom { subdivisions } => {
+                "smoothing-catmull-rom".hash(hasher);
+                subdivisions.hash(hasher);
+            }
+            StrandSmoothing::Bezier { subdivisions } => {
+                "smoothing-bezier".hash(hasher);
+                subdivisions.hash(hasher);
+            }
+        }
+        if let Some(adaptive) = self.adaptive {
+            "adaptive-resolution".hash(hasher);
+            adaptive.min.hash(hasher);
+            adaptive.edge_length.to_bits().hash(hasher);
+        }
+        match self.junctions {
+            JunctionStyle::Disjoint => {}
+            JunctionStyle::Weld => "junction-weld".hash(hasher),
+            JunctionStyle::Blend { flare } => {
+                "junction-blend".hash(hasher);
+                flare.to_bits().hash(hasher);
+            }
+        }
+        if self.cross_section != CrossSection::Circle || !self.material_cross_sections.is_empty() {
+            "cross-section".hash(hasher);
+            self.cross_section.hash_into(hasher);
+            for (material_id, cross_section) in &self.material_cross_sections {
+                material_id.hash(hasher);
+                cross_section.hash_into(hasher);
+            }
+        }
+        if self.displacement.is_active()
+            || self
+                .material_displacements
+                .values()
+                .any(Displacement::is_active)
+        {
+            "displacement".hash(hasher);
+            self.displacement.hash_into(hasher);
+            for (material_id, displacement) in &self.material_displacements {
+                material_id.hash(hasher);
+                displacement.hash_into(hasher);
+            }
+        }
+        if self.hierarchy_attributes {
+            "hierarchy-attributes".hash(hasher);
+        }
+        if self.growth_attributes {
+            "growth-attributes".hash(hasher);
+        }
+        if let Some(facing) = self.ribbons {
+            "ribbons".hash(hasher);
+            facing.hash_into(hasher);
+        }
+        if let Some(padding) = self.lightmap_padding {
+            "lightmap-uvs".hash(hasher);
+            padding.to_bits().hash(hasher);
+        }
+        if self.shading != Shading::Smooth {
+            "shading".hash(hasher);
+            self.shading.hash_into(hasher);
+        }
+        if self.filter.is_active() {
+            "filter".hash(hasher);
+            self.filter.hash_into(hasher);
+        }
+        if !self.material_uvs.is_empty() {
+            "material-uvs".hash(hasher);
+            for (material_id, uv) in &self.material_uvs {
+                material_id.hash(hasher);
+                uv.hash_into(hasher);
+            }
+        }
+        if !self.cards.is_empty() {
+            "cards".hash(hasher);
+            for (material_id, style) in &self.cards {
+                material_id.hash(hasher);
+                style.hash_into(hasher);
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Strand cache
+// ---------------------------------------------------------------------------
+
+/// Per-strand vertex data kept between builds of an evolving skeleton, used
+/// by [`LSystemMeshBuilder::build_incremental`].
+///
+/// Where [`MeshCache`] keys whole skeletons, this cache keys each strand by
+/// its own points, the builder's options, and whatever else feeds that
+/// strand's geometry: its parent strand's points when junctions are enabled,
+/// and its hierarchy values when hierarchy or growth attributes are. Stepping
+/// a derivation by one generation then only re-meshes strands that were added
+/// or changed.
+///
+/// Growth attributes normalize by the skeleton's longest root distance, so a
+/// skeleton that grows longer invalidates every strand. Leave them off for
+/// simulations that re-mesh every tick.
+///
+/// # Invalidation
+///
+/// Entries stay cached until [`Self::clear`] or [`Self::evict_stale`] is
+/// called. Calling `evict_stale` after each build keeps only the strands the
+/// latest skeleton still uses.
+#[derive(Resource, Default, Debug)]
+pub struct StrandCache {
+    entries: HashMap<u64, StrandEntry>,
+    generation: u64,
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Debug)]
+struct StrandEntry {
+    buckets: HashMap<u16, MeshData>,
+    /// Value of [`StrandCache::generation`] when the entry was last used.
+    generation: u64,
+}
+
+impl StrandCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of cached strands.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop all cached strands. Hit/miss counters are preserved (use
+    /// [`Self::reset_stats`]).
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Drops every strand the most recent
+    /// [`LSystemMeshBuilder::build_incremental`] call did not use, returning
+    /// how many were removed.
+    pub fn evict_stale(&mut self) -> usize {
+        let before = self.entries.len();
+        let generation = self.generation;
+        self.entries
+            .retain(|_, entry| entry.generation == generation);
+        before - self.entries.len()
+    }
+
+    /// Cumulative strands reused since construction (or last
+    /// [`Self::reset_stats`]).
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Cumulative strands meshed since construction (or last
+    /// [`Self::reset_stats`]).
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Reset hit/miss counters without clearing entries.
+    pub fn reset_stats(&mut self) {
+        self.hits = 0;
+        self.misses = 0;
+    }
+}
+
+impl LSystemMeshBuilder {
+    /// Like [`LSystemMeshBuilder::build`], but reuses the vertex data of
+    /// strands already meshed into `cache` and only meshes the rest. Bumps the
+    /// cache's `hits`/`misses` counters once per strand.
+    ///
+    /// The output is identical to `build`. Skeletons that change a little
+    /// between calls — growth simulations stepping one generation per tick,
+    /// interactive editing — should prefer this over `build` or
+    /// [`Self::build_cached`], whose whole-skeleton fingerprint misses on any
+    /// change.
+    pub fn build_incremental(
+        self,
+        skeleton: &Skeleton,
+        cache: &mut StrandCache,
+    ) -> HashMap<u16, Mesh> {
+        cache.generation += 1;
+        let hierarchy = self.strand_hierarchy(skeleton);
+        let options = self.options_fingerprint();
+
+        let mut merged: HashMap<u16, MeshData> = HashMap::new();
+        for strand_idx in 0..skeleton.strands.len() {
+            let key = self.strand_fingerprint(options, skeleton, strand_idx, &hierarchy);
+            let entry = match cache.entries.entry(key) {
+                Entry::Occupied(occupied) => {
+                    cache.hits += 1;
+                    occupied.into_mut()
+                }
+                Entry::Vacant(vacant) => {
+                    cache.misses += 1;
+                    vacant.insert(StrandEntry {
+                        buckets: self.mesh_strands(
+                            skeleton,
+                            strand_idx..strand_idx + 1,
+                            &hierarchy,
+                            &[],
+                        ),
+                        generation: 0,
+                    })
+                }
+            };
+            entry.generation = cache.generation;
+            for (mat_id, data) in &entry.buckets {
+                merged.entry(*mat_id).or_default().append(data.clone());
+            }
+        }
+
+        merged
+            .into_iter()
+            .map(|(mat_id, data)| (mat_id, self.finish_mesh(data, mat_id)))
+            .collect()
+    }
+
+    /// Hash of the resolution and every non-default option, independent of
+    /// any skeleton.
+    fn options_fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.resolution.hash(&mut hasher);
+        self.hash_options(&mut hasher);
+        hasher.finish()
+    }
+
+    /// [`StrandCache`] key of one strand: the builder options plus every
+    /// input [`Self::mesh_strands`] reads for it.
+    fn strand_fingerprint(
+        &self,
+        options: u64,
+        skeleton: &Skeleton,
+        strand_idx: usize,
+        hierarchy: &[StrandHierarchy],
+    ) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        options.hash(&mut hasher);
+        let strand = &skeleton.strands[strand_idx];
+        strand.len().hash(&mut hasher);
+        for point in strand {
+            hash_skeleton_point(point, &mut hasher);
+        }
+        if strand.len() >= 2
+            && let Some(parent) = self.junction_parent(skeleton, strand_idx)
+        {
+            "junction-parent".hash(&mut hasher);
+            parent.len().hash(&mut hasher);
+            for point in parent {
+                hash_skeleton_point(point, &mut hasher);
+            }
+        }
+        if self.filter.predicate.is_some() {
+            "filtered-strand".hash(&mut hasher);
+            strand_idx.hash(&mut hasher);
+        }
+        if let Some(tags) = hierarchy.get(strand_idx) {
+            "hierarchy".hash(&mut hasher);
+            tags.index.hash(&mut hasher);
+            tags.depth.hash(&mut hasher);
+            tags.base_distance.to_bits().hash(&mut hasher);
+            if self.growth_attributes {
+                tags.reach.to_bits().hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
 }
 
 fn compute_fingerprint(skeleton: &Skeleton, resolution: u32) -> u64 {
+    fingerprint_hasher(skeleton, resolution).finish()
+}
+
+fn fingerprint_hasher(skeleton: &Skeleton, resolution: u32) -> DefaultHasher {
     let mut hasher = DefaultHasher::new();
     resolution.hash(&mut hasher);
     skeleton.strands.len().hash(&mut hasher);
@@ -478,7 +3889,7 @@ fn compute_fingerprint(skeleton: &Skeleton, resolution: u32) -> u64 {
         }
     }
     skeleton.strand_parents.hash(&mut hasher);
-    hasher.finish()
+    hasher
 }
 
 fn hash_skeleton_point<H: Hasher>(p: &SkeletonPoint, hasher: &mut H) {
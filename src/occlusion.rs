@@ -0,0 +1,416 @@
+//! Baked ambient occlusion for L-System meshes.
+//!
+//! Interior branches of a dense crown receive the same light as the outer
+//! shell unless something darkens them. [`bake_ambient_occlusion`] runs after
+//! meshing: it turns every skeleton segment into a capsule occluder, casts a
+//! fixed set of cosine-weighted hemisphere rays from each vertex, and darkens
+//! the vertex by the fraction of rays that hit a capsule within
+//! [`AmbientOcclusion::max_distance`].
+//!
+//! The result is written into [`Mesh::ATTRIBUTE_COLOR`], which the palette
+//! workflow already multiplies into the base colour, or into
+//! [`ATTRIBUTE_AMBIENT_OCCLUSION`] for custom shaders (see
+//! [`OcclusionTarget`]). The ray pattern is fixed, so bakes are deterministic
+//! and need no GPU: baked meshes can go straight to
+//! [`export`](crate::export).
+//!
+//! # Example
+//!
+//! ```ignore
+//! use bevy_symbios::LSystemMeshBuilder;
+//! use bevy_symbios::occlusion::{AmbientOcclusion, bake_ambient_occlusion};
+//!
+//! let mut meshes = LSystemMeshBuilder::new().build(&skeleton);
+//! bake_ambient_occlusion(&mut meshes, &skeleton, &AmbientOcclusion::new(2.0));
+//! ```
+//!
+//! Build an [`OcclusionVolume`] once and call [`OcclusionVolume::bake`] to
+//! bake several meshes of the same skeleton, e.g. LOD levels or the output of
+//! [`LSystemMeshBuilder::build_merged`](crate::LSystemMeshBuilder::build_merged).
+//! Meshes must be in the skeleton's space, so bake before placing them in a
+//! [`ForestBatch`](crate::forest::ForestBatch).
+
+use bevy::mesh::{MeshVertexAttribute, VertexAttributeValues, VertexFormat};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use symbios_turtle_3d::Skeleton;
+
+/// Baked ambient occlusion of a vertex: 1 for fully open, 0 for fully
+/// occluded. Only present on meshes baked with
+/// [`OcclusionTarget::Attribute`].
+pub const ATTRIBUTE_AMBIENT_OCCLUSION: MeshVertexAttribute = MeshVertexAttribute::new(
+    "Vertex_AmbientOcclusion",
+    1_406_201_526,
+    VertexFormat::Float32,
+);
+
+/// Maximum number of rays cast per vertex.
+const MAX_SAMPLES: u32 = 256;
+
+/// Capsules per BVH leaf.
+const LEAF_SIZE: usize = 4;
+
+/// Where [`bake_ambient_occlusion`] stores its result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OcclusionTarget {
+    /// Multiply the RGB of [`Mesh::ATTRIBUTE_COLOR`], leaving alpha alone.
+    /// Meshes without colours get white ones first.
+    #[default]
+    VertexColor,
+    /// Write [`ATTRIBUTE_AMBIENT_OCCLUSION`] and leave colours untouched.
+    Attribute,
+}
+
+/// Parameters of an ambient occlusion bake.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmbientOcclusion {
+    /// Rays cast per vertex, clamped to `1..=256`.
+    pub samples: u32,
+    /// Occluders farther than this along a ray are ignored, in mesh units.
+    pub max_distance: f32,
+    /// How strongly occlusion darkens: the baked value is
+    /// `1 - strength * hit_fraction`, clamped to `0..=1`.
+    pub strength: f32,
+    pub target: OcclusionTarget,
+}
+
+impl Default for AmbientOcclusion {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl AmbientOcclusion {
+    /// A bake with 32 rays per vertex reaching `max_distance`, at full
+    /// strength, written into vertex colours.
+    pub fn new(max_distance: f32) -> Self {
+        Self {
+            samples: 32,
+            max_distance: max_distance.max(0.0),
+            strength: 1.0,
+            target: OcclusionTarget::VertexColor,
+        }
+    }
+
+    /// Sets the rays cast per vertex. Values above 256 are clamped with a
+    /// warning; `0` is raised to 1.
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        if samples > MAX_SAMPLES {
+            warn!(
+                "Occlusion samples {} exceed maximum of {}; clamping to {}",
+                samples, MAX_SAMPLES, MAX_SAMPLES
+            );
+        }
+        self.samples = samples.clamp(1, MAX_SAMPLES);
+        self
+    }
+
+    /// Sets how strongly occlusion darkens vertices (negative values are
+    /// treated as 0).
+    pub fn with_strength(mut self, strength: f32) -> Self {
+        self.strength = strength.max(0.0);
+        self
+    }
+
+    pub fn with_target(mut self, target: OcclusionTarget) -> Self {
+        self.target = target;
+        self
+    }
+}
+
+/// A skeleton segment as a capsule with the larger of its end radii.
+#[derive(Debug, Clone, Copy)]
+struct Capsule {
+    a: Vec3,
+    b: Vec3,
+    radius: f32,
+}
+
+impl Capsule {
+    fn contains(&self, p: Vec3) -> bool {
+        let ab = self.b - self.a;
+        let t = if ab.length_squared() > 0.0 {
+            ((p - self.a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        p.distance_squared(self.a + ab * t) < self.radius * self.radius
+    }
+
+    /// Distance along the unit ray `origin + t * dir` to the capsule surface,
+    /// for an origin outside the capsule.
+    fn intersect(&self, origin: Vec3, dir: Vec3) -> Option<f32> {
+        let ba = self.b - self.a;
+        let oa = origin - self.a;
+        let baba = ba.dot(ba);
+        let bard = ba.dot(dir);
+        let baoa = ba.dot(oa);
+        let rdoa = dir.dot(oa);
+        let oaoa = oa.dot(oa);
+        let r2 = self.radius * self.radius;
+
+        // Cylinder body.
+        let a = baba - bard * bard;
+        let b = baba * rdoa - baoa * bard;
+        let c = baba * oaoa - baoa * baoa - r2 * baba;
+        let h = b * b - a * c;
+        if h < 0.0 {
+            return None;
+        }
+        if a > f32::EPSILON {
+            let t = (-b - h.sqrt()) / a;
+            let y = baoa + t * bard;
+            if y > 0.0 && y < baba {
+                return (t > 0.0).then_some(t);
+            }
+        }
+
+        // Hemispherical end caps.
+        let sphere = |center: Vec3| {
+            let oc = origin - center;
+            let b = dir.dot(oc);
+            let h = b * b - (oc.dot(oc) - r2);
+            (h >= 0.0).then(|| -b - h.sqrt()).filter(|&t| t > 0.0)
+        };
+        match (sphere(self.a), sphere(self.b)) {
+            (Some(s), Some(t)) => Some(s.min(t)),
+            (s, t) => s.or(t),
+        }
+    }
+
+    fn bounds(&self) -> (Vec3, Vec3) {
+        let r = Vec3::splat(self.radius);
+        (self.a.min(self.b) - r, self.a.max(self.b) + r)
+    }
+}
+
+/// One node of an [`OcclusionVolume`]'s bounding volume hierarchy. Leaves
+/// reference `count` capsules from `start`; inner nodes have `count == 0` and
+/// their children at `start` and `start + 1`.
+#[derive(Debug, Clone, Copy)]
+struct BvhNode {
+    min: Vec3,
+    max: Vec3,
+    start: usize,
+    count: usize,
+}
+
+/// The capsule occluders of one skeleton, in a bounding volume hierarchy for
+/// fast ray queries.
+#[derive(Debug, Clone, Default)]
+pub struct OcclusionVolume {
+    capsules: Vec<Capsule>,
+    nodes: Vec<BvhNode>,
+}
+
+impl OcclusionVolume {
+    /// One capsule per segment of every strand, including segments of card
+    /// materials, so foliage shades the branches behind it.
+    pub fn from_skeleton(skeleton: &Skeleton) -> Self {
+        let mut capsules: Vec<Capsule> = skeleton
+            .strands
+            .iter()
+            .flat_map(|strand| strand.windows(2))
+            .map(|pair| Capsule {
+                a: pair[0].position,
+                b: pair[1].position,
+                radius: pair[0].radius.max(pair[1].radius),
+            })
+            .filter(|c| c.a.is_finite() && c.b.is_finite() && c.radius > 0.0)
+            .collect();
+
+        let mut nodes = Vec::new();
+        if !capsules.is_empty() {
+            nodes.push(BvhNode {
+                min: Vec3::ZERO,
+                max: Vec3::ZERO,
+                start: 0,
+                count: 0,
+            });
+            let len = capsules.len();
+            Self::build_node(&mut capsules, &mut nodes, 0, 0, len);
+        }
+        Self { capsules, nodes }
+    }
+
+    /// Number of capsule occluders.
+    pub fn len(&self) -> usize {
+        self.capsules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.capsules.is_empty()
+    }
+
+    /// Fills in node `index` for `capsules[start..end]`, splitting at the
+    /// median centroid along the longest axis until leaves are small.
+    fn build_node(
+        capsules: &mut [Capsule],
+        nodes: &mut Vec<BvhNode>,
+        index: usize,
+        start: usize,
+        end: usize,
+    ) {
+        let (min, max) = capsules[start..end]
+            .iter()
+            .map(Capsule::bounds)
+            .fold((Vec3::MAX, Vec3::MIN), |(lo, hi), (a, b)| {
+                (lo.min(a), hi.max(b))
+            });
+        if end - start <= LEAF_SIZE {
+            nodes[index] = BvhNode {
+                min,
+                max,
+                start,
+                count: end - start,
+            };
+            return;
+        }
+
+        let extent = max - min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        capsules[start..end].sort_by(|p, q| (p.a + p.b)[axis].total_cmp(&(q.a + q.b)[axis]));
+        let mid = (start + end) / 2;
+
+        let children = nodes.len();
+        let placeholder = nodes[index];
+        nodes.push(placeholder);
+        nodes.push(placeholder);
+        nodes[index] = BvhNode {
+            min,
+            max,
+            start: children,
+            count: 0,
+        };
+        Self::build_node(capsules, nodes, children, start, mid);
+        Self::build_node(capsules, nodes, children + 1, mid, end);
+    }
+
+    /// Whether the unit ray from `origin` hits a capsule within `max_t`.
+    /// Capsules containing `origin` are ignored, so vertices never occlude
+    /// themselves through their own or an overlapping neighbour's segment.
+    fn occluded(&self, origin: Vec3, dir: Vec3, max_t: f32) -> bool {
+        if self.nodes.is_empty() {
+            return false;
+        }
+        let inv = dir.recip();
+        let mut stack = vec![0usize];
+        while let Some(index) = stack.pop() {
+            let node = self.nodes[index];
+            if !ray_hits_box(origin, inv, node.min, node.max, max_t) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node.start);
+                stack.push(node.start + 1);
+                continue;
+            }
+            let hit = self.capsules[node.start..node.start + node.count]
+                .iter()
+                .filter(|capsule| !capsule.contains(origin))
+                .any(|capsule| capsule.intersect(origin, dir).is_some_and(|t| t < max_t));
+            if hit {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Ambient occlusion of a surface point: 1 when no ray is blocked, down
+    /// to `1 - strength` when every ray is.
+    pub fn sample(&self, position: Vec3, normal: Vec3, settings: &AmbientOcclusion) -> f32 {
+        let normal = normal.normalize_or_zero();
+        if normal == Vec3::ZERO || settings.max_distance <= 0.0 {
+            return 1.0;
+        }
+        let samples = settings.samples.clamp(1, MAX_SAMPLES);
+        let (tangent, bitangent) = normal.any_orthonormal_pair();
+        let origin = position + normal * (settings.max_distance * 1e-3);
+        let hits = (0..samples)
+            .filter(|&i| {
+                let local = hemisphere_direction(i, samples);
+                let dir = tangent * local.x + bitangent * local.y + normal * local.z;
+                self.occluded(origin, dir, settings.max_distance)
+            })
+            .count();
+        let fraction = hits as f32 / samples as f32;
+        (1.0 - settings.strength * fraction).clamp(0.0, 1.0)
+    }
+
+    /// Bakes ambient occlusion into one mesh. Meshes without positions or
+    /// normals are left unchanged with a warning.
+    pub fn bake(&self, mesh: &mut Mesh, settings: &AmbientOcclusion) {
+        let (Some(positions), Some(normals)) = (
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+                .and_then(VertexAttributeValues::as_float3),
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+                .and_then(VertexAttributeValues::as_float3),
+        ) else {
+            warn!("Cannot bake ambient occlusion into a mesh without positions and normals");
+            return;
+        };
+        let occlusion: Vec<f32> = positions
+            .iter()
+            .zip(normals)
+            .map(|(p, n)| self.sample(Vec3::from_array(*p), Vec3::from_array(*n), settings))
+            .collect();
+
+        match settings.target {
+            OcclusionTarget::Attribute => {
+                mesh.insert_attribute(ATTRIBUTE_AMBIENT_OCCLUSION, occlusion);
+            }
+            OcclusionTarget::VertexColor => {
+                let mut colors: Vec<[f32; 4]> = match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+                    Some(VertexAttributeValues::Float32x4(colors)) => colors.clone(),
+                    _ => vec![[1.0; 4]; occlusion.len()],
+                };
+                for (color, ao) in colors.iter_mut().zip(&occlusion) {
+                    for channel in &mut color[..3] {
+                        *channel *= ao;
+                    }
+                }
+                mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+            }
+        }
+    }
+}
+
+/// Bakes ambient occlusion from `skeleton`'s capsules into every mesh of
+/// `meshes`, as returned by
+/// [`LSystemMeshBuilder::build`](crate::LSystemMeshBuilder::build).
+pub fn bake_ambient_occlusion(
+    meshes: &mut HashMap<u16, Mesh>,
+    skeleton: &Skeleton,
+    settings: &AmbientOcclusion,
+) {
+    let volume = OcclusionVolume::from_skeleton(skeleton);
+    for mesh in meshes.values_mut() {
+        volume.bake(mesh, settings);
+    }
+}
+
+/// Direction `i` of `n` cosine-weighted hemisphere samples around +Z, spread
+/// on a golden-angle spiral.
+fn hemisphere_direction(i: u32, n: u32) -> Vec3 {
+    const GOLDEN_ANGLE: f32 = 2.399_963;
+    let u = (i as f32 + 0.5) / n as f32;
+    let r = u.sqrt();
+    let (sin, cos) = (i as f32 * GOLDEN_ANGLE).sin_cos();
+    Vec3::new(r * cos, r * sin, (1.0 - u).sqrt())
+}
+
+/// Slab test of the ray `origin + t * dir` (given `1 / dir`) against a box,
+/// for `t` in `0..=max_t`.
+fn ray_hits_box(origin: Vec3, inv_dir: Vec3, min: Vec3, max: Vec3, max_t: f32) -> bool {
+    let t0 = (min - origin) * inv_dir;
+    let t1 = (max - origin) * inv_dir;
+    let near = t0.min(t1).max_element().max(0.0);
+    let far = t0.max(t1).min_element().min(max_t);
+    near <= far
+}
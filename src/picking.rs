@@ -0,0 +1,184 @@
+//! Mapping generated triangles back to the skeleton, and click picking.
+//!
+//! [`LSystemMeshBuilder::build_mapped`](crate::LSystemMeshBuilder::build_mapped)
+//! returns a [`SkeletonMap`] next to every material mesh. It stores one entry
+//! per run of triangles emitted for the same skeleton point, so it stays small
+//! even for dense meshes.
+//!
+//! Insert the map as a component on the entity holding the mesh and add
+//! [`SkeletonPickingPlugin`] (next to Bevy's `MeshPickingPlugin` or another
+//! picking backend). Clicking such an entity then triggers a [`SkeletonPicked`]
+//! event with the strand and point that were hit.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use bevy_symbios::LSystemMeshBuilder;
+//! use bevy_symbios::picking::{SkeletonPicked, SkeletonPickingPlugin};
+//!
+//! app.add_plugins((MeshPickingPlugin, SkeletonPickingPlugin));
+//!
+//! for (mat_id, (mesh, map)) in LSystemMeshBuilder::new().build_mapped(&skeleton) {
+//!     commands.spawn((Mesh3d(meshes.add(mesh)), MeshMaterial3d(palette[&mat_id].clone()), map));
+//! }
+//!
+//! app.add_observer(|picked: On<SkeletonPicked>| {
+//!     info!("strand {} point {}", picked.location.strand, picked.location.point);
+//! });
+//! ```
+
+use bevy::picking::mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings, RayCastVisibility};
+use bevy::picking::pointer::PointerButton;
+use bevy::prelude::*;
+
+/// A point of a [`Skeleton`](symbios_turtle_3d::Skeleton): the strand index
+/// and the index of the point within that strand.
+///
+/// Tube triangles map to the point that starts their segment, even when the
+/// strand was smoothed or had duplicate points dropped before meshing. End
+/// caps map to the strand's first or last point, cards to their own point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub struct SkeletonLocation {
+    pub strand: u32,
+    pub point: u32,
+}
+
+/// Triangle ranges of one mesh and the skeleton point each was built from.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct SkeletonMap {
+    /// First triangle of every run, ascending.
+    starts: Vec<u32>,
+    locations: Vec<SkeletonLocation>,
+    /// Triangle count of the mesh; runs end here or at the next start.
+    triangles: u32,
+}
+
+impl SkeletonMap {
+    /// The skeleton point that produced triangle `triangle` (the index into
+    /// the mesh's index buffer divided by three).
+    pub fn locate(&self, triangle: usize) -> Option<SkeletonLocation> {
+        if triangle >= self.triangles as usize {
+            return None;
+        }
+        let run = self.starts.partition_point(|&s| s as usize <= triangle);
+        run.checked_sub(1).map(|run| self.locations[run])
+    }
+
+    /// Every run as a range of triangle indices and its skeleton point.
+    pub fn ranges(&self) -> impl Iterator<Item = (std::ops::Range<u32>, SkeletonLocation)> + '_ {
+        let ends = self.starts.iter().skip(1).copied().chain([self.triangles]);
+        self.starts
+            .iter()
+            .zip(ends)
+            .zip(&self.locations)
+            .map(|((&start, end), &location)| (start..end, location))
+    }
+
+    /// Number of runs.
+    pub fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+
+    /// Starts a run at `first_triangle`, unless the current run already
+    /// belongs to `location`.
+    pub(crate) fn push(&mut self, first_triangle: u32, location: SkeletonLocation) {
+        if self.locations.last() == Some(&location) {
+            return;
+        }
+        if self.starts.last() == Some(&first_triangle) {
+            // The previous run never received a triangle.
+            self.starts.pop();
+            self.locations.pop();
+            if self.locations.last() == Some(&location) {
+                return;
+            }
+        }
+        self.starts.push(first_triangle);
+        self.locations.push(location);
+    }
+
+    /// Appends `other`, whose triangles follow the first `offset` of this map.
+    pub(crate) fn append(&mut self, other: SkeletonMap, offset: u32) {
+        for (start, location) in other.starts.into_iter().zip(other.locations) {
+            self.push(start + offset, location);
+        }
+    }
+
+    /// Closes the last run at `triangles`.
+    pub(crate) fn finish(mut self, triangles: u32) -> Self {
+        while self.starts.last().is_some_and(|&start| start >= triangles) {
+            self.starts.pop();
+            self.locations.pop();
+        }
+        self.triangles = triangles;
+        self
+    }
+}
+
+/// Triggered on an entity with a [`SkeletonMap`] when a pointer clicks it.
+#[derive(EntityEvent, Debug, Clone, Copy, PartialEq)]
+pub struct SkeletonPicked {
+    /// The clicked mesh entity.
+    pub entity: Entity,
+    pub location: SkeletonLocation,
+    /// World-space hit position.
+    pub position: Vec3,
+    pub button: PointerButton,
+}
+
+/// Resolves clicks on meshes carrying a [`SkeletonMap`] into
+/// [`SkeletonPicked`] events. Requires a picking backend that reports hit
+/// positions and normals, such as Bevy's `MeshPickingPlugin`.
+pub struct SkeletonPickingPlugin;
+
+impl Plugin for SkeletonPickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(pick_skeleton);
+    }
+}
+
+/// Observer behind [`SkeletonPickingPlugin`]. Picking hits carry no triangle
+/// index, so the hit triangle is found again by casting a short ray into the
+/// surface along the hit normal.
+pub fn pick_skeleton(
+    click: On<Pointer<Click>>,
+    maps: Query<&SkeletonMap>,
+    mut ray_cast: MeshRayCast,
+    mut commands: Commands,
+) {
+    let entity = click.entity;
+    let Ok(map) = maps.get(entity) else {
+        return;
+    };
+    let hit = &click.event.hit;
+    let (Some(position), Some(normal)) = (hit.position, hit.normal) else {
+        return;
+    };
+    let Ok(direction) = Dir3::new(-normal) else {
+        return;
+    };
+    let offset = 1e-3 * position.length().max(1.0);
+    let ray = Ray3d::new(position - *direction * offset, direction);
+    let filter = |candidate: Entity| candidate == entity;
+    let settings = MeshRayCastSettings::default()
+        .with_visibility(RayCastVisibility::Any)
+        .with_filter(&filter);
+    let Some(location) = ray_cast
+        .cast_ray(ray, &settings)
+        .first()
+        .and_then(|(_, hit)| hit.triangle_index)
+        .and_then(|triangle| map.locate(triangle))
+    else {
+        return;
+    };
+    commands.trigger(SkeletonPicked {
+        entity,
+        location,
+        position,
+        button: click.event.button,
+    });
+}
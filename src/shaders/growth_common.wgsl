@@ -0,0 +1,37 @@
+#define_import_path bevy_symbios::growth
+
+// Mirrors `GrowthUniform` in `src/growth.rs`.
+struct GrowthUniform {
+    progress: f32,
+    ramp: f32,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> growth: GrowthUniform;
+
+// Mesh-space position of a vertex at the current growth progress.
+//
+// The growth front runs from 0 to `1 + ramp` as progress goes from 0 to 1, so
+// the farthest tips finish thickening exactly at full progress. Born vertices
+// scale their offset from the spine from 0 to 1 over `ramp` behind the front,
+// so tubes taper to a point at the tip. Unborn vertices collapse onto the
+// front itself, placed between the previous spine point and their own, which
+// leaves only degenerate triangles past the tip.
+fn grown_position(
+    position: vec3<f32>,
+    birth: f32,
+    spine: vec3<f32>,
+    previous: vec4<f32>,
+) -> vec3<f32> {
+    let ramp = max(growth.ramp, 1e-4);
+    let front = growth.progress * (1.0 + ramp);
+    if birth > front {
+        let span = birth - previous.w;
+        var t = 0.0;
+        if span > 1e-6 {
+            t = clamp((front - previous.w) / span, 0.0, 1.0);
+        }
+        return mix(previous.xyz, spine, t);
+    }
+    let scale = clamp((front - birth) / ramp, 0.0, 1.0);
+    return spine + (position - spine) * scale;
+}
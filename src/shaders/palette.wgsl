@@ -0,0 +1,176 @@
+// Forward shaders for `PaletteMaterial`: Bevy's mesh vertex stage plus a flat
+// per-vertex material index, and the standard PBR fragment stage with its
+// scalar parameters replaced from the palette storage buffer. Skinning and
+// morph targets are not supported.
+
+#import bevy_pbr::{
+    mesh_functions,
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::{alpha_discard, apply_pbr_lighting, main_pass_post_lighting_processing},
+    pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT,
+    view_transformations::position_world_to_clip,
+}
+
+#ifdef VISIBILITY_RANGE_DITHER
+#import bevy_pbr::pbr_functions::visibility_range_dither;
+#endif
+
+// Mirrors `PaletteSlot` in `merged.rs`.
+struct PaletteSlot {
+    base_color: vec4<f32>,
+    emissive: vec4<f32>,
+    perceptual_roughness: f32,
+    metallic: f32,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<storage, read> palette_slots: array<PaletteSlot>;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+#ifdef VERTEX_NORMALS
+    @location(1) normal: vec3<f32>,
+#endif
+#ifdef VERTEX_UVS_A
+    @location(2) uv: vec2<f32>,
+#endif
+#ifdef VERTEX_UVS_B
+    @location(3) uv_b: vec2<f32>,
+#endif
+#ifdef VERTEX_TANGENTS
+    @location(4) tangent: vec4<f32>,
+#endif
+#ifdef VERTEX_COLORS
+    @location(5) color: vec4<f32>,
+#endif
+    // `MATERIAL_INDEX_SHADER_LOCATION` in `merged.rs`.
+    @location(8) material_index: u32,
+}
+
+// `forward_io::VertexOutput` plus the material index, at the first location
+// Bevy leaves free.
+struct PaletteVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+    @location(1) world_normal: vec3<f32>,
+#ifdef VERTEX_UVS_A
+    @location(2) uv: vec2<f32>,
+#endif
+#ifdef VERTEX_UVS_B
+    @location(3) uv_b: vec2<f32>,
+#endif
+#ifdef VERTEX_TANGENTS
+    @location(4) world_tangent: vec4<f32>,
+#endif
+#ifdef VERTEX_COLORS
+    @location(5) color: vec4<f32>,
+#endif
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    @location(6) @interpolate(flat) instance_index: u32,
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+    @location(7) @interpolate(flat) visibility_range_dither: i32,
+#endif
+    @location(8) @interpolate(flat) material_index: u32,
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> PaletteVertexOutput {
+    var out: PaletteVertexOutput;
+
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+
+#ifdef VERTEX_NORMALS
+    out.world_normal = mesh_functions::mesh_normal_local_to_world(
+        vertex.normal,
+        vertex.instance_index
+    );
+#endif
+
+    out.world_position = mesh_functions::mesh_position_local_to_world(
+        world_from_local,
+        vec4<f32>(vertex.position, 1.0)
+    );
+    out.position = position_world_to_clip(out.world_position.xyz);
+
+#ifdef VERTEX_UVS_A
+    out.uv = vertex.uv;
+#endif
+#ifdef VERTEX_UVS_B
+    out.uv_b = vertex.uv_b;
+#endif
+
+#ifdef VERTEX_TANGENTS
+    out.world_tangent = mesh_functions::mesh_tangent_local_to_world(
+        world_from_local,
+        vertex.tangent,
+        vertex.instance_index
+    );
+#endif
+
+#ifdef VERTEX_COLORS
+    out.color = vertex.color;
+#endif
+
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex.instance_index;
+#endif
+
+#ifdef VISIBILITY_RANGE_DITHER
+    out.visibility_range_dither = mesh_functions::get_visibility_range_dither_level(
+        vertex.instance_index, world_from_local[3]);
+#endif
+
+    out.material_index = vertex.material_index;
+    return out;
+}
+
+@fragment
+fn fragment(
+    palette_in: PaletteVertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var in: VertexOutput;
+    in.position = palette_in.position;
+    in.world_position = palette_in.world_position;
+    in.world_normal = palette_in.world_normal;
+#ifdef VERTEX_UVS_A
+    in.uv = palette_in.uv;
+#endif
+#ifdef VERTEX_UVS_B
+    in.uv_b = palette_in.uv_b;
+#endif
+#ifdef VERTEX_TANGENTS
+    in.world_tangent = palette_in.world_tangent;
+#endif
+#ifdef VERTEX_COLORS
+    in.color = palette_in.color;
+#endif
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    in.instance_index = palette_in.instance_index;
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+    in.visibility_range_dither = palette_in.visibility_range_dither;
+    visibility_range_dither(in.position, in.visibility_range_dither);
+#endif
+
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    let slot = palette_slots[min(palette_in.material_index, arrayLength(&palette_slots) - 1u)];
+    pbr_input.material.base_color *= slot.base_color;
+    pbr_input.material.emissive = slot.emissive;
+    pbr_input.material.perceptual_roughness = slot.perceptual_roughness;
+    pbr_input.material.metallic = slot.metallic;
+
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+    var out: FragmentOutput;
+    if (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_UNLIT_BIT) == 0u {
+        out.color = apply_pbr_lighting(pbr_input);
+    } else {
+        out.color = pbr_input.material.base_color;
+    }
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+    return out;
+}
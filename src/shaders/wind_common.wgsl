@@ -0,0 +1,48 @@
+#define_import_path bevy_symbios::wind
+
+// Mirrors `WindUniform` in `src/wind.rs`.
+struct WindUniform {
+    direction: vec3<f32>,
+    strength: f32,
+    gust_frequency: f32,
+    flexibility: f32,
+    stiffness: f32,
+    flutter: f32,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> wind: WindUniform;
+
+const TAU: f32 = 6.28318530718;
+
+// Bevy wraps `globals.time` back to zero after an hour.
+const TIME_WRAP_PERIOD: f32 = 3600.0;
+
+// `globals.time` on the previous frame, for motion vectors. On the first frame
+// after the clock wraps, the previous frame still ran just before the wrap.
+fn previous_time(time: f32, delta_time: f32) -> f32 {
+    let previous = time - delta_time;
+    return select(previous, previous + TIME_WRAP_PERIOD, previous < 0.0);
+}
+
+// World-space displacement of a vertex at `time`.
+//
+// `origin` is the translation of the mesh's world transform; it decorrelates
+// the gust phase of neighbouring trees. The main bend grows with the square of
+// the distance from the root and shrinks with branch radius, so trunks barely
+// lean while thin tips swing. Flutter is a faster sideways oscillation scaled
+// by branch depth, so leaves and twigs shiver while the trunk stays calm.
+fn wind_offset(
+    origin: vec3<f32>,
+    depth: u32,
+    root_distance: f32,
+    radius: f32,
+    time: f32,
+) -> vec3<f32> {
+    let phase = dot(origin.xz, vec2<f32>(0.37, 0.71));
+    let sway = TAU * wind.gust_frequency * time + phase;
+    let gust = 0.65 + 0.35 * sin(sway);
+    let bend = wind.flexibility * root_distance * root_distance / (1.0 + wind.stiffness * radius);
+    let side = vec3<f32>(-wind.direction.z, 0.0, wind.direction.x);
+    let flutter = wind.flutter * f32(depth) * sin(4.0 * sway + 3.0 * root_distance);
+    return wind.strength * (wind.direction * gust * bend + side * flutter);
+}
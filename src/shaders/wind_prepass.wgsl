@@ -0,0 +1,119 @@
+// Prepass / shadow / deferred vertex shader for `WindMaterial`, so depth,
+// normals, motion vectors and shadows follow the displaced geometry. Skinning
+// and morph targets are not supported.
+
+#import bevy_pbr::{
+    mesh_functions,
+    prepass_io::VertexOutput,
+    view_transformations::position_world_to_clip,
+}
+#import bevy_render::globals::Globals
+#import bevy_symbios::wind::{previous_time, wind_offset}
+
+// The prepass view layout binds the globals at 1, not at the main pass's 11
+// from `mesh_view_bindings`.
+@group(0) @binding(1) var<uniform> globals: Globals;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+#ifdef VERTEX_UVS_A
+    @location(1) uv: vec2<f32>,
+#endif
+#ifdef VERTEX_UVS_B
+    @location(2) uv_b: vec2<f32>,
+#endif
+#ifdef NORMAL_PREPASS_OR_DEFERRED_PREPASS
+#ifdef VERTEX_NORMALS
+    @location(3) normal: vec3<f32>,
+#endif
+#ifdef VERTEX_TANGENTS
+    @location(4) tangent: vec4<f32>,
+#endif
+#endif // NORMAL_PREPASS_OR_DEFERRED_PREPASS
+#ifdef VERTEX_COLORS
+    @location(7) color: vec4<f32>,
+#endif
+    @location(10) branch_depth: u32,
+    @location(11) root_distance: f32,
+    @location(12) branch_radius: f32,
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+    let origin = world_from_local[3].xyz;
+
+    let offset = wind_offset(
+        origin,
+        vertex.branch_depth,
+        vertex.root_distance,
+        vertex.branch_radius,
+        globals.time,
+    );
+    out.world_position = mesh_functions::mesh_position_local_to_world(
+        world_from_local,
+        vec4<f32>(vertex.position, 1.0)
+    ) + vec4<f32>(offset, 0.0);
+    out.position = position_world_to_clip(out.world_position.xyz);
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+    out.unclipped_depth = out.position.z;
+    out.position.z = min(out.position.z, 1.0);
+#endif
+
+#ifdef VERTEX_UVS_A
+    out.uv = vertex.uv;
+#endif
+#ifdef VERTEX_UVS_B
+    out.uv_b = vertex.uv_b;
+#endif
+
+#ifdef NORMAL_PREPASS_OR_DEFERRED_PREPASS
+#ifdef VERTEX_NORMALS
+    out.world_normal = mesh_functions::mesh_normal_local_to_world(
+        vertex.normal,
+        vertex.instance_index
+    );
+#endif
+#ifdef VERTEX_TANGENTS
+    out.world_tangent = mesh_functions::mesh_tangent_local_to_world(
+        world_from_local,
+        vertex.tangent,
+        vertex.instance_index
+    );
+#endif
+#endif // NORMAL_PREPASS_OR_DEFERRED_PREPASS
+
+#ifdef VERTEX_COLORS
+    out.color = vertex.color;
+#endif
+
+#ifdef MOTION_VECTOR_PREPASS
+    let previous_world_from_local =
+        mesh_functions::get_previous_world_from_local(vertex.instance_index);
+    let previous_offset = wind_offset(
+        origin,
+        vertex.branch_depth,
+        vertex.root_distance,
+        vertex.branch_radius,
+        previous_time(globals.time, globals.delta_time),
+    );
+    out.previous_world_position = mesh_functions::mesh_position_local_to_world(
+        previous_world_from_local,
+        vec4<f32>(vertex.position, 1.0)
+    ) + vec4<f32>(previous_offset, 0.0);
+#endif
+
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex.instance_index;
+#endif
+
+#ifdef VISIBILITY_RANGE_DITHER
+    out.visibility_range_dither = mesh_functions::get_visibility_range_dither_level(
+        vertex.instance_index, world_from_local[3]);
+#endif
+
+    return out;
+}
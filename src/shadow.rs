@@ -0,0 +1,282 @@
+//! Cheap shadow casters for L-System meshes.
+//!
+//! Shadow maps are rendered once per light (and per cascade), so on large
+//! forests the full-resolution tree meshes dominate the shadow passes. A
+//! shadow proxy is a single, much coarser mesh of the same skeleton that
+//! casts the shadow instead:
+//!
+//! - tubes use a low ring resolution and no smoothing,
+//! - thin strands (twigs) are dropped and nearly straight runs merged, as for
+//!   a [`LodLevel`],
+//! - card materials are left out, and
+//! - only positions, normals and indices are kept (no UVs or colours).
+//!
+//! [`LSystemMeshBuilder::build_with_shadow_proxy`] builds the visual meshes and
+//! the proxy together. [`spawn_shadow_proxied`] spawns the visual meshes with
+//! [`NotShadowCaster`] and the proxy on [`SHADOW_PROXY_LAYER`] only, which no
+//! camera renders but every light whose [`RenderLayers`] include it (see
+//! [`shadow_caster_layers`]) uses for its shadow maps. Card meshes keep casting
+//! their own shadows, so alpha-masked leaves still cast leaf-shaped shadows.
+//!
+//! The proxy is static: it does not follow [`wind`](crate::wind) sway or
+//! [`growth`](crate::growth) reveals of the visual meshes.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use bevy_symbios::LSystemMeshBuilder;
+//! use bevy_symbios::shadow::*;
+//!
+//! commands.spawn((
+//!     DirectionalLight { shadows_enabled: true, ..default() },
+//!     shadow_caster_layers(),
+//! ));
+//!
+//! let handles = LSystemMeshBuilder::new()
+//!     .with_resolution(12)
+//!     .build_with_shadow_proxy(&skeleton, &ShadowProxy::default())
+//!     .into_handles(&mut meshes);
+//! spawn_shadow_proxied(&mut commands, tree, &handles, &palette);
+//! ```
+
+use std::collections::BTreeSet;
+
+use bevy::asset::RenderAssetUsages;
+use bevy::camera::visibility::{Layer, RenderLayers};
+use bevy::light::NotShadowCaster;
+use bevy::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use symbios_turtle_3d::Skeleton;
+
+use crate::lod::{LodLevel, simplify_skeleton};
+use crate::materials::MaterialPalette;
+use crate::mesher::{LSystemMeshBuilder, Shading, StrandSmoothing};
+
+/// The render layer shadow proxies are spawned on. Cameras render layer 0
+/// by default, so nothing on this layer is ever drawn to the screen.
+pub const SHADOW_PROXY_LAYER: Layer = 31;
+
+/// Render layers for a shadow-casting light: the default layer plus
+/// [`SHADOW_PROXY_LAYER`], so the light sees both the regular scene and the
+/// shadow proxies.
+pub fn shadow_caster_layers() -> RenderLayers {
+    RenderLayers::default().with(SHADOW_PROXY_LAYER)
+}
+
+/// How coarse a shadow proxy is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowProxy {
+    /// Vertices around each tube ring (clamped like
+    /// [`LSystemMeshBuilder::with_resolution`]).
+    pub resolution: u32,
+    /// Strands whose largest radius is below this cast no shadow.
+    pub min_radius: f32,
+    /// Interior points where the strand bends by less than this angle
+    /// (radians) are merged. `None` keeps every point.
+    pub merge_angle: Option<f32>,
+}
+
+impl Default for ShadowProxy {
+    /// Four-sided tubes, strands thinner than 2 cm dropped, and bends under
+    /// about 10° merged.
+    fn default() -> Self {
+        Self {
+            resolution: 4,
+            min_radius: 0.02,
+            merge_angle: Some(0.17),
+        }
+    }
+}
+
+impl ShadowProxy {
+    /// Sets the ring resolution of the proxy's tubes.
+    pub fn with_resolution(mut self, resolution: u32) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// Drops strands whose largest radius is below `min_radius`.
+    pub fn with_min_radius(mut self, min_radius: f32) -> Self {
+        self.min_radius = min_radius.max(0.0);
+        self
+    }
+
+    /// Merges interior points where the strand bends by less than `angle`
+    /// radians; `None` keeps every point.
+    pub fn with_merge_angle(mut self, angle: Option<f32>) -> Self {
+        self.merge_angle = angle.map(|angle| angle.max(0.0));
+        self
+    }
+
+    fn as_lod_level(&self) -> LodLevel {
+        LodLevel {
+            resolution: self.resolution,
+            min_radius: self.min_radius,
+            merge_angle: self.merge_angle,
+            max_distance: f32::INFINITY,
+        }
+    }
+}
+
+/// Visual meshes plus their shadow proxy, as produced by
+/// [`LSystemMeshBuilder::build_with_shadow_proxy`].
+pub struct ShadowProxiedMeshes {
+    /// Per-material meshes, as from [`LSystemMeshBuilder::build`].
+    pub meshes: HashMap<u16, Mesh>,
+    /// The single shadow-casting mesh.
+    pub proxy: Mesh,
+    /// Materials left out of the proxy whose visual meshes should keep
+    /// casting shadows themselves (the card materials).
+    pub self_shadowing: BTreeSet<u16>,
+}
+
+impl ShadowProxiedMeshes {
+    /// Moves the meshes into `assets`, returning handles ready for
+    /// [`spawn_shadow_proxied`].
+    pub fn into_handles(self, assets: &mut Assets<Mesh>) -> ShadowProxiedHandles {
+        ShadowProxiedHandles {
+            meshes: self
+                .meshes
+                .into_iter()
+                .map(|(id, mesh)| (id, assets.add(mesh)))
+                .collect(),
+            proxy: assets.add(self.proxy),
+            self_shadowing: self.self_shadowing,
+        }
+    }
+}
+
+/// Mesh handles for visual meshes plus their shadow proxy.
+#[derive(Clone)]
+pub struct ShadowProxiedHandles {
+    /// Per-material mesh handles.
+    pub meshes: HashMap<u16, Handle<Mesh>>,
+    pub proxy: Handle<Mesh>,
+    /// Materials whose visual meshes keep casting shadows.
+    pub self_shadowing: BTreeSet<u16>,
+}
+
+impl LSystemMeshBuilder {
+    /// Builds a shadow proxy of `skeleton`: one mesh with positions, normals
+    /// and indices only, at `proxy`'s resolution, without thin strands or
+    /// card materials.
+    ///
+    /// Smoothing and shading are reset to their defaults and hierarchy,
+    /// growth, and lightmap attributes are dropped; all other options (caps, junctions,
+    /// cross-sections, displacement, filters, …) carry over so the proxy
+    /// keeps the visual silhouette.
+    pub fn build_shadow_proxy(self, skeleton: &Skeleton, proxy: &ShadowProxy) -> Mesh {
+        let level = proxy.as_lod_level();
+        let meshes = self
+            .without_cards()
+            .with_resolution(level.resolution)
+            .with_smoothing(StrandSmoothing::None)
+            .with_shading(Shading::Smooth)
+            .with_hierarchy_attributes(false)
+            .with_growth_attributes(false)
+            .with_lightmap_uvs(None)
+            .build(&simplify_skeleton(skeleton, &level, &BTreeSet::new()));
+
+        let mut ids: Vec<u16> = meshes.keys().copied().collect();
+        ids.sort_unstable();
+        let mut positions: Vec<[f32; 3]> = Vec::new();
+        let mut normals: Vec<[f32; 3]> = Vec::new();
+        let mut indices: Vec<u32> = Vec::new();
+        for id in ids {
+            let mesh = &meshes[&id];
+            let (
+                Some(VertexAttributeValues::Float32x3(p)),
+                Some(VertexAttributeValues::Float32x3(n)),
+                Some(Indices::U32(i)),
+            ) = (
+                mesh.attribute(Mesh::ATTRIBUTE_POSITION),
+                mesh.attribute(Mesh::ATTRIBUTE_NORMAL),
+                mesh.indices(),
+            )
+            else {
+                continue;
+            };
+            let base = positions.len() as u32;
+            positions.extend_from_slice(p);
+            normals.extend_from_slice(n);
+            indices.extend(i.iter().map(|i| i + base));
+        }
+
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_indices(Indices::U32(indices));
+        mesh
+    }
+
+    /// Builds the regular per-material meshes of [`Self::build`] together
+    /// with a [`Self::build_shadow_proxy`] of the same skeleton.
+    pub fn build_with_shadow_proxy(
+        self,
+        skeleton: &Skeleton,
+        proxy: &ShadowProxy,
+    ) -> ShadowProxiedMeshes {
+        ShadowProxiedMeshes {
+            self_shadowing: self.card_materials(),
+            proxy: self.clone().build_shadow_proxy(skeleton, proxy),
+            meshes: self.build(skeleton),
+        }
+    }
+}
+
+/// Spawns the visual meshes of `handles` as children of `parent`, marked
+/// [`NotShadowCaster`] unless their material is
+/// [`self_shadowing`](ShadowProxiedHandles::self_shadowing), plus one child
+/// for the proxy on [`SHADOW_PROXY_LAYER`] only. Materials are looked up in
+/// `palette`, falling back to [`MaterialPalette::primary_material`], which
+/// the proxy uses too. Returns the visual entities, in ascending material ID
+/// order, followed by the proxy.
+///
+/// Only the plain palette materials are used: slots with a
+/// [`WindPalette`](crate::wind::WindPalette) material do not sway when spawned
+/// through this helper. Spawn the visual meshes yourself to use wind.
+///
+/// Lights only pick up the proxy if their [`RenderLayers`] include
+/// [`SHADOW_PROXY_LAYER`], e.g. [`shadow_caster_layers`]. Lights without it
+/// render no shadow for the tree at all.
+pub fn spawn_shadow_proxied(
+    commands: &mut Commands,
+    parent: Entity,
+    handles: &ShadowProxiedHandles,
+    palette: &MaterialPalette,
+) -> Vec<Entity> {
+    let mut ids: Vec<u16> = handles.meshes.keys().copied().collect();
+    ids.sort_unstable();
+    let mut spawned = Vec::new();
+    for material_id in ids {
+        let material = palette
+            .materials
+            .get(&material_id)
+            .unwrap_or(&palette.primary_material)
+            .clone();
+        let mut entity = commands.spawn((
+            Mesh3d(handles.meshes[&material_id].clone()),
+            MeshMaterial3d(material),
+            ChildOf(parent),
+        ));
+        if !handles.self_shadowing.contains(&material_id) {
+            entity.insert(NotShadowCaster);
+        }
+        spawned.push(entity.id());
+    }
+    spawned.push(
+        commands
+            .spawn((
+                Mesh3d(handles.proxy.clone()),
+                MeshMaterial3d(palette.primary_material.clone()),
+                RenderLayers::layer(SHADOW_PROXY_LAYER),
+                ChildOf(parent),
+            ))
+            .id(),
+    );
+    spawned
+}
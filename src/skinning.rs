@@ -0,0 +1,366 @@
+//! Skinned output for L-System meshes.
+//!
+//! [`LSystemMeshBuilder::build_skinned`] meshes a skeleton like
+//! [`LSystemMeshBuilder::build`], but also lays out a joint hierarchy along it
+//! and binds every vertex to its nearest joints through
+//! [`Mesh::ATTRIBUTE_JOINT_INDEX`] and [`Mesh::ATTRIBUTE_JOINT_WEIGHT`].
+//! [`spawn_skinned`] then spawns one entity per joint plus the mesh parts with
+//! a ready [`SkinnedMesh`], so branches can be posed procedurally or driven by
+//! animation clips.
+//!
+//! Each strand gets one joint per `segments_per_joint` segments, chained from
+//! its base to its tip. The first joint of a strand is parented to the joint
+//! of its parent strand (per [`Skeleton::strand_parents`]) covering the
+//! branching point, so bending a limb carries everything that grows from it.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use bevy::prelude::*;
+//! use bevy_symbios::LSystemMeshBuilder;
+//! use bevy_symbios::materials::MaterialPalette;
+//! use bevy_symbios::skinning::spawn_skinned;
+//!
+//! fn spawn_tentacle(
+//!     mut commands: Commands,
+//!     mut meshes: ResMut<Assets<Mesh>>,
+//!     mut bindposes: ResMut<Assets<SkinnedMeshInverseBindposes>>,
+//!     palette: Res<MaterialPalette>,
+//!     skeleton: symbios_turtle_3d::Skeleton,
+//! ) {
+//!     let Ok(skinned) = LSystemMeshBuilder::new().build_skinned(&skeleton, 2) else {
+//!         return;
+//!     };
+//!     let root = commands.spawn(Transform::default()).id();
+//!     spawn_skinned(&mut commands, root, skinned, &mut meshes, &mut bindposes, &palette);
+//! }
+//! ```
+//!
+//! Bevy limits a skin to [`MAX_JOINTS`] joints. Larger plants get a raised
+//! `segments_per_joint` (with a warning); a skeleton with more strands than
+//! that fails with a [`SkinningError`], since every strand needs a joint.
+
+use bevy::mesh::skinning::{SkinnedMesh, SkinnedMeshInverseBindposes};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use symbios_turtle_3d::{Skeleton, SkeletonPoint};
+
+use crate::materials::MaterialPalette;
+use crate::mesh_tasks::LSystemMeshPart;
+use crate::mesher::{LSystemMeshBuilder, StrandJoints, arc_length_to, cumulative_arc_lengths};
+
+/// Most joints Bevy's skinning shaders accept per skinned mesh.
+pub const MAX_JOINTS: usize = 256;
+
+/// One joint of a [`SkinnedMeshes`] hierarchy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkinJoint {
+    /// Index of the parent joint, or `None` for joints attached to the root.
+    /// Parents always precede their children.
+    pub parent: Option<usize>,
+    /// Strand the joint belongs to.
+    pub strand: usize,
+    /// Index of the strand point the joint sits on.
+    pub point: usize,
+    /// Bind pose relative to the parent joint (or to the root entity).
+    pub transform: Transform,
+    /// Inverse of the joint's bind pose relative to the root entity.
+    pub inverse_bindpose: Mat4,
+}
+
+/// Meshes and joint hierarchy produced by
+/// [`LSystemMeshBuilder::build_skinned`].
+pub struct SkinnedMeshes {
+    /// Per-material meshes, as from [`LSystemMeshBuilder::build`], with joint
+    /// indices and weights referring to [`Self::joints`].
+    pub meshes: HashMap<u16, Mesh>,
+    /// Joints in skin order, parents first.
+    pub joints: Vec<SkinJoint>,
+    /// Segments per joint actually used, after clamping and raising to fit
+    /// [`MAX_JOINTS`].
+    pub segments_per_joint: usize,
+}
+
+/// Why [`LSystemMeshBuilder::build_skinned`] gave up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkinningError {
+    /// Every non-empty strand needs at least one joint, and there are more of
+    /// them than one skin can hold.
+    TooManyStrands { strands: usize, max_joints: usize },
+}
+
+impl std::fmt::Display for SkinningError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyStrands {
+                strands,
+                max_joints,
+            } => write!(
+                f,
+                "{strands} strands need at least one joint each, exceeding the maximum of {max_joints} joints per skin"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SkinningError {}
+
+impl SkinnedMeshes {
+    /// The inverse bind poses of every joint, in skin order.
+    pub fn inverse_bindposes(&self) -> SkinnedMeshInverseBindposes {
+        self.joints
+            .iter()
+            .map(|joint| joint.inverse_bindpose)
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+/// Marker on the joint entities spawned by [`spawn_skinned`], recording where
+/// on the skeleton each one sits.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LSystemJoint {
+    pub strand: usize,
+    pub point: usize,
+}
+
+/// Entities created by [`spawn_skinned`].
+pub struct SpawnedSkin {
+    /// Joint entities, in skin order.
+    pub joints: Vec<Entity>,
+    /// Mesh part entities, one per material.
+    pub parts: Vec<Entity>,
+    /// The skin shared by every part.
+    pub skin: SkinnedMesh,
+}
+
+impl LSystemMeshBuilder {
+    /// Builds skinned meshes from `skeleton`, consuming the builder.
+    ///
+    /// Every strand is split into joints of `segments_per_joint` segments
+    /// (clamped to at least 1). Vertices are weighted to their own joint and
+    /// blend linearly into the neighbouring one across each joint boundary, so
+    /// bends stay smooth. All other builder options apply as usual.
+    ///
+    /// If that would make more than [`MAX_JOINTS`] joints, `segments_per_joint`
+    /// is raised to the smallest value that fits, with a warning. Fails when
+    /// the skeleton has more non-empty strands than [`MAX_JOINTS`].
+    pub fn build_skinned(
+        self,
+        skeleton: &Skeleton,
+        segments_per_joint: u32,
+    ) -> Result<SkinnedMeshes, SkinningError> {
+        let requested = if segments_per_joint == 0 {
+            warn!("Segments per joint must be at least 1; clamping to 1");
+            1
+        } else {
+            segments_per_joint as usize
+        };
+        let segments_per_joint = fit_segments_per_joint(skeleton, requested)?;
+
+        let mut joints: Vec<SkinJoint> = Vec::new();
+        let mut binds: Vec<GlobalTransform> = Vec::new();
+        let mut layouts: Vec<StrandJoints> = Vec::with_capacity(skeleton.strands.len());
+        for (strand_idx, strand) in skeleton.strands.iter().enumerate() {
+            let first = joints.len();
+            let positions = finite_positions(strand);
+            let arcs = cumulative_arc_lengths(&positions);
+            let Some(&length) = arcs.last() else {
+                layouts.push(StrandJoints::default());
+                continue;
+            };
+
+            // Strands only attach to earlier strands, which keeps parents
+            // ahead of their children and rules out cycles.
+            let attach = skeleton
+                .strand_parents
+                .get(strand_idx)
+                .copied()
+                .flatten()
+                .filter(|&parent| parent < strand_idx)
+                .and_then(|parent| {
+                    let layout = &layouts[parent];
+                    let arc = arc_length_to(&skeleton.strands[parent], positions[0]);
+                    let offset = layout.origins.iter().rposition(|&o| o <= arc)?;
+                    Some(layout.first as usize + offset)
+                });
+
+            let segments = strand.len().saturating_sub(1);
+            let count = segments.div_ceil(segments_per_joint).max(1);
+            let mut origins = Vec::with_capacity(count);
+            for j in 0..count {
+                let point = j * segments_per_joint;
+                let bind = GlobalTransform::from(Transform {
+                    translation: positions[point],
+                    rotation: bind_rotation(strand[point].rotation),
+                    ..default()
+                });
+                let parent = if j == 0 { attach } else { Some(first + j - 1) };
+                let transform = match parent {
+                    Some(parent) => bind.reparented_to(&binds[parent]),
+                    None => bind.compute_transform(),
+                };
+                joints.push(SkinJoint {
+                    parent,
+                    strand: strand_idx,
+                    point,
+                    transform,
+                    inverse_bindpose: bind.to_matrix().inverse(),
+                });
+                binds.push(bind);
+                origins.push(arcs[point]);
+            }
+            layouts.push(StrandJoints {
+                first: u16::try_from(first).map_err(|_| too_many_strands(skeleton))?,
+                origins,
+                length,
+            });
+        }
+
+        Ok(SkinnedMeshes {
+            meshes: self.build_with_joints(skeleton, &layouts),
+            joints,
+            segments_per_joint,
+        })
+    }
+}
+
+/// Strand positions with every non-finite one replaced by the last finite
+/// position before it (or the first finite one, at the start). Like the
+/// mesher's dedup, this gives such points zero arc length.
+fn finite_positions(strand: &[SkeletonPoint]) -> Vec<Vec3> {
+    let mut last = strand
+        .iter()
+        .map(|p| p.position)
+        .find(|p| p.is_finite())
+        .unwrap_or(Vec3::ZERO);
+    strand
+        .iter()
+        .map(|p| {
+            if p.position.is_finite() {
+                last = p.position;
+            }
+            last
+        })
+        .collect()
+}
+
+/// The normalized joint rotation, or identity for zero and non-finite input,
+/// which would otherwise poison the bind pose and its inverse with NaNs.
+fn bind_rotation(rotation: Quat) -> Quat {
+    Some(rotation.normalize())
+        .filter(|r| r.is_finite())
+        .unwrap_or(Quat::IDENTITY)
+}
+
+/// Joints [`LSystemMeshBuilder::build_skinned`] lays out with
+/// `segments_per_joint`: one per started run of that many segments, and at
+/// least one per non-empty strand.
+fn joint_count(skeleton: &Skeleton, segments_per_joint: usize) -> usize {
+    skeleton
+        .strands
+        .iter()
+        .filter(|strand| !strand.is_empty())
+        .map(|strand| (strand.len() - 1).div_ceil(segments_per_joint).max(1))
+        .sum()
+}
+
+fn too_many_strands(skeleton: &Skeleton) -> SkinningError {
+    SkinningError::TooManyStrands {
+        strands: skeleton.strands.iter().filter(|s| !s.is_empty()).count(),
+        max_joints: MAX_JOINTS,
+    }
+}
+
+/// The smallest segments per joint from `requested` up that keeps the skin
+/// within [`MAX_JOINTS`].
+fn fit_segments_per_joint(skeleton: &Skeleton, requested: usize) -> Result<usize, SkinningError> {
+    if joint_count(skeleton, requested) <= MAX_JOINTS {
+        return Ok(requested);
+    }
+    // With one joint per strand the count can drop no further.
+    let longest = skeleton.strands.iter().map(Vec::len).max().unwrap_or(0);
+    if joint_count(skeleton, longest.max(1)) > MAX_JOINTS {
+        return Err(too_many_strands(skeleton));
+    }
+    // The count never grows with longer joints, so bisect for the boundary.
+    let (mut low, mut high) = (requested + 1, longest.max(1));
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if joint_count(skeleton, mid) <= MAX_JOINTS {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    warn!(
+        "Skinning with {requested} segments per joint needs {} joints, more than the maximum of {MAX_JOINTS}; using {low} segments per joint",
+        joint_count(skeleton, requested)
+    );
+    Ok(low)
+}
+
+/// Spawns the joints of `skinned` as a hierarchy under `parent`, then one
+/// child of `parent` per material with a [`SkinnedMesh`] bound to those
+/// joints. Materials are looked up in `palette`, falling back to
+/// [`MaterialPalette::primary_material`].
+///
+/// Pose the plant by changing the joints' [`Transform`]s; moving `parent`
+/// moves everything.
+pub fn spawn_skinned(
+    commands: &mut Commands,
+    parent: Entity,
+    skinned: SkinnedMeshes,
+    meshes: &mut Assets<Mesh>,
+    inverse_bindposes: &mut Assets<SkinnedMeshInverseBindposes>,
+    palette: &MaterialPalette,
+) -> SpawnedSkin {
+    let bindposes = inverse_bindposes.add(skinned.inverse_bindposes());
+
+    let mut joints: Vec<Entity> = Vec::with_capacity(skinned.joints.len());
+    for joint in &skinned.joints {
+        let joint_parent = joint.parent.map_or(parent, |index| joints[index]);
+        joints.push(
+            commands
+                .spawn((
+                    joint.transform,
+                    LSystemJoint {
+                        strand: joint.strand,
+                        point: joint.point,
+                    },
+                    ChildOf(joint_parent),
+                ))
+                .id(),
+        );
+    }
+
+    let skin = SkinnedMesh {
+        inverse_bindposes: bindposes,
+        joints: joints.clone(),
+    };
+    let mut parts = Vec::with_capacity(skinned.meshes.len());
+    for (material_id, mesh) in skinned.meshes {
+        let material = palette
+            .materials
+            .get(&material_id)
+            .unwrap_or(&palette.primary_material)
+            .clone();
+        parts.push(
+            commands
+                .spawn((
+                    Mesh3d(meshes.add(mesh)),
+                    MeshMaterial3d(material),
+                    skin.clone(),
+                    LSystemMeshPart { material_id },
+                    ChildOf(parent),
+                ))
+                .id(),
+        );
+    }
+
+    SpawnedSkin {
+        joints,
+        parts,
+        skin,
+    }
+}
@@ -16,16 +16,17 @@ use bevy_egui::egui;
 use bevy_symbios_texture::TextureConfig;
 
 use crate::materials::{MaterialSettings, TextureType};
+use crate::wind::WindResponse;
 
 pub use bevy_symbios_texture::ui::*;
 
 /// Renders a material palette editor widget.
 ///
 /// Shows a collapsible section per material ID with controls for base color,
-/// emission, roughness, metallic, texture type, UV scale, and — for any
-/// `Procedural(TextureConfig)` selection — a nested **"Texture parameters"**
-/// subsection wrapping the per-variant editor for the active config (any
-/// generator in the [`bevy_symbios_texture`] registry). The nested collapsible
+/// emission, roughness, metallic, texture type, UV scale, wind response, and —
+/// for any `Procedural(TextureConfig)` selection — a nested **"Texture
+/// parameters"** subsection wrapping the per-variant editor for the active
+/// config (any generator in the [`bevy_symbios_texture`] registry). The nested collapsible
 /// keeps PBR sliders visually separate from the (sometimes lengthy)
 /// generator-specific sliders.
 ///
@@ -59,6 +60,7 @@ pub fn material_palette_editor(
         let mut local_metallic = current.metallic;
         let mut local_texture = current.texture.clone();
         let mut local_uv_scale = current.uv_scale;
+        let mut local_wind = current.wind;
 
         // mat_regen: caller should fire MaterialSettingsChanged so on_material_settings_changed re-applies the palette
         // mat_writeback: slider value changed visually but regen not yet needed (prevents snap-back)
@@ -90,6 +92,25 @@ pub fn material_palette_editor(
                 .add(egui::Slider::new(&mut local_uv_scale, 0.1..=10.0).text("UV Scale"))
                 .changed();
 
+            let mut wind_enabled = local_wind.is_some();
+            if ui.checkbox(&mut wind_enabled, "Wind").changed() {
+                local_wind = wind_enabled.then(WindResponse::default);
+                mat_regen = true;
+            }
+            if let Some(response) = &mut local_wind {
+                mat_regen |= ui
+                    .add(
+                        egui::Slider::new(&mut response.flexibility, 0.0..=0.2).text("Flexibility"),
+                    )
+                    .changed();
+                mat_regen |= ui
+                    .add(egui::Slider::new(&mut response.stiffness, 0.0..=50.0).text("Stiffness"))
+                    .changed();
+                mat_regen |= ui
+                    .add(egui::Slider::new(&mut response.flutter, 0.0..=0.1).text("Flutter"))
+                    .changed();
+            }
+
             ui.horizontal(|ui| {
                 ui.label("Texture:");
                 egui::ComboBox::from_id_salt(format!("mat_tex_{}", mat_id))
@@ -140,6 +161,7 @@ pub fn material_palette_editor(
             s.metallic = local_metallic;
             s.texture = local_texture;
             s.uv_scale = local_uv_scale;
+            s.wind = local_wind;
         }
 
         if mat_regen {
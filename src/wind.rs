@@ -0,0 +1,266 @@
+//! Wind sway for L-System meshes.
+//!
+//! [`WindMaterial`] is an [`ExtendedMaterial`] over [`StandardMaterial`] whose
+//! vertex stage bends geometry using the hierarchy data the mesher bakes when
+//! [`LSystemMeshBuilder::with_hierarchy_attributes`] is enabled: branch depth,
+//! distance from the root along the strand chain, and branch radius. Trunks
+//! stay planted, thin tips swing, and deep twigs flutter.
+//!
+//! Global parameters come from the [`Wind`] resource. Each material slot opts
+//! in by setting [`MaterialSettings::wind`]; [`sync_wind_materials`] then keeps
+//! a matching [`WindMaterial`] in [`WindPalette`], copying the slot's
+//! `StandardMaterial` from [`MaterialPalette`] as its base so colours,
+//! textures and alpha settings stay in step with the regular workflow.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use bevy::prelude::*;
+//! use bevy_symbios::wind::{Wind, WindPlugin, WindResponse};
+//!
+//! app.add_plugins(WindPlugin)
+//!     .insert_resource(Wind {
+//!         direction: Vec3::new(1.0, 0.0, 0.3),
+//!         strength: 1.5,
+//!         gust_frequency: 0.2,
+//!     });
+//!
+//! // Opt material 0 in, then fire MaterialSettingsChanged as usual.
+//! settings.settings.get_mut(&0).unwrap().wind = Some(WindResponse::default());
+//! ```
+//!
+//! Meshes rendered with a [`WindMaterial`] must carry
+//! [`ATTRIBUTE_BRANCH_DEPTH`], [`ATTRIBUTE_ROOT_DISTANCE`] and
+//! [`ATTRIBUTE_BRANCH_RADIUS`]; pipelines for meshes without them fail to
+//! specialize and are not drawn. Skinned and morphed meshes are not supported.
+//!
+//! [`LSystemMeshBuilder::with_hierarchy_attributes`]: crate::LSystemMeshBuilder::with_hierarchy_attributes
+
+use bevy::asset::embedded_asset;
+use bevy::mesh::MeshVertexBufferLayoutRef;
+use bevy::pbr::{
+    ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline,
+};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::render::render_resource::{
+    AsBindGroup, RenderPipelineDescriptor, ShaderType, SpecializedMeshPipelineError,
+};
+use bevy::shader::ShaderRef;
+
+use crate::materials::{MaterialPalette, MaterialSettingsMap};
+use crate::mesher::{ATTRIBUTE_BRANCH_DEPTH, ATTRIBUTE_BRANCH_RADIUS, ATTRIBUTE_ROOT_DISTANCE};
+
+/// Shader location of [`ATTRIBUTE_BRANCH_DEPTH`] in the wind vertex shaders.
+/// The following two locations hold root distance and branch radius. Chosen
+/// above every location Bevy's own mesh and prepass shaders use.
+const HIERARCHY_SHADER_LOCATION: u32 = 10;
+
+const WIND_SHADER_PATH: &str = "embedded://bevy_symbios/shaders/wind.wgsl";
+const WIND_PREPASS_SHADER_PATH: &str = "embedded://bevy_symbios/shaders/wind_prepass.wgsl";
+
+/// Scene-wide wind parameters shared by every [`WindMaterial`].
+#[derive(Resource, Clone, Debug, PartialEq, Reflect)]
+pub struct Wind {
+    /// Direction the wind blows towards. Only its horizontal part drives the
+    /// flutter axis; it is normalised before upload, and a zero vector
+    /// disables the main bend.
+    pub direction: Vec3,
+    /// Overall multiplier on every displacement. `0.0` is calm.
+    pub strength: f32,
+    /// Gust cycles per second.
+    pub gust_frequency: f32,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Self {
+            direction: Vec3::X,
+            strength: 1.0,
+            gust_frequency: 0.25,
+        }
+    }
+}
+
+/// How strongly one material slot reacts to the [`Wind`].
+///
+/// Stored in [`MaterialSettings::wind`](crate::materials::MaterialSettings::wind);
+/// `None` there leaves the slot static.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct WindResponse {
+    /// Bend per unit of squared root distance. Scales the main sway.
+    pub flexibility: f32,
+    /// Resistance per unit of branch radius: the bend is divided by
+    /// `1 + stiffness * radius`, so thick limbs move less.
+    pub stiffness: f32,
+    /// Amplitude of the fast sideways oscillation, multiplied by branch depth.
+    pub flutter: f32,
+}
+
+impl Default for WindResponse {
+    fn default() -> Self {
+        Self {
+            flexibility: 0.03,
+            stiffness: 10.0,
+            flutter: 0.02,
+        }
+    }
+}
+
+/// GPU layout of the wind parameters. Mirrors `WindUniform` in
+/// `shaders/wind_common.wgsl`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ShaderType, Reflect)]
+pub struct WindUniform {
+    pub direction: Vec3,
+    pub strength: f32,
+    pub gust_frequency: f32,
+    pub flexibility: f32,
+    pub stiffness: f32,
+    pub flutter: f32,
+}
+
+impl WindUniform {
+    /// Combines the scene [`Wind`] with one slot's [`WindResponse`].
+    pub fn new(wind: &Wind, response: &WindResponse) -> Self {
+        Self {
+            direction: wind.direction.normalize_or_zero(),
+            strength: wind.strength,
+            gust_frequency: wind.gust_frequency,
+            flexibility: response.flexibility,
+            stiffness: response.stiffness,
+            flutter: response.flutter,
+        }
+    }
+}
+
+/// [`MaterialExtension`] that displaces vertices by the wind. Use through
+/// [`WindMaterial`].
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
+pub struct WindExtension {
+    #[uniform(100)]
+    pub wind: WindUniform,
+}
+
+/// [`StandardMaterial`] with wind sway.
+pub type WindMaterial = ExtendedMaterial<StandardMaterial, WindExtension>;
+
+impl MaterialExtension for WindExtension {
+    fn vertex_shader() -> ShaderRef {
+        WIND_SHADER_PATH.into()
+    }
+
+    fn prepass_vertex_shader() -> ShaderRef {
+        WIND_PREPASS_SHADER_PATH.into()
+    }
+
+    fn deferred_vertex_shader() -> ShaderRef {
+        WIND_PREPASS_SHADER_PATH.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        // The base pipeline only declares the attributes Bevy knows about;
+        // append the hierarchy attributes to the same interleaved buffer.
+        let hierarchy = layout.0.get_layout(&[
+            ATTRIBUTE_BRANCH_DEPTH.at_shader_location(HIERARCHY_SHADER_LOCATION),
+            ATTRIBUTE_ROOT_DISTANCE.at_shader_location(HIERARCHY_SHADER_LOCATION + 1),
+            ATTRIBUTE_BRANCH_RADIUS.at_shader_location(HIERARCHY_SHADER_LOCATION + 2),
+        ])?;
+        descriptor.vertex.buffers[0]
+            .attributes
+            .extend(hierarchy.attributes);
+        Ok(())
+    }
+}
+
+/// Wind-enabled counterparts of [`MaterialPalette`] slots, keyed by the same
+/// material ID. Only slots whose settings opt in have an entry.
+#[derive(Resource, Default)]
+pub struct WindPalette {
+    pub materials: HashMap<u16, Handle<WindMaterial>>,
+}
+
+/// Update system that keeps [`WindPalette`] in step with
+/// [`MaterialSettingsMap`], [`MaterialPalette`] and [`Wind`].
+///
+/// Slots with [`MaterialSettings::wind`](crate::materials::MaterialSettings::wind)
+/// set get a [`WindMaterial`]; slots without it are dropped. Materials are
+/// only rewritten when one of the three resources changes: the gust clock
+/// comes from Bevy's `globals.time` in the shaders, so idle frames leave the
+/// bind groups alone.
+pub fn sync_wind_materials(
+    wind: Res<Wind>,
+    settings: Option<Res<MaterialSettingsMap>>,
+    palette: Option<Res<MaterialPalette>>,
+    mut wind_palette: ResMut<WindPalette>,
+    standard_materials: Res<Assets<StandardMaterial>>,
+    mut wind_materials: ResMut<Assets<WindMaterial>>,
+) {
+    let (Some(settings), Some(palette)) = (settings, palette) else {
+        return;
+    };
+    if !(wind.is_changed() || settings.is_changed() || palette.is_changed()) {
+        return;
+    }
+
+    wind_palette
+        .materials
+        .retain(|id, _| settings.settings.get(id).is_some_and(|s| s.wind.is_some()));
+
+    for (id, material_settings) in &settings.settings {
+        let Some(response) = &material_settings.wind else {
+            continue;
+        };
+        let Some(base) = palette
+            .materials
+            .get(id)
+            .and_then(|handle| standard_materials.get(handle))
+        else {
+            continue;
+        };
+
+        let uniform = WindUniform::new(&wind, response);
+        let existing = wind_palette
+            .materials
+            .get(id)
+            .and_then(|handle| wind_materials.get_mut(handle));
+        match existing {
+            Some(material) => {
+                material.base = base.clone();
+                material.extension.wind = uniform;
+            }
+            None => {
+                let handle = wind_materials.add(WindMaterial {
+                    base: base.clone(),
+                    extension: WindExtension { wind: uniform },
+                });
+                wind_palette.materials.insert(*id, handle);
+            }
+        }
+    }
+}
+
+/// Plugin that registers the wind shaders and [`WindMaterial`], inserts
+/// [`Wind`] and [`WindPalette`], and runs [`sync_wind_materials`] in `Update`.
+///
+/// Requires the rendering plugins and the [`materials`](crate::materials)
+/// workflow ([`MaterialSettingsMap`] and [`MaterialPalette`]).
+pub struct WindPlugin;
+
+impl Plugin for WindPlugin {
+    fn build(&self, app: &mut App) {
+        bevy::shader::load_shader_library!(app, "shaders/wind_common.wgsl");
+        embedded_asset!(app, "shaders/wind.wgsl");
+        embedded_asset!(app, "shaders/wind_prepass.wgsl");
+
+        app.add_plugins(MaterialPlugin::<WindMaterial>::default())
+            .init_resource::<Wind>()
+            .init_resource::<WindPalette>()
+            .add_systems(Update, sync_wind_materials);
+    }
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::{LSystemMeshBuilder, MeshCache};
+use common::point;
+use symbios_turtle_3d::Skeleton;
+
+/// A thick trunk (material 0) and a hair-thin twig (material 1).
+fn trunk_and_twig() -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 1.0, 0), None);
+    s.push_node(point(Vec3::Y * 2.0, 1.0, 0));
+    s.push_node(point(Vec3::Y * 4.0, 0.8, 0));
+    s.start_strand(point(Vec3::Y * 4.0, 0.002, 1), Some(0));
+    s.push_node(point(Vec3::new(1.0, 5.0, 0.0), 0.002, 1));
+    s
+}
+
+#[test]
+fn ring_count_follows_radius() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_resolution(32)
+        .with_adaptive_resolution(4, 0.2)
+        .build(&trunk_and_twig());
+
+    // Trunk: ceil(TAU * 1.0 / 0.2) = 32, three shared rings of 33 vertices.
+    assert_eq!(meshes[&0].count_vertices(), 3 * 33);
+    // Twig: clamped up to the minimum of 4, two rings of 5 vertices.
+    assert_eq!(meshes[&1].count_vertices(), 2 * 5);
+}
+
+#[test]
+fn resolution_is_the_upper_bound() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_resolution(12)
+        .with_adaptive_resolution(4, 0.01)
+        .build(&trunk_and_twig());
+    assert_eq!(meshes[&0].count_vertices(), 3 * 13);
+}
+
+#[test]
+fn invalid_edge_length_disables_adaptive_mode() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_adaptive_resolution(4, 0.0)
+        .build(&trunk_and_twig());
+    assert_eq!(meshes[&1].count_vertices(), 2 * 9);
+}
+
+#[test]
+fn adaptive_mode_changes_cache_key() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(bevy::asset::AssetPlugin::default())
+        .init_asset::<Mesh>();
+    let skeleton = trunk_and_twig();
+    let mut cache = MeshCache::new();
+    let mut meshes = app.world_mut().resource_mut::<Assets<Mesh>>();
+
+    LSystemMeshBuilder::new().build_cached(&skeleton, &mut cache, &mut meshes);
+    LSystemMeshBuilder::new()
+        .with_adaptive_resolution(3, 0.5)
+        .build_cached(&skeleton, &mut cache, &mut meshes);
+
+    assert_eq!(cache.len(), 2);
+    assert!(cache.contains(&skeleton, 8));
+}
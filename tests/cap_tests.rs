@@ -0,0 +1,80 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::{CapStyle, LSystemMeshBuilder};
+use common::{assert_watertight, normals, polyline, positions};
+
+#[test]
+fn open_caps_are_default() {
+    let s = polyline(&[Vec3::ZERO, Vec3::Y], &[0, 0]);
+    let meshes = LSystemMeshBuilder::new().build(&s);
+    assert_eq!(meshes[&0].count_vertices(), 18);
+}
+
+#[test]
+fn flat_caps_close_the_tube() {
+    let s = polyline(&[Vec3::ZERO, Vec3::Y, Vec3::Y * 2.0], &[0, 0, 0]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_caps(CapStyle::Flat)
+        .build(&s);
+    let mesh = &meshes[&0];
+
+    // 3 shared rings + 2 caps of (center + 9 rim vertices).
+    assert_eq!(mesh.count_vertices(), 27 + 2 * 10);
+    assert_watertight(mesh);
+
+    // Cap normals face along the strand axis.
+    let normals = normals(mesh);
+    assert_eq!(normals[27], Vec3::NEG_Y, "start cap faces -Y");
+    assert!(normals[37].abs_diff_eq(Vec3::Y, 1e-5), "end cap faces +Y");
+}
+
+#[test]
+fn dome_and_point_caps_are_watertight() {
+    let s = polyline(&[Vec3::ZERO, Vec3::Y], &[0, 0]);
+    for cap in [CapStyle::Dome, CapStyle::Point { length: 2.0 }] {
+        let meshes = LSystemMeshBuilder::new()
+            .with_resolution(12)
+            .with_caps(cap)
+            .build(&s);
+        assert_watertight(&meshes[&0]);
+    }
+}
+
+#[test]
+fn point_cap_apex_extends_beyond_end() {
+    let s = polyline(&[Vec3::ZERO, Vec3::Y], &[0, 0]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_end_cap(CapStyle::Point { length: 3.0 })
+        .build(&s);
+    let positions = positions(&meshes[&0]);
+    let max_y = positions.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+    assert!(
+        (max_y - 1.3).abs() < 1e-5,
+        "apex should sit 3 radii past the end"
+    );
+    let min_y = positions.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+    assert!(min_y.abs() < 1e-5, "start stays open");
+}
+
+#[test]
+fn caps_follow_adjacent_segment_material() {
+    let s = polyline(&[Vec3::ZERO, Vec3::Y, Vec3::Y * 2.0], &[0, 1, 1]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_caps(CapStyle::Flat)
+        .build(&s);
+    // Each bucket holds one segment (2 rings) plus one cap.
+    assert_eq!(meshes[&0].count_vertices(), 18 + 10);
+    assert_eq!(meshes[&1].count_vertices(), 18 + 10);
+}
+
+#[test]
+fn cap_geometry_is_finite() {
+    let s = polyline(&[Vec3::ZERO, Vec3::Y], &[0, 0]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_caps(CapStyle::Point {
+            length: f32::INFINITY,
+        })
+        .build(&s);
+    assert!(positions(&meshes[&0]).iter().all(|p| p.is_finite()));
+}
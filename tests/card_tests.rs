@@ -0,0 +1,99 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::{CardStyle, LSystemMeshBuilder};
+use common::{normals, point, positions, strand, triangles, uvs};
+
+#[test]
+fn card_material_replaces_tube_with_quad() {
+    // Bark segment, then a leaf at the tip.
+    let s = strand(&[point(Vec3::ZERO, 0.1, 0), point(Vec3::Y, 0.1, 2)]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_cards(2, CardStyle::Quad { size: 4.0 })
+        .build(&s);
+
+    assert_eq!(meshes[&0].count_vertices(), 18, "bark tube is unchanged");
+    let leaf = &meshes[&2];
+    assert_eq!(leaf.count_vertices(), 4);
+    assert_eq!(leaf.indices().unwrap().len(), 6);
+
+    // 0.4 x 0.4 square growing up from the tip, centred on the turtle X axis.
+    let pos = positions(leaf);
+    let min = pos.iter().fold(Vec3::splat(f32::MAX), |a, b| a.min(*b));
+    let max = pos.iter().fold(Vec3::splat(f32::MIN), |a, b| a.max(*b));
+    assert!(
+        min.abs_diff_eq(Vec3::new(-0.2, 1.0, 0.0), 1e-5),
+        "min {min}"
+    );
+    assert!(max.abs_diff_eq(Vec3::new(0.2, 1.4, 0.0), 1e-5), "max {max}");
+}
+
+#[test]
+fn card_uvs_span_unit_square() {
+    let s = strand(&[point(Vec3::ZERO, 0.1, 0), point(Vec3::Y, 0.1, 2)]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_cards(2, CardStyle::Quad { size: 2.0 })
+        .build(&s);
+    let leaf = &meshes[&2];
+    let pos = positions(leaf);
+    for (p, uv) in pos.iter().zip(uvs(leaf)) {
+        assert!(uv.x == 0.0 || uv.x == 1.0);
+        // Base of the card (at the attachment point) is V = 1.
+        let at_base = (p.y - 1.0).abs() < 1e-5;
+        assert_eq!(uv.y, if at_base { 1.0 } else { 0.0 });
+    }
+}
+
+#[test]
+fn card_faces_turtle_z_with_matching_winding() {
+    let rotation = Quat::from_rotation_x(0.7) * Quat::from_rotation_y(1.1);
+    let mut leaf = point(Vec3::new(1.0, 2.0, 3.0), 0.1, 2);
+    leaf.rotation = rotation;
+    let s = strand(&[leaf]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_cards(2, CardStyle::Quad { size: 1.0 })
+        .build(&s);
+    let mesh = &meshes[&2];
+    let pos = positions(mesh);
+    let facing = rotation * Vec3::Z;
+    for tri in triangles(mesh) {
+        let (a, b, c) = (pos[tri[0]], pos[tri[1]], pos[tri[2]]);
+        let normal = (b - a).cross(c - a).normalize();
+        assert!(normal.dot(facing) > 0.999);
+    }
+}
+
+#[test]
+fn crossed_cards_emit_two_perpendicular_quads() {
+    let s = strand(&[point(Vec3::ZERO, 0.1, 0), point(Vec3::Y, 0.1, 3)]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_cards(3, CardStyle::Crossed { size: 2.0 })
+        .build(&s);
+    let mesh = &meshes[&3];
+    assert_eq!(mesh.count_vertices(), 8);
+    let normals = normals(mesh);
+    assert!(normals[0].dot(normals[4]).abs() < 1e-5);
+}
+
+#[test]
+fn single_point_strands_get_cards() {
+    let s = strand(&[point(Vec3::ZERO, 0.1, 2)]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_cards(2, CardStyle::Quad { size: 2.0 })
+        .build(&s);
+    assert_eq!(meshes[&2].count_vertices(), 4);
+}
+
+#[test]
+fn segments_after_card_points_have_no_tube() {
+    let s = strand(&[
+        point(Vec3::ZERO, 0.1, 2),
+        point(Vec3::Y, 0.1, 2),
+        point(Vec3::Y * 2.0, 0.1, 2),
+    ]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_cards(2, CardStyle::Quad { size: 2.0 })
+        .build(&s);
+    assert_eq!(meshes.len(), 1);
+    assert_eq!(meshes[&2].count_vertices(), 12, "one card per point only");
+}
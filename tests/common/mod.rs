@@ -0,0 +1,142 @@
+//! Fixtures shared by the integration tests. Each test binary uses a subset.
+#![allow(dead_code)]
+
+use bevy::mesh::{MeshVertexAttribute, VertexAttributeValues};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use symbios_turtle_3d::{Skeleton, SkeletonPoint};
+
+/// An untwisted white point. Override other fields with struct update syntax.
+pub fn point(position: Vec3, radius: f32, material_id: u8) -> SkeletonPoint {
+    SkeletonPoint {
+        position,
+        rotation: Quat::IDENTITY,
+        radius,
+        color: Vec4::ONE,
+        material_id,
+        uv_scale: 1.0,
+    }
+}
+
+/// A skeleton of one root strand through `points`.
+pub fn strand(points: &[SkeletonPoint]) -> Skeleton {
+    strands(&[points])
+}
+
+/// One root strand through `positions`, radius 0.1, with a material per point.
+pub fn polyline(positions: &[Vec3], material_ids: &[u8]) -> Skeleton {
+    let points: Vec<SkeletonPoint> = positions
+        .iter()
+        .zip(material_ids)
+        .map(|(&p, &m)| point(p, 0.1, m))
+        .collect();
+    strand(&points)
+}
+
+/// A skeleton of unconnected root strands.
+pub fn strands(strands: &[&[SkeletonPoint]]) -> Skeleton {
+    let mut s = Skeleton::new();
+    for strand in strands {
+        s.start_strand(strand[0], None);
+        for p in &strand[1..] {
+            s.push_node(*p);
+        }
+    }
+    s
+}
+
+pub fn floats(mesh: &Mesh, attribute: MeshVertexAttribute) -> Vec<f32> {
+    match mesh.attribute(attribute) {
+        Some(VertexAttributeValues::Float32(v)) => v.clone(),
+        other => panic!("expected Float32 {}, got {other:?}", attribute.name),
+    }
+}
+
+pub fn uints(mesh: &Mesh, attribute: MeshVertexAttribute) -> Vec<u32> {
+    match mesh.attribute(attribute) {
+        Some(VertexAttributeValues::Uint32(v)) => v.clone(),
+        other => panic!("expected Uint32 {}, got {other:?}", attribute.name),
+    }
+}
+
+pub fn float2(mesh: &Mesh, attribute: MeshVertexAttribute) -> Vec<Vec2> {
+    match mesh.attribute(attribute) {
+        Some(VertexAttributeValues::Float32x2(v)) => v.iter().map(|&v| v.into()).collect(),
+        other => panic!("expected Float32x2 {}, got {other:?}", attribute.name),
+    }
+}
+
+pub fn float3(mesh: &Mesh, attribute: MeshVertexAttribute) -> Vec<Vec3> {
+    match mesh.attribute(attribute) {
+        Some(VertexAttributeValues::Float32x3(v)) => v.iter().map(|&v| v.into()).collect(),
+        other => panic!("expected Float32x3 {}, got {other:?}", attribute.name),
+    }
+}
+
+pub fn float4(mesh: &Mesh, attribute: MeshVertexAttribute) -> Vec<Vec4> {
+    match mesh.attribute(attribute) {
+        Some(VertexAttributeValues::Float32x4(v)) => v.iter().map(|&v| v.into()).collect(),
+        other => panic!("expected Float32x4 {}, got {other:?}", attribute.name),
+    }
+}
+
+pub fn positions(mesh: &Mesh) -> Vec<Vec3> {
+    float3(mesh, Mesh::ATTRIBUTE_POSITION)
+}
+
+pub fn normals(mesh: &Mesh) -> Vec<Vec3> {
+    float3(mesh, Mesh::ATTRIBUTE_NORMAL)
+}
+
+pub fn uvs(mesh: &Mesh) -> Vec<Vec2> {
+    float2(mesh, Mesh::ATTRIBUTE_UV_0)
+}
+
+pub fn colors(mesh: &Mesh) -> Vec<Vec4> {
+    float4(mesh, Mesh::ATTRIBUTE_COLOR)
+}
+
+pub fn indices(mesh: &Mesh) -> Vec<usize> {
+    mesh.indices().unwrap().iter().collect()
+}
+
+pub fn triangles(mesh: &Mesh) -> Vec<[usize; 3]> {
+    indices(mesh)
+        .chunks_exact(3)
+        .map(|t| [t[0], t[1], t[2]])
+        .collect()
+}
+
+/// Welds vertices by position and checks that every edge borders exactly two
+/// triangles — i.e. the surface is closed.
+pub fn assert_watertight(mesh: &Mesh) {
+    let key = |p: Vec3| {
+        (
+            (p.x * 1e4).round() as i64,
+            (p.y * 1e4).round() as i64,
+            (p.z * 1e4).round() as i64,
+        )
+    };
+    let mut welded: HashMap<(i64, i64, i64), usize> = HashMap::new();
+    let ids: Vec<usize> = positions(mesh)
+        .into_iter()
+        .map(|p| {
+            let next = welded.len();
+            *welded.entry(key(p)).or_insert(next)
+        })
+        .collect();
+
+    let mut edges: HashMap<(usize, usize), u32> = HashMap::new();
+    for tri in triangles(mesh) {
+        for (a, b) in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])] {
+            let (a, b) = (ids[a], ids[b]);
+            if a == b {
+                continue;
+            }
+            *edges.entry((a.min(b), a.max(b))).or_default() += 1;
+        }
+    }
+    for (edge, count) in edges {
+        assert_eq!(count, 2, "edge {edge:?} is not shared by two triangles");
+    }
+}
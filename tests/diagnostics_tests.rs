@@ -0,0 +1,216 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::diagnostics::*;
+use bevy_symbios::{CapStyle, CardStyle, CrossSection, LSystemMeshBuilder};
+use common::{point, strands};
+use symbios_turtle_3d::SkeletonPoint;
+
+/// A vertical strand of `segments` unit segments.
+fn column(x: f32, segments: usize, material_id: u8) -> Vec<SkeletonPoint> {
+    (0..=segments)
+        .map(|i| point(Vec3::new(x, i as f32, 0.0), 0.2, material_id))
+        .collect()
+}
+
+#[test]
+fn report_counts_match_build_output() {
+    let skeleton = strands(&[&column(0.0, 3, 0), &column(2.0, 2, 1)]);
+    let builder = || {
+        LSystemMeshBuilder::new()
+            .with_resolution(6)
+            .with_cards(1, CardStyle::Quad { size: 0.5 })
+    };
+    let meshes = builder().build(&skeleton);
+    let (checked, report) = builder().try_build(&skeleton).unwrap();
+
+    assert_eq!(checked.len(), meshes.len());
+    assert_eq!(report.materials.len(), meshes.len());
+    for (id, mesh) in &checked {
+        let stats = report.materials[id];
+        assert_eq!(stats.vertices, mesh.count_vertices());
+        assert_eq!(stats.triangles, mesh.indices().unwrap().len() / 3);
+        assert_eq!(stats.vertices, meshes[id].count_vertices());
+    }
+    assert_eq!(
+        report.vertex_count(),
+        checked.values().map(|m| m.count_vertices()).sum::<usize>()
+    );
+    assert!(report.triangle_count() > 0);
+    assert!(report.is_clean());
+}
+
+#[test]
+fn duplicate_points_are_counted_as_dropped() {
+    // `push_node` already skips repeats, so write the strand directly.
+    let mut skeleton = strands(&[&column(0.0, 2, 0)]);
+    let strand = &mut skeleton.strands[0];
+    strand.insert(1, strand[0]);
+    strand.push(*strand.last().unwrap());
+
+    let (meshes, report) = LSystemMeshBuilder::new().try_build(&skeleton).unwrap();
+    assert_eq!(report.dropped_points, 2);
+    assert!(report.non_finite_inputs.is_empty());
+    assert!(!report.is_clean());
+    assert_eq!(
+        meshes[&0].count_vertices(),
+        LSystemMeshBuilder::new().build(&strands(&[&column(0.0, 2, 0)]))[&0].count_vertices()
+    );
+}
+
+#[test]
+fn non_finite_inputs_are_located() {
+    let good = column(0.0, 2, 0);
+    let mut bad = column(1.0, 3, 0);
+    bad[2].position.x = f32::NAN;
+    bad[3].radius = f32::INFINITY;
+    let skeleton = strands(&[&good, &bad]);
+
+    let (_, report) = LSystemMeshBuilder::new().try_build(&skeleton).unwrap();
+    assert_eq!(
+        report.non_finite_inputs,
+        vec![
+            NonFiniteInput {
+                strand: 1,
+                point: 2,
+                field: InputField::Position,
+            },
+            NonFiniteInput {
+                strand: 1,
+                point: 3,
+                field: InputField::Radius,
+            },
+        ]
+    );
+    // The NaN point is dropped, the infinite radius poisons its ring.
+    assert_eq!(report.dropped_points, 1);
+    assert!(report.non_finite_vertices > 0);
+}
+
+#[test]
+fn budgets_abort_at_the_offending_strand() {
+    let columns: Vec<Vec<SkeletonPoint>> = (0..4).map(|i| column(i as f32, 2, 0)).collect();
+    let refs: Vec<&[SkeletonPoint]> = columns.iter().map(Vec::as_slice).collect();
+    let skeleton = strands(&refs);
+    let (_, report) = LSystemMeshBuilder::new().try_build(&skeleton).unwrap();
+    let per_strand = report.vertex_count() / 4;
+    let tris_per_strand = report.triangle_count() / 4;
+
+    let err = LSystemMeshBuilder::new()
+        .with_vertex_budget(per_strand * 2 + 1)
+        .try_build(&skeleton)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        BuildError::VertexBudgetExceeded {
+            budget: per_strand * 2 + 1,
+            vertices: per_strand * 3,
+            strand: Some(2),
+        }
+    );
+    assert!(err.to_string().contains("strand 2"));
+
+    let err = LSystemMeshBuilder::new()
+        .with_triangle_budget(tris_per_strand)
+        .try_build(&skeleton)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        BuildError::TriangleBudgetExceeded {
+            strand: Some(1),
+            ..
+        }
+    ));
+
+    // Budgets that fit leave the output untouched, and `build` ignores them.
+    let (meshes, _) = LSystemMeshBuilder::new()
+        .with_vertex_budget(report.vertex_count())
+        .with_triangle_budget(report.triangle_count())
+        .try_build(&skeleton)
+        .unwrap();
+    assert_eq!(meshes[&0].count_vertices(), report.vertex_count());
+    let unchecked = LSystemMeshBuilder::new()
+        .with_vertex_budget(0)
+        .build(&skeleton);
+    assert_eq!(unchecked[&0].count_vertices(), report.vertex_count());
+}
+
+#[test]
+fn clamped_options_are_reported() {
+    let skeleton = strands(&[&column(0.0, 2, 0)]);
+    let (meshes, report) = LSystemMeshBuilder::new()
+        .with_resolution(500)
+        .with_material_cross_section(0, CrossSection::Polygon { sides: 2 })
+        .with_cards(1, CardStyle::Quad { size: -1.0 })
+        .with_lightmap_uvs(Some(0.01))
+        .try_build(&skeleton)
+        .unwrap();
+    assert_eq!(
+        report.clamped_options,
+        vec![
+            ClampedOption {
+                method: "with_resolution",
+                parameter: "resolution",
+                material_id: None,
+                requested: 500.0,
+                effective: 128.0,
+            },
+            ClampedOption {
+                method: "with_material_cross_section",
+                parameter: "sides",
+                material_id: Some(0),
+                requested: 2.0,
+                effective: 3.0,
+            },
+            ClampedOption {
+                method: "with_cards",
+                parameter: "size",
+                material_id: Some(1),
+                requested: -1.0,
+                effective: 0.0,
+            },
+        ]
+    );
+    assert!(report.is_clean(), "clamps are not skeleton problems");
+    assert!(!meshes.is_empty());
+
+    // Setting an option again replaces its entry.
+    let (_, report) = LSystemMeshBuilder::new()
+        .with_resolution(1)
+        .with_resolution(12)
+        .try_build(&skeleton)
+        .unwrap();
+    assert!(report.clamped_options.is_empty());
+}
+
+#[test]
+fn budgets_cover_vertices_added_when_finishing() {
+    let skeleton = strands(&[&column(0.0, 2, 0), &column(2.0, 2, 0)]);
+    let builder = || {
+        LSystemMeshBuilder::new()
+            .with_resolution(6)
+            .with_caps(CapStyle::Dome)
+    };
+    let (_, plain) = builder().try_build(&skeleton).unwrap();
+    let (_, unwrapped) = builder()
+        .with_lightmap_uvs(Some(0.0))
+        .try_build(&skeleton)
+        .unwrap();
+    assert!(unwrapped.vertex_count() > plain.vertex_count());
+
+    // Every strand fits, but the lightmap seams push the total over.
+    let err = builder()
+        .with_lightmap_uvs(Some(0.0))
+        .with_vertex_budget(plain.vertex_count())
+        .try_build(&skeleton)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        BuildError::VertexBudgetExceeded {
+            budget: plain.vertex_count(),
+            vertices: unwrapped.vertex_count(),
+            strand: None,
+        }
+    );
+    assert!(err.to_string().contains("finished meshes"));
+}
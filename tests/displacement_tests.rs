@@ -0,0 +1,178 @@
+mod common;
+
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy_symbios::displacement::{Displacement, Heightfield};
+use bevy_symbios::materials::ProceduralTextureConfig;
+use bevy_symbios::{CapStyle, LSystemMeshBuilder};
+use common::{float3, point};
+use symbios_turtle_3d::Skeleton;
+
+const RADIUS: f32 = 0.5;
+
+/// A straight vertical strand of four unit segments.
+fn column() -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, RADIUS, 0), None);
+    for i in 1..=4 {
+        s.push_node(point(Vec3::Y * i as f32, RADIUS, 0));
+    }
+    s
+}
+
+fn noise(amplitude: f32, seed: u32) -> Displacement {
+    Displacement::Noise {
+        amplitude,
+        frequency: 3.0,
+        seed,
+    }
+}
+
+#[test]
+fn zero_amplitude_matches_plain_build() {
+    let plain = LSystemMeshBuilder::new().build(&column());
+    let flat = LSystemMeshBuilder::new()
+        .with_displacement(noise(0.0, 1))
+        .build(&column());
+    assert_eq!(
+        float3(&plain[&0], Mesh::ATTRIBUTE_POSITION),
+        float3(&flat[&0], Mesh::ATTRIBUTE_POSITION)
+    );
+    assert_eq!(
+        float3(&plain[&0], Mesh::ATTRIBUTE_NORMAL),
+        float3(&flat[&0], Mesh::ATTRIBUTE_NORMAL)
+    );
+}
+
+#[test]
+fn noise_relief_is_bounded_and_seeded() {
+    let build = |seed| {
+        let meshes = LSystemMeshBuilder::new()
+            .with_resolution(16)
+            .with_displacement(noise(0.4, seed))
+            .build(&column());
+        float3(&meshes[&0], Mesh::ATTRIBUTE_POSITION)
+    };
+    let positions = build(7);
+    let radii: Vec<f32> = positions.iter().map(|p| p.xz().length()).collect();
+    // Heights span 0..=1 around 0.5, so radii stay within ±20%.
+    assert!(
+        radii
+            .iter()
+            .all(|r| (r - RADIUS).abs() <= 0.2 * RADIUS + 1e-5)
+    );
+    assert!(radii.iter().any(|r| (r - RADIUS).abs() > 0.01));
+
+    assert_eq!(positions, build(7));
+    assert_ne!(positions, build(8));
+}
+
+#[test]
+fn recomputed_normals_are_outward_and_seamless() {
+    let res = 12;
+    let meshes = LSystemMeshBuilder::new()
+        .with_resolution(res)
+        .with_displacement(noise(0.5, 3))
+        .build(&column());
+    let positions = float3(&meshes[&0], Mesh::ATTRIBUTE_POSITION);
+    let normals = float3(&meshes[&0], Mesh::ATTRIBUTE_NORMAL);
+
+    for (p, n) in positions.iter().zip(&normals) {
+        assert!((n.length() - 1.0).abs() < 1e-4);
+        assert!(n.dot(p.with_y(0.0).normalize()) > 0.0, "{p} {n}");
+    }
+    // Both seam vertices of every ring share position and normal.
+    let ring = res as usize + 1;
+    for start in (0..positions.len()).step_by(ring) {
+        let end = start + ring - 1;
+        assert!(positions[start].distance(positions[end]) < 1e-4);
+        assert!(normals[start].distance(normals[end]) < 1e-4);
+    }
+    // Displacement tilts normals away from the undisplaced radial direction.
+    assert!(
+        positions
+            .iter()
+            .zip(&normals)
+            .any(|(p, n)| n.dot(p.with_y(0.0).normalize()) < 0.99)
+    );
+}
+
+#[test]
+fn heightfield_validates_and_wraps() {
+    assert!(Heightfield::new(2, 2, vec![0.0; 3]).is_none());
+    assert!(Heightfield::new(0, 2, Vec::new()).is_none());
+
+    let field = Heightfield::new(2, 1, vec![0.0, 1.0]).unwrap();
+    assert_eq!(field.sample(Vec2::new(0.25, 0.5)), 0.0);
+    assert_eq!(field.sample(Vec2::new(0.75, 0.5)), 1.0);
+    // Halfway between the last and (wrapped) first texel.
+    assert!((field.sample(Vec2::new(1.0, 0.5)) - 0.5).abs() < 1e-6);
+    assert_eq!(
+        field.sample(Vec2::new(0.25, 0.5)),
+        field.sample(Vec2::new(-0.75, 3.5))
+    );
+
+    // A uniform peak field inflates every ring by half the amplitude.
+    let ridge = Heightfield::new(1, 1, vec![1.0]).unwrap();
+    let meshes = LSystemMeshBuilder::new()
+        .with_displacement(Displacement::Heightfield {
+            amplitude: 0.5,
+            field: Arc::new(ridge),
+            tiling: Vec2::ONE,
+        })
+        .build(&column());
+    for p in float3(&meshes[&0], Mesh::ATTRIBUTE_POSITION) {
+        assert!((p.xz().length() - RADIUS * 1.25).abs() < 1e-4, "{p}");
+    }
+}
+
+#[test]
+fn heightfield_from_procedural_texture_spans_unit_range() {
+    let bark = ProceduralTextureConfig::Bark(Default::default());
+    let field = Heightfield::from_texture_config(&bark, 16).unwrap();
+    assert_eq!((field.width(), field.height()), (16, 16));
+    let samples: Vec<f32> = (0..16)
+        .flat_map(|y| (0..16).map(move |x| Vec2::new(x as f32 + 0.5, y as f32 + 0.5) / 16.0))
+        .map(|uv| field.sample(uv))
+        .collect();
+    assert!(samples.iter().all(|h| (0.0..=1.0).contains(h)));
+    assert!(samples.iter().any(|&h| h < 1e-6));
+    assert!(samples.iter().any(|&h| h > 1.0 - 1e-6));
+    assert!(Heightfield::from_texture_config(&ProceduralTextureConfig::None, 16).is_none());
+}
+
+#[test]
+fn caps_and_material_overrides_follow_displaced_rings() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_caps(CapStyle::Flat)
+        .with_displacement(noise(0.5, 11))
+        .build(&column());
+    let positions = float3(&meshes[&0], Mesh::ATTRIBUTE_POSITION);
+    let normals = float3(&meshes[&0], Mesh::ATTRIBUTE_NORMAL);
+    // Every cap rim vertex sits exactly on a tube vertex.
+    for (p, n) in positions.iter().zip(&normals) {
+        if n.y.abs() > 0.999 && p.xz().length() > 1e-3 {
+            assert!(
+                positions
+                    .iter()
+                    .zip(&normals)
+                    .any(|(q, m)| q == p && m.y.abs() < 0.999),
+                "{p}"
+            );
+        }
+    }
+
+    // A material override of `None` exempts that material.
+    let exempt = LSystemMeshBuilder::new()
+        .with_displacement(noise(0.5, 11))
+        .with_material_displacement(0, Displacement::None)
+        .build(&column());
+    assert_eq!(
+        float3(&exempt[&0], Mesh::ATTRIBUTE_POSITION),
+        float3(
+            &LSystemMeshBuilder::new().build(&column())[&0],
+            Mesh::ATTRIBUTE_POSITION
+        )
+    );
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bevy::prelude::*;
+use bevy_symbios::{CapStyle, CardStyle, LSystemMeshBuilder, MeshCache, StrandSmoothing};
+use common::point;
+use symbios_turtle_3d::Skeleton;
+
+/// A tapering trunk (material 0) with a branch (material 0, then leaves in
+/// material 2) and a twig off the branch.
+fn tree() -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.4, 0), None);
+    s.push_node(point(Vec3::Y, 0.3, 0));
+    s.push_node(point(Vec3::Y * 2.0, 0.2, 0));
+    s.start_strand(point(Vec3::Y, 0.1, 0), Some(0));
+    s.push_node(point(Vec3::new(1.0, 1.5, 0.0), 0.08, 2));
+    s.push_node(point(Vec3::new(2.0, 2.0, 0.0), 0.05, 2));
+    s.start_strand(point(Vec3::new(1.0, 1.5, 0.0), 0.04, 0), Some(1));
+    s.push_node(point(Vec3::new(1.0, 2.5, 0.0), 0.02, 0));
+    s
+}
+
+/// Tube vertices of one segment at the default resolution of 8.
+const RING: usize = 9;
+
+fn vertices(meshes: &bevy::platform::collections::HashMap<u16, Mesh>, id: u16) -> usize {
+    meshes.get(&id).map_or(0, Mesh::count_vertices)
+}
+
+#[test]
+fn radius_filters_skip_segments_by_their_start_point() {
+    let all = LSystemMeshBuilder::new().build(&tree());
+    assert_eq!(vertices(&all, 0), 3 * RING + 2 * RING + 2 * RING);
+
+    // Only the trunk is at least 0.15 thick.
+    let trunk = LSystemMeshBuilder::new()
+        .with_min_radius(0.15)
+        .build(&tree());
+    assert_eq!(vertices(&trunk, 0), 3 * RING);
+    assert_eq!(vertices(&trunk, 2), 0);
+
+    // The trunk's first segment starts at radius 0.4; the rest stay.
+    let slim = LSystemMeshBuilder::new()
+        .with_max_radius(0.35)
+        .build(&tree());
+    assert_eq!(vertices(&slim, 0), 2 * RING + 2 * RING + 2 * RING);
+    assert_eq!(vertices(&slim, 2), vertices(&all, 2));
+}
+
+#[test]
+fn material_sets_select_buckets() {
+    let leaves = LSystemMeshBuilder::new()
+        .with_included_materials([2])
+        .build(&tree());
+    assert_eq!(leaves.len(), 1);
+    assert_eq!(
+        vertices(&leaves, 2),
+        vertices(&LSystemMeshBuilder::new().build(&tree()), 2)
+    );
+
+    let wood = LSystemMeshBuilder::new()
+        .with_included_materials([0, 2])
+        .with_excluded_materials([2])
+        .build(&tree());
+    assert!(!wood.contains_key(&2));
+
+    // Card materials are filtered as well.
+    let cards = LSystemMeshBuilder::new()
+        .with_cards(2, CardStyle::Quad { size: 1.0 })
+        .with_excluded_materials([2]);
+    assert!(!cards.build(&tree()).contains_key(&2));
+}
+
+#[test]
+fn max_depth_drops_whole_strands() {
+    let depth = |max| {
+        vertices(
+            &LSystemMeshBuilder::new()
+                .with_max_depth(max)
+                .with_caps(CapStyle::Flat)
+                .build(&tree()),
+            0,
+        )
+    };
+    let capped = vertices(
+        &LSystemMeshBuilder::new()
+            .with_caps(CapStyle::Flat)
+            .build(&tree()),
+        0,
+    );
+    assert!(depth(0) < depth(1));
+    assert!(depth(1) < depth(2));
+    assert_eq!(depth(2), capped);
+}
+
+#[test]
+fn point_filter_sees_strand_indices_and_original_points() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    // Drop the second strand's points, whichever smoothing is applied.
+    let meshes = LSystemMeshBuilder::new()
+        .with_smoothing(StrandSmoothing::CatmullRom { subdivisions: 2 })
+        .with_point_filter(1, move |strand, point| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            assert!(point.radius > 0.0);
+            strand != 1
+        })
+        .build(&tree());
+    assert!(calls.load(Ordering::Relaxed) >= 8);
+    assert!(!meshes.contains_key(&2));
+
+    let unsmoothed = LSystemMeshBuilder::new()
+        .with_point_filter(1, |strand, _| strand != 1)
+        .build(&tree());
+    assert_eq!(vertices(&unsmoothed, 0), 3 * RING + 2 * RING);
+}
+
+#[test]
+fn filters_are_part_of_the_cache_key() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(bevy::asset::AssetPlugin::default())
+        .init_asset::<Mesh>();
+    let mut cache = MeshCache::new();
+    let mut meshes = app.world_mut().resource_mut::<Assets<Mesh>>();
+
+    let full = LSystemMeshBuilder::new().build_cached(&tree(), &mut cache, &mut meshes);
+    let trunk = LSystemMeshBuilder::new()
+        .with_min_radius(0.15)
+        .build_cached(&tree(), &mut cache, &mut meshes);
+    assert_eq!(cache.misses(), 2);
+    assert_ne!(full[&0], trunk[&0]);
+    assert_eq!(meshes.get(&trunk[&0]).unwrap().count_vertices(), 3 * RING);
+}
+
+#[test]
+fn point_filters_are_cached_by_key() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(bevy::asset::AssetPlugin::default())
+        .init_asset::<Mesh>();
+    let mut cache = MeshCache::new();
+    let mut meshes = app.world_mut().resource_mut::<Assets<Mesh>>();
+
+    let builder = LSystemMeshBuilder::new().with_point_filter(1, |strand, _| strand != 1);
+    let first = builder.build_cached(&tree(), &mut cache, &mut meshes);
+    // A different closure under a different key misses, even though the
+    // first builder and its predicate are gone.
+    let second = LSystemMeshBuilder::new()
+        .with_point_filter(2, |strand, _| strand != 2)
+        .build_cached(&tree(), &mut cache, &mut meshes);
+    assert_eq!(cache.misses(), 2);
+    assert_ne!(first[&0], second[&0]);
+
+    // The same key hits, whatever closure it comes with.
+    LSystemMeshBuilder::new()
+        .with_point_filter(1, |strand, _| strand != 1)
+        .build_cached(&tree(), &mut cache, &mut meshes);
+    assert_eq!(cache.hits(), 1);
+}
@@ -0,0 +1,208 @@
+mod common;
+
+use bevy::camera::primitives::Aabb;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_symbios::forest::*;
+use bevy_symbios::materials::MaterialPalette;
+use bevy_symbios::{
+    ATTRIBUTE_BIRTH, ATTRIBUTE_BRANCH_RADIUS, ATTRIBUTE_PREVIOUS_SPINE, ATTRIBUTE_ROOT_DISTANCE,
+    ATTRIBUTE_SPINE, LSystemMeshBuilder,
+};
+use common::{float3, float4, floats, point, positions};
+use symbios_turtle_3d::Skeleton;
+
+/// A unit-tall trunk in material 0 topped by a short material 1 tip.
+fn tree_meshes() -> HashMap<u16, Mesh> {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.1, 0), None);
+    s.push_node(point(Vec3::Y, 0.1, 0));
+    s.start_strand(point(Vec3::Y, 0.1, 1), Some(0));
+    s.push_node(point(Vec3::Y * 1.5, 0.1, 1));
+    LSystemMeshBuilder::new().build(&s)
+}
+
+#[test]
+fn trees_in_one_chunk_merge_per_material() {
+    let meshes = tree_meshes();
+    let mut forest = ForestBatch::new(10.0);
+    let a = forest.insert(Transform::from_xyz(1.0, 0.0, 1.0), meshes.clone());
+    let b = forest.insert(
+        Transform::from_xyz(5.0, 2.0, 5.0).with_scale(Vec3::splat(2.0)),
+        meshes.clone(),
+    );
+    assert_eq!(forest.chunk_of(a), forest.chunk_of(b));
+    assert_eq!(forest.trees_in(IVec2::ZERO), &[a, b]);
+
+    let chunk = forest.build_chunk(IVec2::ZERO);
+    assert_eq!(chunk.meshes.len(), 2);
+    let trunk = &chunk.meshes[&0];
+    let single = meshes[&0].count_vertices();
+    assert_eq!(trunk.count_vertices(), 2 * single);
+    assert_eq!(
+        trunk.indices().unwrap().len(),
+        2 * meshes[&0].indices().unwrap().len()
+    );
+
+    // Vertices are placed by each tree's transform.
+    let placed = positions(trunk);
+    let source = positions(&meshes[&0]);
+    assert!(placed[0].abs_diff_eq(source[0] + Vec3::new(1.0, 0.0, 1.0), 1e-5));
+    assert!(placed[single].abs_diff_eq(source[0] * 2.0 + Vec3::new(5.0, 2.0, 5.0), 1e-5));
+
+    // Bounds cover the scaled tree's 3-unit-tall top.
+    let aabb = chunk.aabb.unwrap();
+    assert!((aabb.max().y - 5.0).abs() < 1e-4);
+    assert!(aabb.min().x < 1.0);
+}
+
+#[test]
+fn growth_and_hierarchy_attributes_follow_the_transform() {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.1, 0), None);
+    s.push_node(point(Vec3::Y, 0.1, 0));
+    s.push_node(point(Vec3::Y * 2.0, 0.1, 0));
+    let meshes = LSystemMeshBuilder::new()
+        .with_growth_attributes(true)
+        .with_hierarchy_attributes(true)
+        .build(&s);
+    let transform = Transform::from_xyz(3.0, 1.0, 2.0).with_scale(Vec3::splat(2.0));
+    let mut forest = ForestBatch::new(10.0);
+    forest.insert(transform, meshes.clone());
+
+    let chunk = forest.build_chunk(IVec2::ZERO);
+    let (source, placed) = (&meshes[&0], &chunk.meshes[&0]);
+    for (before, after) in float3(source, ATTRIBUTE_SPINE)
+        .iter()
+        .zip(float3(placed, ATTRIBUTE_SPINE))
+    {
+        assert!(after.abs_diff_eq(transform.transform_point(*before), 1e-5));
+    }
+    for (before, after) in float4(source, ATTRIBUTE_PREVIOUS_SPINE)
+        .iter()
+        .zip(float4(placed, ATTRIBUTE_PREVIOUS_SPINE))
+    {
+        let moved = transform.transform_point(before.truncate());
+        assert!(after.truncate().abs_diff_eq(moved, 1e-5));
+        assert_eq!(after.w, before.w, "birth is unchanged");
+    }
+    for attribute in [ATTRIBUTE_ROOT_DISTANCE, ATTRIBUTE_BRANCH_RADIUS] {
+        for (before, after) in floats(source, attribute)
+            .iter()
+            .zip(floats(placed, attribute))
+        {
+            assert!((after - before * 2.0).abs() < 1e-5);
+        }
+    }
+    assert_eq!(
+        floats(source, ATTRIBUTE_BIRTH),
+        floats(placed, ATTRIBUTE_BIRTH)
+    );
+}
+
+#[test]
+fn chunk_keys_floor_on_the_xz_plane() {
+    let forest = ForestBatch::new(10.0);
+    assert_eq!(
+        forest.chunk_key(Vec3::new(9.9, 100.0, 0.0)),
+        IVec2::new(0, 0)
+    );
+    assert_eq!(
+        forest.chunk_key(Vec3::new(-0.1, 0.0, 25.0)),
+        IVec2::new(-1, 2)
+    );
+    assert_eq!(ForestBatch::new(-3.0).chunk_size(), DEFAULT_CHUNK_SIZE);
+}
+
+#[test]
+fn edits_only_dirty_affected_chunks() {
+    let mut forest = ForestBatch::new(10.0);
+    let a = forest.insert(Transform::from_xyz(1.0, 0.0, 1.0), tree_meshes());
+    forest.insert(Transform::from_xyz(15.0, 0.0, 1.0), tree_meshes());
+    forest.insert(Transform::from_xyz(25.0, 0.0, 1.0), tree_meshes());
+    assert_eq!(forest.take_dirty().len(), 3);
+    assert!(forest.take_dirty().is_empty());
+
+    // Moving a tree dirties the chunk it left and the one it entered.
+    assert!(forest.replace(a, Transform::from_xyz(12.0, 0.0, 1.0), tree_meshes()));
+    assert_eq!(
+        forest.take_dirty(),
+        vec![IVec2::new(0, 0), IVec2::new(1, 0)]
+    );
+    assert!(forest.trees_in(IVec2::ZERO).is_empty());
+    assert_eq!(forest.trees_in(IVec2::new(1, 0)).len(), 2);
+
+    assert!(forest.remove(a));
+    assert!(!forest.remove(a));
+    assert_eq!(forest.take_dirty(), vec![IVec2::new(1, 0)]);
+    assert_eq!(forest.len(), 2);
+    assert!(forest.build_chunk(IVec2::ZERO).aabb.is_none());
+}
+
+#[test]
+fn mismatched_attributes_are_skipped() {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.1, 0), None);
+    s.push_node(point(Vec3::Y, 0.1, 0));
+    let plain = LSystemMeshBuilder::new().build(&s);
+    let tagged = LSystemMeshBuilder::new()
+        .with_hierarchy_attributes(true)
+        .build(&s);
+
+    let mut forest = ForestBatch::new(10.0);
+    forest.insert(Transform::IDENTITY, plain.clone());
+    forest.insert(Transform::from_xyz(1.0, 0.0, 0.0), tagged);
+    let chunk = forest.build_chunk(IVec2::ZERO);
+    assert_eq!(
+        chunk.meshes[&0].count_vertices(),
+        plain[&0].count_vertices()
+    );
+}
+
+#[test]
+fn sync_respawns_only_dirty_chunks() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(bevy::asset::AssetPlugin::default())
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .insert_resource(ForestBatch::new(10.0))
+        .add_systems(Update, sync_forest_chunks);
+    let primary = app
+        .world_mut()
+        .resource_mut::<Assets<StandardMaterial>>()
+        .add(StandardMaterial::default());
+    app.insert_resource(MaterialPalette {
+        materials: HashMap::new(),
+        primary_material: primary,
+    });
+
+    let (a, _) = {
+        let mut forest = app.world_mut().resource_mut::<ForestBatch>();
+        let a = forest.insert(Transform::from_xyz(1.0, 0.0, 1.0), tree_meshes());
+        let b = forest.insert(Transform::from_xyz(15.0, 0.0, 1.0), tree_meshes());
+        (a, b)
+    };
+    app.update();
+
+    let parts = |app: &mut App| {
+        let mut query = app.world_mut().query::<(Entity, &ForestChunkPart, &Aabb)>();
+        let mut parts: Vec<(Entity, ForestChunkPart)> = query
+            .iter(app.world())
+            .map(|(entity, part, _)| (entity, *part))
+            .collect();
+        parts.sort_by_key(|(entity, _)| *entity);
+        parts
+    };
+    let before = parts(&mut app);
+    assert_eq!(before.len(), 4, "two chunks with two materials each");
+
+    app.world_mut().resource_mut::<ForestBatch>().remove(a);
+    app.update();
+    let after = parts(&mut app);
+    assert_eq!(after.len(), 2);
+    // The untouched chunk keeps its entities.
+    assert!(after.iter().all(|(entity, part)| {
+        part.key == IVec2::new(1, 0) && before.iter().any(|(e, _)| e == entity)
+    }));
+}
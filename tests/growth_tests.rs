@@ -0,0 +1,183 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::growth::*;
+use bevy_symbios::{
+    ATTRIBUTE_BIRTH, ATTRIBUTE_PREVIOUS_SPINE, ATTRIBUTE_ROOT_DISTANCE, ATTRIBUTE_SPINE, CapStyle,
+    CardStyle, LSystemMeshBuilder,
+};
+use common::{float3, float4, floats, point};
+use symbios_turtle_3d::Skeleton;
+
+/// Trunk of length 2 with a branch of length 2 from its tip, ending in a leaf.
+fn tree() -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.1, 0), None);
+    s.push_node(point(Vec3::Y, 0.1, 0));
+    s.push_node(point(Vec3::Y * 2.0, 0.1, 0));
+    s.start_strand(point(Vec3::Y * 2.0, 0.1, 0), Some(0));
+    s.push_node(point(Vec3::new(2.0, 2.0, 0.0), 0.1, 2));
+    s
+}
+
+fn births(mesh: &Mesh) -> Vec<f32> {
+    floats(mesh, ATTRIBUTE_BIRTH)
+}
+
+fn spines(mesh: &Mesh) -> Vec<Vec3> {
+    float3(mesh, ATTRIBUTE_SPINE)
+}
+
+fn previous(mesh: &Mesh) -> Vec<Vec4> {
+    float4(mesh, ATTRIBUTE_PREVIOUS_SPINE)
+}
+
+#[test]
+fn growth_attributes_are_opt_in_and_independent() {
+    let plain = LSystemMeshBuilder::new().build(&tree());
+    assert!(plain[&0].attribute(ATTRIBUTE_BIRTH).is_none());
+
+    let growth = LSystemMeshBuilder::new()
+        .with_growth_attributes(true)
+        .build(&tree());
+    assert!(growth[&0].attribute(ATTRIBUTE_BIRTH).is_some());
+    assert!(
+        growth[&0].attribute(ATTRIBUTE_ROOT_DISTANCE).is_none(),
+        "hierarchy attributes stay off"
+    );
+}
+
+#[test]
+fn birth_is_normalized_root_distance() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_growth_attributes(true)
+        .with_caps(CapStyle::Dome)
+        .build(&tree());
+    let mesh = &meshes[&0];
+    let count = mesh.count_vertices();
+    let births = births(mesh);
+    let spines = spines(mesh);
+    assert_eq!(births.len(), count);
+    assert_eq!(spines.len(), count);
+
+    // Longest root distance is 4 (trunk 2 + branch 2).
+    for (birth, spine) in births.iter().zip(&spines) {
+        let root_distance = if spine.x > 0.0 {
+            2.0 + spine.x
+        } else {
+            spine.y
+        };
+        assert!(
+            (birth - root_distance / 4.0).abs() < 1e-5,
+            "birth {birth} at {spine}"
+        );
+    }
+    assert!(births.contains(&0.0));
+}
+
+#[test]
+fn previous_spine_points_back_along_the_strand() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_growth_attributes(true)
+        .build(&tree());
+    let mesh = &meshes[&0];
+    for ((spine, previous), birth) in spines(mesh).iter().zip(previous(mesh)).zip(births(mesh)) {
+        if previous.truncate() == *spine {
+            // First point of a strand refers to itself.
+            assert_eq!(previous.w, birth);
+            assert!(*spine == Vec3::ZERO || *spine == Vec3::Y * 2.0);
+        } else {
+            // Trunk segments are 1 long, the branch segment 2.
+            let expected = if spine.x > 0.0 { 2.0 } else { 1.0 };
+            assert!((spine.distance(previous.truncate()) - expected).abs() < 1e-5);
+            assert!((birth - previous.w - expected / 4.0).abs() < 1e-5);
+        }
+    }
+}
+
+#[test]
+fn cards_grow_from_their_base() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_growth_attributes(true)
+        .with_cards(2, CardStyle::Quad { size: 2.0 })
+        .build(&tree());
+    let leaf = &meshes[&2];
+    let tip = Vec3::new(2.0, 2.0, 0.0);
+    assert!(spines(leaf).iter().all(|s| *s == tip));
+    assert!(births(leaf).iter().all(|b| (*b - 1.0).abs() < 1e-6));
+    assert!(previous(leaf).iter().all(|p| *p == tip.extend(1.0)));
+}
+
+#[test]
+fn parallel_build_matches_serial() {
+    let serial = LSystemMeshBuilder::new()
+        .with_growth_attributes(true)
+        .build(&tree());
+    let parallel = LSystemMeshBuilder::new()
+        .with_growth_attributes(true)
+        .build_parallel(&tree());
+    assert_eq!(births(&serial[&0]), births(&parallel[&0]));
+    assert_eq!(previous(&serial[&0]), previous(&parallel[&0]));
+}
+
+fn growth_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(bevy::asset::AssetPlugin::default())
+        .init_asset::<Image>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<GrowthMaterial>()
+        .add_systems(Update, (advance_growth, apply_growth_progress).chain());
+    app
+}
+
+fn progress_of(app: &App, handle: &Handle<GrowthMaterial>) -> f32 {
+    app.world()
+        .resource::<Assets<GrowthMaterial>>()
+        .get(handle)
+        .unwrap()
+        .extension
+        .growth
+        .progress
+}
+
+#[test]
+fn progress_is_read_from_the_parent() {
+    let mut app = growth_app();
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<GrowthMaterial>>()
+        .add(GrowthMaterial::default());
+    let tree = app.world_mut().spawn(GrowthProgress(0.25)).id();
+    app.world_mut()
+        .spawn((MeshMaterial3d(handle.clone()), ChildOf(tree)));
+    app.update();
+    assert_eq!(progress_of(&app, &handle), 0.25);
+
+    app.world_mut().get_mut::<GrowthProgress>(tree).unwrap().0 = 0.75;
+    app.update();
+    assert_eq!(progress_of(&app, &handle), 0.75);
+}
+
+#[test]
+fn growth_rate_advances_and_clamps() {
+    let mut app = growth_app();
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<GrowthMaterial>>()
+        .add(GrowthMaterial::default());
+    let plant = app
+        .world_mut()
+        .spawn((
+            MeshMaterial3d(handle.clone()),
+            GrowthProgress(0.0),
+            GrowthRate(1000.0),
+        ))
+        .id();
+    // The first update has zero delta time.
+    app.update();
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    app.update();
+    assert_eq!(app.world().get::<GrowthProgress>(plant).unwrap().0, 1.0);
+    assert_eq!(progress_of(&app, &handle), 1.0);
+}
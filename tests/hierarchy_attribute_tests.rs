@@ -0,0 +1,120 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::{
+    ATTRIBUTE_BRANCH_DEPTH, ATTRIBUTE_BRANCH_RADIUS, ATTRIBUTE_ROOT_DISTANCE,
+    ATTRIBUTE_STRAND_INDEX, ATTRIBUTE_STRAND_PROGRESS, CapStyle, LSystemMeshBuilder,
+};
+use common::{floats, point, positions, uints};
+use symbios_turtle_3d::Skeleton;
+
+/// Trunk from the origin up to (0, 2, 0), a branch from its tip along +X, and
+/// a twig from the branch tip along +Z.
+fn tree() -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.2, 0), None);
+    s.push_node(point(Vec3::Y * 2.0, 0.1, 0));
+    s.start_strand(point(Vec3::Y * 2.0, 0.1, 0), Some(0));
+    s.push_node(point(Vec3::new(1.0, 2.0, 0.0), 0.05, 0));
+    s.start_strand(point(Vec3::new(1.0, 2.0, 0.0), 0.05, 0), Some(1));
+    s.push_node(point(Vec3::new(1.0, 2.0, 0.5), 0.02, 0));
+    s
+}
+
+#[test]
+fn attributes_are_opt_in() {
+    let meshes = LSystemMeshBuilder::new().build(&tree());
+    let mesh = &meshes[&0];
+    assert!(mesh.attribute(ATTRIBUTE_STRAND_INDEX).is_none());
+    assert!(mesh.attribute(ATTRIBUTE_ROOT_DISTANCE).is_none());
+}
+
+#[test]
+fn every_vertex_is_tagged() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_hierarchy_attributes(true)
+        .with_caps(CapStyle::Dome)
+        .build(&tree());
+    let mesh = &meshes[&0];
+    let count = mesh.count_vertices();
+    assert_eq!(uints(mesh, ATTRIBUTE_STRAND_INDEX).len(), count);
+    assert_eq!(uints(mesh, ATTRIBUTE_BRANCH_DEPTH).len(), count);
+    assert_eq!(floats(mesh, ATTRIBUTE_STRAND_PROGRESS).len(), count);
+    assert_eq!(floats(mesh, ATTRIBUTE_ROOT_DISTANCE).len(), count);
+    assert_eq!(floats(mesh, ATTRIBUTE_BRANCH_RADIUS).len(), count);
+}
+
+#[test]
+fn values_follow_the_hierarchy() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_hierarchy_attributes(true)
+        .build(&tree());
+    let mesh = &meshes[&0];
+    let positions = positions(mesh);
+    let strands = uints(mesh, ATTRIBUTE_STRAND_INDEX);
+    let depths = uints(mesh, ATTRIBUTE_BRANCH_DEPTH);
+    let progress = floats(mesh, ATTRIBUTE_STRAND_PROGRESS);
+    let root = floats(mesh, ATTRIBUTE_ROOT_DISTANCE);
+    let radii = floats(mesh, ATTRIBUTE_BRANCH_RADIUS);
+
+    for i in 0..positions.len() {
+        let p = positions[i];
+        assert_eq!(depths[i], strands[i], "depth equals strand index here");
+        match strands[i] {
+            0 => {
+                // Trunk: progress is height / 2.
+                assert!((progress[i] - p.y / 2.0).abs() < 1e-4);
+                assert!((root[i] - p.y).abs() < 1e-4);
+            }
+            1 => {
+                assert!((root[i] - (2.0 + progress[i])).abs() < 1e-4);
+            }
+            2 => {
+                assert!((root[i] - (3.0 + progress[i] * 0.5)).abs() < 1e-4);
+            }
+            other => panic!("unexpected strand {other}"),
+        }
+        let expected_radius = [(0.2, 0.1), (0.1, 0.05), (0.05, 0.02)][strands[i] as usize];
+        let at_base = progress[i] == 0.0;
+        let expected = if at_base {
+            expected_radius.0
+        } else {
+            expected_radius.1
+        };
+        assert!((radii[i] - expected).abs() < 1e-6);
+    }
+    assert!(progress.contains(&1.0));
+}
+
+#[test]
+fn cyclic_parents_are_treated_as_roots() {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.1, 0), Some(1));
+    s.push_node(point(Vec3::Y, 0.1, 0));
+    s.start_strand(point(Vec3::Y, 0.1, 0), Some(0));
+    s.push_node(point(Vec3::Y * 2.0, 0.1, 0));
+
+    let meshes = LSystemMeshBuilder::new()
+        .with_hierarchy_attributes(true)
+        .build(&s);
+    let depths = uints(&meshes[&0], ATTRIBUTE_BRANCH_DEPTH);
+    assert!(depths.iter().all(|&d| d <= 1));
+}
+
+#[test]
+fn parallel_build_matches_serial() {
+    let serial = LSystemMeshBuilder::new()
+        .with_hierarchy_attributes(true)
+        .build(&tree());
+    let parallel = LSystemMeshBuilder::new()
+        .with_hierarchy_attributes(true)
+        .build_parallel(&tree());
+    assert_eq!(
+        floats(&serial[&0], ATTRIBUTE_ROOT_DISTANCE),
+        floats(&parallel[&0], ATTRIBUTE_ROOT_DISTANCE)
+    );
+    assert_eq!(
+        uints(&serial[&0], ATTRIBUTE_STRAND_INDEX),
+        uints(&parallel[&0], ATTRIBUTE_STRAND_INDEX)
+    );
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::{CapStyle, JunctionStyle, LSystemMeshBuilder};
+use common::{assert_watertight, normals, point, positions, triangles};
+use symbios_turtle_3d::Skeleton;
+
+/// A vertical trunk (material 0, radius 0.2) that forks at y = 1 into a 45°
+/// branch (material 1, radius 0.05) and a trunk continuation (material 2).
+fn fork_skeleton() -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.2, 0), None);
+    s.push_node(point(Vec3::Y, 0.2, 0));
+    s.start_strand(point(Vec3::Y, 0.05, 1), Some(0));
+    s.push_node(point(Vec3::new(1.0, 2.0, 0.0), 0.05, 1));
+    s.start_strand(point(Vec3::Y, 0.1, 2), Some(0));
+    s.push_node(point(Vec3::Y * 2.0, 0.1, 2));
+    s
+}
+
+fn axis_distance(p: Vec3) -> f32 {
+    p.xz().length()
+}
+
+#[test]
+fn disjoint_is_default() {
+    let meshes = LSystemMeshBuilder::new().build(&fork_skeleton());
+    let branch = positions(&meshes[&1]);
+    // Base ring is centered on the parent axis, buried inside the trunk.
+    for p in &branch[0..9] {
+        assert!(axis_distance(*p) < 0.2);
+    }
+}
+
+#[test]
+fn weld_seats_base_ring_on_parent_surface() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_junctions(JunctionStyle::Weld)
+        .build(&fork_skeleton());
+    let branch = positions(&meshes[&1]);
+    for p in &branch[0..9] {
+        assert!(
+            (axis_distance(*p) - 0.2).abs() < 1e-4,
+            "welded vertex {p:?} should lie on the trunk surface"
+        );
+    }
+}
+
+#[test]
+fn parallel_continuation_is_not_welded() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_junctions(JunctionStyle::Weld)
+        .build(&fork_skeleton());
+    let resume = positions(&meshes[&2]);
+    for p in &resume[0..9] {
+        assert!((axis_distance(*p) - 0.1).abs() < 1e-4);
+        assert!((p.y - 1.0).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn blend_adds_collar_and_flares_base() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_junctions(JunctionStyle::Blend { flare: 1.0 })
+        .build(&fork_skeleton());
+    let branch = &meshes[&1];
+    // Base ring + collar ring + tip ring.
+    assert_eq!(branch.count_vertices(), 27);
+
+    let p = positions(branch);
+    let base_center = p[0..8].iter().sum::<Vec3>() / 8.0;
+    let widest = p[0..8]
+        .iter()
+        .map(|v| v.distance(base_center))
+        .fold(0.0, f32::max);
+    assert!(widest > 0.05 * 1.5, "base ring should be flared");
+    for v in &p[0..9] {
+        assert!((axis_distance(*v) - 0.2).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn welded_strand_keeps_a_hidden_start_cap() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_junctions(JunctionStyle::Weld)
+        .with_caps(CapStyle::Flat)
+        .build(&fork_skeleton());
+    // Two rings plus both caps (center + 9 rim vertices each).
+    assert_eq!(meshes[&1].count_vertices(), 18 + 2 * 10);
+    // The base cap center stays buried inside the trunk.
+    let branch = positions(&meshes[&1]);
+    assert!(branch.iter().any(|p| axis_distance(*p) < 0.19));
+}
+
+#[test]
+fn capped_welds_are_watertight() {
+    for junctions in [JunctionStyle::Weld, JunctionStyle::Blend { flare: 1.0 }] {
+        let meshes = LSystemMeshBuilder::new()
+            .with_junctions(junctions)
+            .with_caps(CapStyle::Flat)
+            .build(&fork_skeleton());
+        for mesh in meshes.values() {
+            assert_watertight(mesh);
+        }
+    }
+}
+
+#[test]
+fn welded_base_ring_normals_follow_the_flare() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_junctions(JunctionStyle::Blend { flare: 1.0 })
+        .build(&fork_skeleton());
+    let branch = &meshes[&1];
+    let p = positions(branch);
+    let normals = normals(branch);
+
+    // Area-weighted face normals around each base vertex, from the final
+    // (welded, flared) triangles. The U seam's two vertices share one sum.
+    let mut expected = [Vec3::ZERO; 8];
+    for tri in triangles(branch) {
+        let face = (p[tri[1]] - p[tri[0]]).cross(p[tri[2]] - p[tri[0]]);
+        for &v in tri.iter().filter(|&&v| v < 9) {
+            expected[v % 8] += face;
+        }
+    }
+
+    for i in 0..9 {
+        let normal = normals[i];
+        let expected = expected[i % 8].normalize();
+        assert!(
+            normal.dot(expected) > 0.999,
+            "vertex {i}: {normal} vs {expected}"
+        );
+    }
+}
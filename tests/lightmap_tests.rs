@@ -0,0 +1,197 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::{CapStyle, CardStyle, LSystemMeshBuilder, StrandSmoothing};
+use common::{float2, point, positions, triangles};
+use symbios_turtle_3d::Skeleton;
+
+/// A bending trunk, a thinner branch, and a branch ending in two leaves
+/// (material 2).
+fn tree() -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.3, 0), None);
+    for i in 1..5 {
+        let x = (i as f32 * 0.7).sin() * 0.5;
+        s.push_node(point(Vec3::new(x, i as f32, 0.0), 0.3 - i as f32 * 0.05, 0));
+    }
+    s.start_strand(point(Vec3::Y * 2.0, 0.1, 0), Some(0));
+    s.push_node(point(Vec3::new(1.5, 3.0, 0.5), 0.05, 0));
+    s.start_strand(point(Vec3::Y * 3.0, 0.08, 0), Some(0));
+    s.push_node(point(Vec3::new(-1.0, 3.5, -0.5), 0.06, 2));
+    s.push_node(point(Vec3::new(-1.5, 4.0, -0.5), 0.06, 2));
+    s
+}
+
+fn builder() -> LSystemMeshBuilder {
+    LSystemMeshBuilder::new()
+        .with_resolution(6)
+        .with_caps(CapStyle::Dome)
+        .with_cards(2, CardStyle::Crossed { size: 3.0 })
+}
+
+fn lightmap(mesh: &Mesh) -> Vec<Vec2> {
+    float2(mesh, Mesh::ATTRIBUTE_UV_1)
+}
+
+/// Whether two triangles overlap by more than `eps` along every axis of the
+/// separating axis test.
+fn overlap(a: [Vec2; 3], b: [Vec2; 3], eps: f32) -> bool {
+    for tri in [a, b] {
+        for i in 0..3 {
+            let edge = tri[(i + 1) % 3] - tri[i];
+            let axis = edge.perp().normalize_or_zero();
+            if axis == Vec2::ZERO {
+                continue;
+            }
+            let span = |t: [Vec2; 3]| {
+                let d = t.map(|p| p.dot(axis));
+                (d[0].min(d[1]).min(d[2]), d[0].max(d[1]).max(d[2]))
+            };
+            let ((a_lo, a_hi), (b_lo, b_hi)) = (span(a), span(b));
+            if a_hi.min(b_hi) - a_lo.max(b_lo) <= eps {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Groups vertices into connected pieces by shared triangles.
+fn pieces(mesh: &Mesh) -> Vec<Vec<usize>> {
+    let count = mesh.count_vertices();
+    let mut parent: Vec<usize> = (0..count).collect();
+    fn root(parent: &mut [usize], mut v: usize) -> usize {
+        while parent[v] != v {
+            parent[v] = parent[parent[v]];
+            v = parent[v];
+        }
+        v
+    }
+    for [a, b, c] in triangles(mesh) {
+        for other in [b, c] {
+            let (ra, ro) = (root(&mut parent, a), root(&mut parent, other));
+            parent[ra] = ro;
+        }
+    }
+    let mut groups: std::collections::BTreeMap<usize, Vec<usize>> = Default::default();
+    for v in 0..count {
+        let r = root(&mut parent, v);
+        groups.entry(r).or_default().push(v);
+    }
+    groups.into_values().collect()
+}
+
+#[test]
+fn lightmap_uvs_are_opt_in_and_inside_the_unit_square() {
+    let plain = builder().build(&tree());
+    assert!(
+        plain
+            .values()
+            .all(|m| m.attribute(Mesh::ATTRIBUTE_UV_1).is_none())
+    );
+
+    let meshes = builder().with_lightmap_uvs(Some(0.01)).build(&tree());
+    assert_eq!(meshes.len(), plain.len());
+    for (id, mesh) in &meshes {
+        let uvs = lightmap(mesh);
+        assert_eq!(uvs.len(), mesh.count_vertices());
+        assert!(
+            uvs.iter()
+                .all(|uv| uv.min_element() >= 0.0 && uv.max_element() <= 1.0)
+        );
+        // Only chart boundaries are split; triangles stay as they were.
+        assert!(mesh.count_vertices() >= plain[id].count_vertices());
+        assert_eq!(
+            mesh.indices().unwrap().len(),
+            plain[id].indices().unwrap().len()
+        );
+    }
+}
+
+#[test]
+fn charts_do_not_overlap() {
+    for builder in [
+        builder(),
+        builder().with_smoothing(StrandSmoothing::CatmullRom { subdivisions: 3 }),
+        builder().with_caps(CapStyle::Flat),
+    ] {
+        let meshes = builder.with_lightmap_uvs(Some(0.0)).build(&tree());
+        for mesh in meshes.values() {
+            let uvs = lightmap(mesh);
+            let tris: Vec<[Vec2; 3]> = triangles(mesh)
+                .into_iter()
+                .map(|t| t.map(|v| uvs[v]))
+                .filter(|[a, b, c]| (*b - *a).perp_dot(*c - *a).abs() > 1e-9)
+                .collect();
+            for (i, a) in tris.iter().enumerate() {
+                for b in &tris[i + 1..] {
+                    assert!(!overlap(*a, *b, 1e-5), "{a:?} overlaps {b:?}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn padding_separates_charts_and_the_border() {
+    let padding = 0.02;
+    let meshes = builder().with_lightmap_uvs(Some(padding)).build(&tree());
+    let mesh = &meshes[&0];
+    let uvs = lightmap(mesh);
+    let boxes: Vec<(Vec2, Vec2)> = pieces(mesh)
+        .iter()
+        .map(|piece| {
+            piece.iter().fold((Vec2::MAX, Vec2::MIN), |(lo, hi), &v| {
+                (lo.min(uvs[v]), hi.max(uvs[v]))
+            })
+        })
+        .collect();
+    // Three tubes and five caps; the leafy branch's tube ends at its cards.
+    assert_eq!(boxes.len(), 8);
+    let eps = 1e-4;
+    for (i, (lo, hi)) in boxes.iter().enumerate() {
+        assert!(lo.min_element() >= padding - eps && hi.max_element() <= 1.0 - padding + eps);
+        for (other_lo, other_hi) in &boxes[i + 1..] {
+            let gap = (*other_lo - *hi).max(*lo - *other_hi);
+            assert!(gap.max_element() >= padding - eps, "gap {gap}");
+        }
+    }
+
+    // Out-of-range padding is clamped rather than breaking the layout.
+    let clamped = builder().with_lightmap_uvs(Some(f32::NAN)).build(&tree());
+    assert!(lightmap(&clamped[&0]).iter().all(|uv| uv.is_finite()));
+}
+
+#[test]
+fn texel_density_is_uniform_across_charts() {
+    let meshes = builder().with_lightmap_uvs(Some(0.0)).build(&tree());
+    let mesh = &meshes[&0];
+    let (uvs, positions) = (lightmap(mesh), positions(mesh));
+    let densities: Vec<f32> = pieces(mesh)
+        .iter()
+        .map(|piece| {
+            let (mut world, mut uv) = (0.0, 0.0);
+            for t in triangles(mesh)
+                .into_iter()
+                .filter(|t| piece.contains(&t[0]))
+            {
+                let [a, b, c] = t.map(|v| positions[v]);
+                world += (b - a).cross(c - a).length();
+                let [a, b, c] = t.map(|v| uvs[v]);
+                uv += (b - a).perp_dot(c - a).abs();
+            }
+            uv / world
+        })
+        .collect();
+    assert_eq!(densities.len(), 8, "tubes and caps");
+    let (lo, hi) = densities
+        .iter()
+        .fold((f32::MAX, 0.0f32), |(lo, hi), &d| (lo.min(d), hi.max(d)));
+    assert!(hi / lo < 1.1, "{densities:?}");
+
+    // Every build path lays out the same UVs.
+    let merged = builder()
+        .with_lightmap_uvs(Some(0.0))
+        .build_parallel(&tree());
+    assert_eq!(lightmap(&merged[&0]), uvs);
+}
@@ -0,0 +1,234 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use bevy::camera::visibility::VisibilityRange;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_symbios::lod::{LodHandles, simplify_skeleton, spawn_lod_children};
+use bevy_symbios::materials::MaterialPalette;
+use bevy_symbios::{CapStyle, CardStyle, LSystemMeshBuilder, LodChain, LodLevel, MeshCache};
+use common::point;
+use symbios_turtle_3d::Skeleton;
+
+/// A straight 5-point trunk (radius 0.2) with a thin 2-point twig (radius 0.01).
+fn tree() -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.2, 0), None);
+    for i in 1..5 {
+        s.push_node(point(Vec3::Y * i as f32, 0.2, 0));
+    }
+    s.start_strand(point(Vec3::Y * 2.0, 0.01, 0), Some(0));
+    s.push_node(point(Vec3::new(1.0, 3.0, 0.0), 0.01, 0));
+    s
+}
+
+fn chain() -> LodChain {
+    LodChain::new()
+        .with_level(
+            LodLevel::new(4, 100.0)
+                .with_min_radius(0.05)
+                .with_merge_collinear(0.01),
+        )
+        .with_level(LodLevel::new(12, 20.0))
+        .with_fade(5.0)
+}
+
+fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(bevy::asset::AssetPlugin::default())
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>();
+    app
+}
+
+#[test]
+fn levels_are_sorted_by_distance() {
+    let c = chain();
+    assert_eq!(c.levels()[0].resolution, 12);
+    assert_eq!(c.levels()[1].resolution, 4);
+}
+
+#[test]
+fn visibility_ranges_share_margins() {
+    let c = chain();
+    let near = c.visibility_range(0).unwrap();
+    let far = c.visibility_range(1).unwrap();
+    assert_eq!(near.start_margin, 0.0..0.0);
+    assert_eq!(near.end_margin, 20.0..25.0);
+    assert_eq!(far.start_margin, near.end_margin);
+    assert_eq!(far.end_margin, 100.0..105.0);
+}
+
+#[test]
+fn visibility_range_is_none_past_the_last_level() {
+    assert!(chain().visibility_range(2).is_none());
+    assert!(LodChain::new().visibility_range(0).is_none());
+}
+
+#[test]
+fn final_level_can_stay_visible() {
+    let unbounded = chain().with_final_level_unbounded();
+    assert_eq!(
+        unbounded.visibility_range(0).unwrap().end_margin,
+        20.0..25.0
+    );
+    let far = unbounded.visibility_range(1).unwrap();
+    assert_eq!(far.start_margin, 20.0..25.0);
+    assert_eq!(far.end_margin, f32::INFINITY..f32::INFINITY);
+
+    let infinite = LodChain::new()
+        .with_level(LodLevel::new(12, 20.0))
+        .with_level(LodLevel::new(4, f32::INFINITY))
+        .with_fade(5.0);
+    let far = infinite.visibility_range(1).unwrap();
+    assert_eq!(far.end_margin, f32::INFINITY..f32::INFINITY);
+}
+
+#[test]
+fn simplify_drops_thin_strands_and_merges_points() {
+    let level = LodLevel::new(4, 1.0)
+        .with_min_radius(0.05)
+        .with_merge_collinear(0.01);
+    let simplified = simplify_skeleton(&tree(), &level, &BTreeSet::new());
+    assert_eq!(simplified.strands.len(), 1, "twig should be dropped");
+    assert_eq!(simplified.strands[0].len(), 2, "straight trunk collapses");
+    assert_eq!(simplified.strand_parents, vec![None]);
+}
+
+#[test]
+fn build_lods_reduces_detail() {
+    let lods = LSystemMeshBuilder::new().build_lods(&tree(), &chain());
+    assert_eq!(lods.len(), 2);
+    let near: usize = lods[0].meshes.values().map(Mesh::count_vertices).sum();
+    let far: usize = lods[1].meshes.values().map(Mesh::count_vertices).sum();
+    // Near: trunk 5 rings + twig 2 rings at 13 verts each.
+    assert_eq!(near, 7 * 13);
+    // Far: trunk only, 2 rings at 5 verts each.
+    assert_eq!(far, 2 * 5);
+}
+
+#[test]
+fn kept_materials_survive_simplification() {
+    // Two single-point strands (material 2) on the trunk, and a material 2
+    // point in the middle of a straight run of the trunk itself.
+    let mut skeleton = tree();
+    skeleton.strands[0][2].material_id = 2;
+    for y in [3.0, 4.0] {
+        skeleton.start_strand(point(Vec3::new(0.5, y, 0.0), 0.2, 2), Some(0));
+    }
+
+    // By default a lone point meshes to nothing and is dropped.
+    let level = LodLevel::new(4, 1.0);
+    assert_eq!(
+        simplify_skeleton(&skeleton, &level, &BTreeSet::new())
+            .strands
+            .len(),
+        2
+    );
+    let kept = BTreeSet::from([2]);
+    let simplified = simplify_skeleton(&skeleton, &level.with_merge_collinear(0.01), &kept);
+    assert_eq!(simplified.strands.len(), 4);
+    assert_eq!(simplified.strand_parents[2..], [Some(0), Some(0)]);
+    // Base, kept point, the material boundary after it, and the tip.
+    assert_eq!(simplified.strands[0].len(), 4);
+    assert_eq!(simplified.strands[0][1].material_id, 2);
+}
+
+#[test]
+fn card_strands_survive_every_level() {
+    // Two single-point leaf strands (material 2) on the trunk, and a leaf
+    // point in the middle of a straight run of the trunk itself.
+    let mut skeleton = tree();
+    skeleton.strands[0][2].material_id = 2;
+    for y in [3.0, 4.0] {
+        skeleton.start_strand(point(Vec3::new(0.5, y, 0.0), 0.2, 2), Some(0));
+    }
+
+    let lods = LSystemMeshBuilder::new()
+        .with_cards(2, CardStyle::Quad { size: 1.0 })
+        .build_lods(&skeleton, &chain());
+    for lod in &lods {
+        assert_eq!(
+            lod.meshes[&2].count_vertices(),
+            3 * 4,
+            "level {}",
+            lod.level
+        );
+    }
+}
+
+#[test]
+fn build_lods_cached_keys_each_level() {
+    let mut app = test_app();
+    let skeleton = tree();
+    let mut cache = MeshCache::new();
+    let c = chain();
+
+    for _ in 0..2 {
+        let mut meshes = app.world_mut().resource_mut::<Assets<Mesh>>();
+        LSystemMeshBuilder::new().build_lods_cached(&skeleton, &c, &mut cache, &mut meshes);
+    }
+
+    assert_eq!(cache.len(), 2, "one entry per level");
+    assert_eq!(cache.misses(), 2);
+    assert_eq!(cache.hits(), 2);
+    for (index, level) in c.levels().iter().enumerate() {
+        assert!(cache.contains_lod(&skeleton, index, level));
+    }
+}
+
+#[test]
+fn is_lod_cached_matches_configured_builders() {
+    let mut app = test_app();
+    let skeleton = tree();
+    let builder = LSystemMeshBuilder::new().with_caps(CapStyle::Flat);
+    let mut cache = MeshCache::new();
+    let c = chain();
+
+    {
+        let mut meshes = app.world_mut().resource_mut::<Assets<Mesh>>();
+        builder
+            .clone()
+            .build_lods_cached(&skeleton, &c, &mut cache, &mut meshes);
+    }
+
+    for (index, level) in c.levels().iter().enumerate() {
+        assert!(builder.is_lod_cached(&cache, &skeleton, index, level));
+        assert!(!cache.contains_lod(&skeleton, index, level));
+    }
+    assert_eq!(cache.hits(), 0);
+}
+
+#[test]
+fn spawn_lod_children_attaches_visibility_ranges() {
+    let mut app = test_app();
+    let lods: Vec<LodHandles> = {
+        let mut meshes = app.world_mut().resource_mut::<Assets<Mesh>>();
+        LSystemMeshBuilder::new()
+            .build_lods(&tree(), &chain())
+            .into_iter()
+            .map(|lod| lod.into_handles(&mut meshes))
+            .collect()
+    };
+    let palette = MaterialPalette {
+        materials: HashMap::new(),
+        primary_material: Handle::default(),
+    };
+
+    let parent = app.world_mut().spawn(Transform::default()).id();
+    let spawned = {
+        let mut commands = app.world_mut().commands();
+        spawn_lod_children(&mut commands, parent, &lods, &palette)
+    };
+    app.world_mut().flush();
+
+    assert_eq!(spawned.len(), 2);
+    let children = app.world().get::<Children>(parent).expect("children");
+    assert_eq!(children.len(), 2);
+    for (entity, lod) in spawned.iter().zip(&lods) {
+        let range = app.world().get::<VisibilityRange>(*entity).unwrap();
+        assert!(*range == lod.visibility_range);
+    }
+}
@@ -0,0 +1,211 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::diagnostics::ClampedOption;
+use bevy_symbios::{CardStyle, LSystemMeshBuilder, MAX_U_REPEAT, MaterialUv};
+use common::{point, triangles, uvs};
+use symbios_turtle_3d::Skeleton;
+
+/// A three-segment column of material 0 ending in a leaf (material 2).
+fn column() -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.25, 0), None);
+    s.push_node(point(Vec3::Y, 0.25, 0));
+    s.push_node(point(Vec3::Y * 2.0, 0.25, 0));
+    s.push_node(point(Vec3::Y * 2.5, 0.25, 2));
+    s
+}
+
+fn max_v(mesh: &Mesh) -> f32 {
+    uvs(mesh).iter().map(|uv| uv.y).fold(0.0, f32::max)
+}
+
+#[test]
+fn u_repeat_tiles_around_the_tube_and_keeps_texels_square() {
+    let plain = LSystemMeshBuilder::new()
+        .with_resolution(8)
+        .build(&column());
+    let tiled = LSystemMeshBuilder::new()
+        .with_resolution(8)
+        .with_material_uv(0, MaterialUv::new().with_u_repeat(3))
+        .build(&column());
+
+    let (plain, tiled) = (&plain[&0], &tiled[&0]);
+    assert_eq!(tiled.count_vertices(), plain.count_vertices());
+    let max_u = uvs(tiled).iter().map(|uv| uv.x).fold(0.0, f32::max);
+    assert!((max_u - 3.0).abs() < 1e-5);
+    assert!((max_v(tiled) - 3.0 * max_v(plain)).abs() < 1e-4);
+}
+
+#[test]
+fn u_repeat_is_clamped_and_reported() {
+    let clamped = |repeats| {
+        let (meshes, report) = LSystemMeshBuilder::new()
+            .with_resolution(8)
+            .with_material_uv(0, MaterialUv::new().with_u_repeat(repeats))
+            .try_build(&column())
+            .unwrap();
+        let max_u = uvs(&meshes[&0]).iter().map(|uv| uv.x).fold(0.0, f32::max);
+        (max_u, report.clamped_options)
+    };
+
+    // Zero repeats are clamped to one, and huge counts to the maximum.
+    let (max_u, clamps) = clamped(0);
+    assert!((max_u - 1.0).abs() < 1e-5);
+    assert_eq!(
+        clamps,
+        vec![ClampedOption {
+            method: "with_material_uv",
+            parameter: "u_repeat",
+            material_id: Some(0),
+            requested: 0.0,
+            effective: 1.0,
+        }]
+    );
+    let (max_u, clamps) = clamped(u32::MAX);
+    assert!((max_u - MAX_U_REPEAT as f32).abs() < 1e-3);
+    assert_eq!(clamps[0].requested, f64::from(u32::MAX));
+    assert_eq!(clamps[0].effective, f64::from(MAX_U_REPEAT));
+}
+
+#[test]
+fn struct_literals_are_sanitized_like_the_builders() {
+    let build = |uv: MaterialUv| {
+        let (meshes, report) = LSystemMeshBuilder::new()
+            .with_material_uv(0, uv)
+            .try_build(&column())
+            .unwrap();
+        (uvs(&meshes[&0]), report.clamped_options)
+    };
+    let plain = uvs(&LSystemMeshBuilder::new().build(&column())[&0]);
+
+    let (_, clamps) = build(MaterialUv {
+        u_repeat: 0,
+        atlas: None,
+    });
+    assert_eq!(clamps.len(), 1);
+
+    // Non-finite rectangles are dropped rather than turning UVs into NaN.
+    for bad in [
+        Rect {
+            min: Vec2::new(f32::NAN, 0.0),
+            max: Vec2::ONE,
+        },
+        Rect {
+            min: Vec2::ZERO,
+            max: Vec2::new(f32::INFINITY, 1.0),
+        },
+    ] {
+        let (uvs, _) = build(MaterialUv {
+            u_repeat: 1,
+            atlas: Some(bad),
+        });
+        assert_eq!(uvs, plain);
+    }
+
+    // Inverted corners map like the normalized rectangle, without mirroring.
+    let bark = Rect::new(0.5, 0.0, 0.75, 1.0);
+    let inverted = Rect {
+        min: bark.max,
+        max: bark.min,
+    };
+    let (normalized, _) = build(MaterialUv::new().with_atlas_rect(bark));
+    let (swapped, _) = build(MaterialUv {
+        u_repeat: 1,
+        atlas: Some(inverted),
+    });
+    assert_eq!(swapped, normalized);
+}
+
+#[test]
+fn atlas_rect_remaps_tubes_and_cards() {
+    let bark = Rect::new(0.5, 0.0, 0.75, 1.0);
+    let leaf = Rect::new(0.0, 0.5, 0.25, 0.75);
+    let build = |builder: LSystemMeshBuilder| {
+        builder
+            .with_cards(2, CardStyle::Quad { size: 2.0 })
+            .build(&column())
+    };
+    let plain = build(LSystemMeshBuilder::new());
+    let mapped = build(
+        LSystemMeshBuilder::new()
+            .with_material_uv(0, MaterialUv::new().with_atlas_rect(bark))
+            .with_material_uv(2, MaterialUv::new().with_atlas_rect(leaf)),
+    );
+
+    for (before, after) in uvs(&plain[&0]).iter().zip(uvs(&mapped[&0])) {
+        assert!((after.x - (0.5 + before.x * 0.25)).abs() < 1e-6);
+        assert_eq!(after.y, before.y, "a full-height column keeps V");
+    }
+    let card = uvs(&mapped[&2]);
+    assert_eq!(card.len(), 4);
+    assert!(card.iter().all(|uv| leaf.contains(*uv)));
+    assert!(card.contains(&leaf.min) && card.contains(&leaf.max));
+}
+
+#[test]
+fn atlas_repeats_split_rings_at_repeat_boundaries() {
+    let bark = Rect::new(0.25, 0.0, 0.5, 1.0);
+    let build = |resolution, repeats| {
+        LSystemMeshBuilder::new()
+            .with_resolution(resolution)
+            .with_material_uv(
+                0,
+                MaterialUv::new()
+                    .with_u_repeat(repeats)
+                    .with_atlas_rect(bark),
+            )
+            .build(&column())
+            .remove(&0)
+            .unwrap()
+    };
+
+    // Boundaries between ring vertices add a vertex on each side, boundaries
+    // on a ring vertex duplicate it. Four rings: the segment into the leaf
+    // is still bark.
+    assert_eq!(build(8, 3).count_vertices(), 4 * (9 + 2 * 2));
+    assert_eq!(build(6, 3).count_vertices(), 4 * (7 + 2));
+
+    for mesh in [build(8, 3), build(6, 3)] {
+        let uvs = uvs(&mesh);
+        assert!(
+            uvs.iter()
+                .all(|uv| uv.x >= bark.min.x - 1e-6 && uv.x <= bark.max.x + 1e-6)
+        );
+        // Every repeat runs across the whole rectangle and no triangle
+        // wraps around it: a face covers at most half a repeat here.
+        let span = |t: &[usize; 3]| {
+            let u = t.map(|v| uvs[v].x);
+            u.iter().copied().fold(f32::MIN, f32::max) - u.iter().copied().fold(f32::MAX, f32::min)
+        };
+        assert!(
+            triangles(&mesh)
+                .iter()
+                .all(|t| span(t) <= bark.width() * 0.5 + 1e-6)
+        );
+        assert!(uvs.iter().any(|uv| (uv.x - bark.min.x).abs() < 1e-6));
+        assert!(uvs.iter().any(|uv| (uv.x - bark.max.x).abs() < 1e-6));
+    }
+}
+
+#[test]
+fn merged_builds_map_each_material_into_its_rect() {
+    let bark = Rect::new(0.0, 0.0, 0.5, 1.0);
+    let leaf = Rect::new(0.5, 0.0, 1.0, 0.5);
+    let builder = || {
+        LSystemMeshBuilder::new()
+            .with_cards(2, CardStyle::Crossed { size: 2.0 })
+            .with_material_uv(0, MaterialUv::new().with_atlas_rect(bark))
+            .with_material_uv(2, MaterialUv::new().with_atlas_rect(leaf))
+    };
+    let separate = builder().build(&column());
+    let merged = builder().build_merged(&column());
+
+    // Buckets are concatenated in ascending material order.
+    let expected: Vec<Vec2> = uvs(&separate[&0])
+        .into_iter()
+        .chain(uvs(&separate[&2]))
+        .collect();
+    assert_eq!(uvs(&merged), expected);
+    assert!(uvs(&separate[&2]).iter().all(|uv| leaf.contains(*uv)));
+}
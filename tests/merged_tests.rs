@@ -0,0 +1,140 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::storage::ShaderStorageBuffer;
+use bevy_symbios::materials::{MaterialSettings, MaterialSettingsMap};
+use bevy_symbios::merged::*;
+use bevy_symbios::{ATTRIBUTE_MATERIAL_INDEX, LSystemMeshBuilder};
+use common::{point, uints};
+use symbios_turtle_3d::Skeleton;
+
+/// Trunk in material 3 with a branch whose tip switches to material 1.
+fn tree() -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.1, 3), None);
+    s.push_node(point(Vec3::Y, 0.1, 3));
+    s.start_strand(point(Vec3::Y, 0.1, 3), Some(0));
+    s.push_node(point(Vec3::new(1.0, 1.0, 0.0), 0.1, 3));
+    s.push_node(point(Vec3::new(2.0, 1.0, 0.0), 0.1, 1));
+    s
+}
+
+fn material_indices(mesh: &Mesh) -> Vec<u32> {
+    uints(mesh, ATTRIBUTE_MATERIAL_INDEX)
+}
+
+#[test]
+fn merged_mesh_concatenates_buckets_in_id_order() {
+    let buckets = LSystemMeshBuilder::new().build(&tree());
+    let merged = LSystemMeshBuilder::new().build_merged(&tree());
+
+    let mut ids: Vec<u16> = buckets.keys().copied().collect();
+    ids.sort();
+    let expected: Vec<u32> = ids
+        .iter()
+        .flat_map(|id| std::iter::repeat_n(u32::from(*id), buckets[id].count_vertices()))
+        .collect();
+    assert_eq!(material_indices(&merged), expected);
+    assert_eq!(
+        merged.indices().unwrap().len(),
+        buckets
+            .values()
+            .map(|m| m.indices().unwrap().len())
+            .sum::<usize>()
+    );
+    assert!(
+        buckets[&3].attribute(ATTRIBUTE_MATERIAL_INDEX).is_none(),
+        "per-bucket builds stay unchanged"
+    );
+}
+
+#[test]
+fn empty_skeleton_merges_to_an_empty_mesh() {
+    let merged = LSystemMeshBuilder::new().build_merged(&Skeleton::new());
+    assert_eq!(merged.count_vertices(), 0);
+}
+
+#[test]
+fn material_index_location_is_within_wgpu_limits() {
+    // wgpu's default `max_vertex_attributes` is 16, and Bevy's standard mesh
+    // attributes take locations 0 to 7.
+    const { assert!(MATERIAL_INDEX_SHADER_LOCATION > 7 && MATERIAL_INDEX_SHADER_LOCATION < 16) };
+
+    let shader = include_str!("../src/shaders/palette.wgsl");
+    let declaration = format!("@location({MATERIAL_INDEX_SHADER_LOCATION}) material_index: u32");
+    assert!(shader.contains(&declaration), "vertex input out of sync");
+}
+
+#[test]
+fn palette_slots_are_dense_and_linear() {
+    let mut settings = MaterialSettingsMap {
+        settings: Default::default(),
+    };
+    settings.settings.insert(
+        2,
+        MaterialSettings {
+            base_color: [1.0, 0.5, 0.0],
+            emission_color: [1.0, 1.0, 1.0],
+            emission_strength: 3.0,
+            roughness: 0.9,
+            metallic: 0.25,
+            ..default()
+        },
+    );
+    let slots = palette_slots(&settings);
+    assert_eq!(slots.len(), 3);
+    assert_eq!(slots[0], PaletteSlot::default());
+
+    let slot = slots[2];
+    let linear = Color::srgb(1.0, 0.5, 0.0).to_linear();
+    assert!(slot.base_color.abs_diff_eq(linear.to_vec4(), 1e-6));
+    assert!(slot.emissive.truncate().abs_diff_eq(Vec3::splat(3.0), 1e-5));
+    assert_eq!(slot.perceptual_roughness, 0.9);
+    assert_eq!(slot.metallic, 0.25);
+
+    settings.settings.clear();
+    assert_eq!(palette_slots(&settings).len(), 1);
+}
+
+fn buffer_data(app: &App) -> Vec<u8> {
+    let handle = app.world().resource::<MergedPalette>().material.clone();
+    let material = app
+        .world()
+        .resource::<Assets<PaletteMaterial>>()
+        .get(&handle)
+        .expect("material created");
+    app.world()
+        .resource::<Assets<ShaderStorageBuffer>>()
+        .get(&material.extension.slots)
+        .unwrap()
+        .data
+        .clone()
+        .unwrap()
+}
+
+#[test]
+fn sync_rewrites_slots_when_settings_change() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(bevy::asset::AssetPlugin::default())
+        .init_asset::<Image>()
+        .init_asset::<ShaderStorageBuffer>()
+        .init_asset::<PaletteMaterial>()
+        .init_resource::<MaterialSettingsMap>()
+        .init_resource::<MergedPalette>()
+        .add_systems(Update, sync_palette_material);
+    app.update();
+    let before = buffer_data(&app);
+    let expected = palette_slots(app.world().resource::<MaterialSettingsMap>());
+    assert_eq!(before, ShaderStorageBuffer::from(expected).data.unwrap());
+
+    app.world_mut()
+        .resource_mut::<MaterialSettingsMap>()
+        .settings
+        .get_mut(&0)
+        .unwrap()
+        .metallic = 0.1;
+    app.update();
+    assert_ne!(buffer_data(&app), before);
+    assert_eq!(app.world().resource::<Assets<PaletteMaterial>>().len(), 1);
+}
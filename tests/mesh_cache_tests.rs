@@ -1,6 +1,6 @@
 use bevy::platform::collections::HashMap;
 use bevy::prelude::*;
-use bevy_symbios::{LSystemMeshBuilder, MeshCache, compute_skeleton_fingerprint};
+use bevy_symbios::{CapStyle, LSystemMeshBuilder, MeshCache, compute_skeleton_fingerprint};
 use symbios_turtle_3d::{Skeleton, SkeletonPoint};
 
 fn make_skeleton(positions: &[Vec3]) -> Skeleton {
@@ -125,6 +125,26 @@ fn cache_miss_on_resolution_change() {
     );
 }
 
+#[test]
+fn is_cached_matches_configured_builders() {
+    let mut app = test_app();
+    let skel = make_skeleton(&[Vec3::ZERO, Vec3::Y]);
+    let builder = LSystemMeshBuilder::new().with_caps(CapStyle::Flat);
+    let mut cache = MeshCache::new();
+
+    assert!(!builder.is_cached(&cache, &skel));
+    {
+        let mut meshes = app.world_mut().resource_mut::<Assets<Mesh>>();
+        builder.clone().build_cached(&skel, &mut cache, &mut meshes);
+    }
+
+    assert!(builder.is_cached(&cache, &skel));
+    assert!(!LSystemMeshBuilder::new().is_cached(&cache, &skel));
+    // `contains` only knows about default builders.
+    assert!(!cache.contains(&skel, 8));
+    assert_eq!(cache.hits() + cache.misses(), 1, "probes don't count");
+}
+
 #[test]
 fn clear_drops_all_entries() {
     let mut app = test_app();
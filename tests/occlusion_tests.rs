@@ -0,0 +1,115 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::LSystemMeshBuilder;
+use bevy_symbios::occlusion::*;
+use common::{colors, floats, point, positions};
+use symbios_turtle_3d::{Skeleton, SkeletonPoint};
+
+/// A tinted point, so the bake can be seen to scale every channel alike.
+fn tinted(position: Vec3, radius: f32) -> SkeletonPoint {
+    SkeletonPoint {
+        color: Vec4::new(0.5, 0.8, 1.0, 1.0),
+        ..point(position, radius, 0)
+    }
+}
+
+/// Vertical strands of three unit segments at each x offset.
+fn columns(xs: &[f32]) -> Skeleton {
+    let mut s = Skeleton::new();
+    for &x in xs {
+        s.start_strand(tinted(Vec3::new(x, 0.0, 0.0), 0.2), None);
+        for i in 1..=3 {
+            s.push_node(tinted(Vec3::new(x, i as f32, 0.0), 0.2));
+        }
+    }
+    s
+}
+
+#[test]
+fn lone_strand_is_unoccluded() {
+    let skeleton = columns(&[0.0]);
+    let mut meshes = LSystemMeshBuilder::new().build(&skeleton);
+    let before = colors(&meshes[&0]);
+    bake_ambient_occlusion(&mut meshes, &skeleton, &AmbientOcclusion::new(2.0));
+    assert_eq!(colors(&meshes[&0]), before);
+}
+
+#[test]
+fn facing_sides_of_neighbouring_strands_darken() {
+    let skeleton = columns(&[0.0, 0.6]);
+    let mut meshes = LSystemMeshBuilder::new()
+        .with_resolution(16)
+        .build(&skeleton);
+    bake_ambient_occlusion(&mut meshes, &skeleton, &AmbientOcclusion::new(2.0));
+    let mesh = &meshes[&0];
+
+    let (mut inner, mut outer) = (Vec::new(), Vec::new());
+    for (p, c) in positions(mesh).iter().zip(colors(mesh)) {
+        // Alpha is never touched and green keeps its ratio to red.
+        assert_eq!(c.w, 1.0);
+        assert!((c.y / c.x - 1.6).abs() < 1e-4);
+        let ao = c.x / 0.5;
+        if p.x < 0.3 {
+            if p.x > 0.19 {
+                inner.push(ao);
+            } else if p.x < -0.19 {
+                outer.push(ao);
+            }
+        }
+    }
+    let mean = |v: &[f32]| v.iter().sum::<f32>() / v.len() as f32;
+    assert!(!inner.is_empty() && !outer.is_empty());
+    assert!(outer.iter().all(|&ao| ao > 0.99));
+    assert!(mean(&inner) < 0.8, "{}", mean(&inner));
+}
+
+#[test]
+fn attribute_target_leaves_colors_alone() {
+    let skeleton = columns(&[0.0, 0.6]);
+    let mut meshes = LSystemMeshBuilder::new().build(&skeleton);
+    let before = colors(&meshes[&0]);
+    let settings = AmbientOcclusion::new(2.0).with_target(OcclusionTarget::Attribute);
+    bake_ambient_occlusion(&mut meshes, &skeleton, &settings);
+    let mesh = &meshes[&0];
+
+    assert_eq!(colors(mesh), before);
+    let ao = floats(mesh, ATTRIBUTE_AMBIENT_OCCLUSION);
+    assert_eq!(ao.len(), mesh.count_vertices());
+    assert!(ao.iter().all(|v| (0.0..=1.0).contains(v)));
+    assert!(ao.iter().any(|&v| v < 0.9));
+}
+
+#[test]
+fn bakes_are_deterministic_and_respect_strength() {
+    let skeleton = columns(&[0.0, 0.5, 1.0]);
+    let volume = OcclusionVolume::from_skeleton(&skeleton);
+    assert_eq!(volume.len(), 9);
+
+    let bake = |settings: AmbientOcclusion| {
+        let mut mesh = LSystemMeshBuilder::new()
+            .build(&skeleton)
+            .remove(&0)
+            .unwrap();
+        volume.bake(&mut mesh, &settings);
+        colors(&mesh)
+    };
+    let settings = AmbientOcclusion::new(1.5).with_samples(64);
+    assert_eq!(bake(settings), bake(settings));
+
+    let plain = colors(&LSystemMeshBuilder::new().build(&skeleton)[&0]);
+    assert_eq!(bake(settings.with_strength(0.0)), plain);
+}
+
+#[test]
+fn sample_counts_are_clamped() {
+    assert_eq!(AmbientOcclusion::new(1.0).with_samples(0).samples, 1);
+    assert_eq!(AmbientOcclusion::new(1.0).with_samples(10_000).samples, 256);
+    // No occluders means nothing to darken.
+    let volume = OcclusionVolume::from_skeleton(&Skeleton::new());
+    assert!(volume.is_empty());
+    assert_eq!(
+        volume.sample(Vec3::ZERO, Vec3::Y, &AmbientOcclusion::default()),
+        1.0
+    );
+}
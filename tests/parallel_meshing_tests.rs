@@ -0,0 +1,92 @@
+mod common;
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_symbios::LSystemMeshBuilder;
+use bevy_symbios::materials::MaterialPalette;
+use bevy_symbios::mesh_tasks::{LSystemMeshPart, LSystemMeshTask, apply_lsystem_mesh_tasks};
+use common::{point, positions};
+use symbios_turtle_3d::Skeleton;
+
+/// A fan of 64 bent strands alternating between two materials.
+fn bushy_skeleton() -> Skeleton {
+    let mut s = Skeleton::new();
+    for i in 0..64 {
+        let angle = i as f32 * 0.1;
+        let dir = Vec3::new(angle.cos(), 1.0, angle.sin()).normalize();
+        for j in 0..4 {
+            let point = point(
+                dir * j as f32 + Vec3::X * (j * j) as f32 * 0.1,
+                0.1 / (j + 1) as f32,
+                (i % 2) as u8,
+            );
+            if j == 0 {
+                s.start_strand(point, None);
+            } else {
+                s.push_node(point);
+            }
+        }
+    }
+    s
+}
+
+#[test]
+fn parallel_build_matches_serial_build() {
+    let skeleton = bushy_skeleton();
+    let serial = LSystemMeshBuilder::new().build(&skeleton);
+    let parallel = LSystemMeshBuilder::new().build_parallel(&skeleton);
+
+    assert_eq!(serial.len(), parallel.len());
+    for (id, mesh) in &serial {
+        let other = &parallel[id];
+        assert_eq!(positions(mesh), positions(other));
+        let a: Vec<usize> = mesh.indices().unwrap().iter().collect();
+        let b: Vec<usize> = other.indices().unwrap().iter().collect();
+        assert_eq!(a, b);
+    }
+}
+
+#[test]
+fn parallel_build_of_empty_skeleton_is_empty() {
+    assert!(
+        LSystemMeshBuilder::new()
+            .build_parallel(&Skeleton::new())
+            .is_empty()
+    );
+}
+
+#[test]
+fn async_task_spawns_children_when_done() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(bevy::asset::AssetPlugin::default())
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .insert_resource(MaterialPalette {
+            materials: HashMap::new(),
+            primary_material: Handle::default(),
+        })
+        .add_systems(Update, apply_lsystem_mesh_tasks);
+
+    let task = LSystemMeshBuilder::new().build_async(bushy_skeleton());
+    let tree = app.world_mut().spawn(LSystemMeshTask(task)).id();
+
+    for _ in 0..1000 {
+        app.update();
+        if app.world().get::<LSystemMeshTask>(tree).is_none() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+
+    assert!(
+        app.world().get::<LSystemMeshTask>(tree).is_none(),
+        "task component should be removed once finished"
+    );
+    let children = app.world().get::<Children>(tree).expect("children spawned");
+    assert_eq!(children.len(), 2, "one child per material bucket");
+    for child in children.iter() {
+        assert!(app.world().get::<Mesh3d>(child).is_some());
+        assert!(app.world().get::<LSystemMeshPart>(child).is_some());
+    }
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use bevy::camera::NormalizedRenderTarget;
+use bevy::camera::primitives::MeshAabb;
+use bevy::picking::backend::HitData;
+use bevy::picking::pointer::{Location, PointerButton, PointerId};
+use bevy::prelude::*;
+use bevy_symbios::picking::*;
+use bevy_symbios::{CapStyle, CardStyle, LSystemMeshBuilder, StrandSmoothing};
+use common::point;
+use symbios_turtle_3d::Skeleton;
+
+/// A vertical trunk of three unit segments and a branch along +X whose second
+/// segment switches to material 1.
+fn tree() -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.2, 0), None);
+    for i in 1..=3 {
+        s.push_node(point(Vec3::Y * i as f32, 0.2, 0));
+    }
+    s.start_strand(point(Vec3::Y * 2.0, 0.2, 0), Some(0));
+    s.push_node(point(Vec3::new(1.0, 2.0, 0.0), 0.2, 1));
+    s.push_node(point(Vec3::new(2.0, 2.0, 0.0), 0.2, 1));
+    s
+}
+
+fn location(strand: u32, point: u32) -> SkeletonLocation {
+    SkeletonLocation { strand, point }
+}
+
+fn triangle_count(mesh: &Mesh) -> usize {
+    mesh.indices().unwrap().len() / 3
+}
+
+#[test]
+fn maps_cover_every_triangle_of_the_plain_build() {
+    let plain = LSystemMeshBuilder::new().build(&tree());
+    let mapped = LSystemMeshBuilder::new()
+        .with_caps(CapStyle::Dome)
+        .build_mapped(&tree());
+    assert_eq!(mapped.len(), plain.len());
+
+    for (mesh, map) in mapped.values() {
+        let triangles = triangle_count(mesh);
+        assert!((0..triangles).all(|t| map.locate(t).is_some()));
+        assert_eq!(map.locate(triangles), None);
+        let covered: u32 = map.ranges().map(|(range, _)| range.len() as u32).sum();
+        assert_eq!(covered as usize, triangles);
+    }
+    assert_eq!(
+        mapped[&0].0.count_vertices(),
+        LSystemMeshBuilder::new()
+            .with_caps(CapStyle::Dome)
+            .build(&tree())[&0]
+            .count_vertices()
+    );
+}
+
+#[test]
+fn segments_map_to_their_start_point() {
+    // `push_node` skips repeats, so insert the duplicate directly.
+    let mut skeleton = tree();
+    let base = skeleton.strands[0][0];
+    skeleton.strands[0].insert(1, base);
+    let mapped = LSystemMeshBuilder::new()
+        .with_resolution(4)
+        .build_mapped(&skeleton);
+    let map = &mapped[&0].1;
+
+    let ranges: Vec<_> = map.ranges().collect();
+    // Three trunk segments after the dropped duplicate, then the branch.
+    assert_eq!(
+        ranges.iter().map(|(_, l)| *l).collect::<Vec<_>>(),
+        vec![
+            location(0, 0),
+            location(0, 2),
+            location(0, 3),
+            location(1, 0)
+        ]
+    );
+    assert!(ranges[..3].iter().all(|(range, _)| range.len() == 8));
+    // The branch's second segment went to material 1.
+    assert_eq!(
+        mapped[&1].1.ranges().map(|(_, l)| l).collect::<Vec<_>>(),
+        vec![location(1, 1)]
+    );
+}
+
+#[test]
+fn smoothed_strands_and_caps_map_to_original_points() {
+    let mapped = LSystemMeshBuilder::new()
+        .with_smoothing(StrandSmoothing::CatmullRom { subdivisions: 3 })
+        .with_caps(CapStyle::Flat)
+        .build_mapped(&tree());
+    let trunk: Vec<_> = mapped[&0]
+        .1
+        .ranges()
+        .map(|(_, l)| l)
+        .filter(|l| l.strand == 0)
+        .collect();
+    assert_eq!(
+        trunk.iter().map(|l| l.point).collect::<Vec<_>>(),
+        vec![0, 1, 2, 0, 3],
+        "three segments, then the start and end caps"
+    );
+
+    let bezier = LSystemMeshBuilder::new()
+        .with_smoothing(StrandSmoothing::Bezier { subdivisions: 4 })
+        .build_mapped(&tree());
+    let points: Vec<u32> = bezier[&0]
+        .1
+        .ranges()
+        .filter(|(_, l)| l.strand == 0)
+        .map(|(_, l)| l.point)
+        .collect();
+    assert_eq!(points, vec![0, 1, 2]);
+}
+
+#[test]
+fn cards_map_to_their_own_point() {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.2, 0), None);
+    s.push_node(point(Vec3::Y, 0.2, 2));
+    s.push_node(point(Vec3::Y * 2.0, 0.2, 2));
+    let mapped = LSystemMeshBuilder::new()
+        .with_cards(2, CardStyle::Crossed { size: 1.0 })
+        .build_mapped(&s);
+    let cards: Vec<_> = mapped[&2].1.ranges().collect();
+    assert_eq!(cards.len(), 2);
+    assert_eq!(cards[0], (0..4, location(0, 1)));
+    assert_eq!(cards[1], (4..8, location(0, 2)));
+}
+
+#[derive(Resource, Default)]
+struct Picked(Vec<SkeletonPicked>);
+
+#[test]
+fn clicks_resolve_to_skeleton_locations() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(bevy::asset::AssetPlugin::default())
+        .init_asset::<Mesh>()
+        .init_resource::<Picked>()
+        .add_plugins(SkeletonPickingPlugin)
+        .add_observer(|picked: On<SkeletonPicked>, mut log: ResMut<Picked>| {
+            log.0.push(*picked);
+        });
+
+    let (mesh, map) = LSystemMeshBuilder::new()
+        .build_mapped(&tree())
+        .remove(&0)
+        .unwrap();
+    let aabb = mesh.compute_aabb().unwrap();
+    let handle = app.world_mut().resource_mut::<Assets<Mesh>>().add(mesh);
+    let offset = Vec3::new(10.0, 0.0, 0.0);
+    let tree = app
+        .world_mut()
+        .spawn((
+            Mesh3d(handle),
+            map,
+            aabb,
+            Visibility::default(),
+            Transform::from_translation(offset),
+            GlobalTransform::from_translation(offset),
+        ))
+        .id();
+    let other = app.world_mut().spawn(Transform::default()).id();
+
+    let click = |entity, position: Vec3, normal: Vec3| {
+        Pointer::new(
+            PointerId::Mouse,
+            Location {
+                target: NormalizedRenderTarget::None {
+                    width: 1,
+                    height: 1,
+                },
+                position: Vec2::ZERO,
+            },
+            Click {
+                button: PointerButton::Primary,
+                hit: HitData::new(Entity::PLACEHOLDER, 1.0, Some(position), Some(normal)),
+                duration: Default::default(),
+            },
+            entity,
+        )
+    };
+    // Mid-height of the trunk's second segment, through the middle of a face.
+    let normal = Quat::from_rotation_y(std::f32::consts::FRAC_PI_8) * Vec3::X;
+    let hit = offset + Vec3::Y * 1.5 + normal * 0.18;
+    app.world_mut().trigger(click(tree, hit, normal));
+    // Entities without a map are ignored.
+    app.world_mut().trigger(click(other, hit, normal));
+    app.update();
+
+    let log = &app.world().resource::<Picked>().0;
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].entity, tree);
+    assert_eq!(log[0].location, location(0, 1));
+    assert_eq!(log[0].button, PointerButton::Primary);
+}
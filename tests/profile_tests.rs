@@ -0,0 +1,190 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::{CapStyle, CrossSection, LSystemMeshBuilder};
+use common::{assert_watertight, float3, polyline, uvs};
+
+#[test]
+fn polygon_has_hard_edges() {
+    let s = polyline(&[Vec3::ZERO, Vec3::Y], &[0, 0]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_cross_section(CrossSection::Polygon { sides: 4 })
+        .build(&s);
+    let mesh = &meshes[&0];
+    // Two rings of four faces, each face with its own pair of vertices.
+    assert_eq!(mesh.count_vertices(), 16);
+
+    let positions = float3(mesh, Mesh::ATTRIBUTE_POSITION);
+    let normals = float3(mesh, Mesh::ATTRIBUTE_NORMAL);
+    for (p, n) in positions.iter().zip(&normals) {
+        // Face normals of a square with corners on the axes are diagonal.
+        assert!(
+            (n.x.abs() - n.z.abs()).abs() < 1e-4,
+            "normal {n} is not a face normal"
+        );
+        assert!(n.y.abs() < 1e-4);
+        assert!(
+            n.dot(Vec3::new(p.x, 0.0, p.z)) > 0.0,
+            "normal {n} points inward"
+        );
+    }
+    // Every corner position carries two different normals.
+    let corner = positions[1];
+    let split: Vec<_> = positions
+        .iter()
+        .zip(&normals)
+        .filter(|(p, _)| p.distance(corner) < 1e-5)
+        .map(|(_, n)| *n)
+        .collect();
+    assert_eq!(split.len(), 2);
+    assert!(split[0].dot(split[1]) < 0.5);
+}
+
+#[test]
+fn u_follows_perimeter_length() {
+    let s = polyline(&[Vec3::ZERO, Vec3::Y], &[0, 0]);
+    // Rectangle 4 wide, 1 tall: the long sides take 40% of U each.
+    let rectangle = vec![
+        Vec2::new(-2.0, -0.5),
+        Vec2::new(2.0, -0.5),
+        Vec2::new(2.0, 0.5),
+        Vec2::new(-2.0, 0.5),
+    ];
+    let meshes = LSystemMeshBuilder::new()
+        .with_cross_section(CrossSection::Custom {
+            points: rectangle,
+            crease_angle: 0.1,
+        })
+        .build(&s);
+    let mesh = &meshes[&0];
+    let positions = float3(mesh, Mesh::ATTRIBUTE_POSITION);
+    let uvs = uvs(mesh);
+    let ring: Vec<_> = positions.iter().zip(&uvs).take(8).collect();
+
+    let expected = [0.0, 0.4, 0.4, 0.5, 0.5, 0.9, 0.9, 1.0];
+    for ((_, uv), expected) in ring.iter().zip(expected) {
+        assert!((uv.x - expected).abs() < 1e-5, "u {} != {expected}", uv.x);
+    }
+}
+
+#[test]
+fn reversed_custom_outline_faces_outward() {
+    let s = polyline(&[Vec3::ZERO, Vec3::Y], &[0, 0]);
+    let clockwise = vec![
+        Vec2::new(1.0, 0.0),
+        Vec2::new(0.0, -1.0),
+        Vec2::new(-1.0, 0.0),
+        Vec2::new(0.0, 1.0),
+    ];
+    let meshes = LSystemMeshBuilder::new()
+        .with_cross_section(CrossSection::Custom {
+            points: clockwise,
+            crease_angle: 0.0,
+        })
+        .build(&s);
+    let mesh = &meshes[&0];
+    let positions = float3(mesh, Mesh::ATTRIBUTE_POSITION);
+    let normals = float3(mesh, Mesh::ATTRIBUTE_NORMAL);
+    for (p, n) in positions.iter().zip(&normals) {
+        assert!(
+            n.dot(Vec3::new(p.x, 0.0, p.z)) > 0.0,
+            "normal {n} points inward"
+        );
+    }
+
+    // Triangles wind counter-clockwise when seen from outside.
+    let indices: Vec<usize> = mesh.indices().unwrap().iter().collect();
+    for tri in indices.chunks(3) {
+        let (a, b, c) = (positions[tri[0]], positions[tri[1]], positions[tri[2]]);
+        let face = (b - a).cross(c - a);
+        let centroid = (a + b + c) / 3.0;
+        assert!(face.dot(Vec3::new(centroid.x, 0.0, centroid.z)) > 0.0);
+    }
+}
+
+#[test]
+fn gentle_corners_are_smoothed() {
+    let s = polyline(&[Vec3::ZERO, Vec3::Y], &[0, 0]);
+    let circle: Vec<Vec2> = (0..32)
+        .map(|i| Vec2::from_angle(i as f32 / 32.0 * std::f32::consts::TAU))
+        .collect();
+    let meshes = LSystemMeshBuilder::new()
+        .with_cross_section(CrossSection::Custom {
+            points: circle,
+            crease_angle: 0.5,
+        })
+        .build(&s);
+    let mesh = &meshes[&0];
+    // No splits: 33 vertices per ring, with radial normals.
+    assert_eq!(mesh.count_vertices(), 66);
+    let positions = float3(mesh, Mesh::ATTRIBUTE_POSITION);
+    let normals = float3(mesh, Mesh::ATTRIBUTE_NORMAL);
+    for (p, n) in positions.iter().zip(&normals) {
+        let radial = Vec3::new(p.x, 0.0, p.z).normalize();
+        assert!(n.dot(radial) > 0.999);
+    }
+}
+
+#[test]
+fn star_uses_both_radii() {
+    let s = polyline(&[Vec3::ZERO, Vec3::Y], &[0, 0]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_cross_section(CrossSection::Star {
+            points: 5,
+            inner_radius: 0.5,
+        })
+        .build(&s);
+    let positions = float3(&meshes[&0], Mesh::ATTRIBUTE_POSITION);
+    let radii: Vec<f32> = positions
+        .iter()
+        .map(|p| Vec2::new(p.x, p.z).length())
+        .collect();
+    assert!(radii.iter().any(|r| (r - 0.1).abs() < 1e-5));
+    assert!(radii.iter().any(|r| (r - 0.05).abs() < 1e-5));
+    assert_eq!(meshes[&0].count_vertices(), 2 * 20);
+}
+
+#[test]
+fn cross_section_is_selected_per_material() {
+    let s = polyline(&[Vec3::ZERO, Vec3::Y, Vec3::Y * 2.0], &[0, 1, 1]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_material_cross_section(1, CrossSection::Polygon { sides: 3 })
+        .build(&s);
+    // Material 0 keeps the default circle at resolution 8.
+    assert_eq!(meshes[&0].count_vertices(), 18);
+    assert_eq!(meshes[&1].count_vertices(), 12);
+}
+
+#[test]
+fn degenerate_custom_outline_falls_back_to_circle() {
+    let s = polyline(&[Vec3::ZERO, Vec3::Y], &[0, 0]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_cross_section(CrossSection::Custom {
+            points: vec![Vec2::ZERO, Vec2::X, Vec2::X * 2.0],
+            crease_angle: 0.0,
+        })
+        .build(&s);
+    assert_eq!(meshes[&0].count_vertices(), 18);
+}
+
+#[test]
+fn caps_close_non_circular_profiles() {
+    let s = polyline(&[Vec3::ZERO, Vec3::Y, Vec3::Y * 2.0], &[0, 0, 0]);
+    let star = CrossSection::Star {
+        points: 6,
+        inner_radius: 0.6,
+    };
+    for cap in [
+        CapStyle::Flat,
+        CapStyle::Dome,
+        CapStyle::Point { length: 2.0 },
+    ] {
+        for profile in [CrossSection::Polygon { sides: 5 }, star.clone()] {
+            let meshes = LSystemMeshBuilder::new()
+                .with_cross_section(profile.clone())
+                .with_caps(cap)
+                .build(&s);
+            assert_watertight(&meshes[&0]);
+        }
+    }
+}
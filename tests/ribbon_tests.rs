@@ -0,0 +1,138 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::{CapStyle, CardStyle, LSystemMeshBuilder, RibbonFacing};
+use common::{colors, normals, point, positions, strand, triangles, uvs};
+use symbios_turtle_3d::{Skeleton, SkeletonPoint};
+
+/// A green point with its own turtle orientation.
+fn blade_point(position: Vec3, rotation: Quat, material_id: u8) -> SkeletonPoint {
+    SkeletonPoint {
+        rotation,
+        color: Vec4::new(0.2, 0.6, 0.1, 1.0),
+        ..point(position, 0.25, material_id)
+    }
+}
+
+/// A blade bending to the right in the XY plane, as a 2D turtle draws it.
+fn blade() -> Skeleton {
+    let yaw = Quat::from_rotation_z(-0.3);
+    strand(&[
+        blade_point(Vec3::ZERO, Quat::IDENTITY, 0),
+        blade_point(Vec3::Y, Quat::IDENTITY, 0),
+        blade_point(Vec3::Y + yaw * Vec3::Y, yaw, 0),
+    ])
+}
+
+fn triangle_normals(mesh: &Mesh) -> Vec<Vec3> {
+    let positions = positions(mesh);
+    triangles(mesh)
+        .iter()
+        .map(|t| {
+            (positions[t[1]] - positions[t[0]])
+                .cross(positions[t[2]] - positions[t[0]])
+                .normalize()
+        })
+        .collect()
+}
+
+#[test]
+fn turtle_ribbons_are_flat_strips_facing_z() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_ribbons(Some(RibbonFacing::Turtle))
+        .build(&blade());
+    let mesh = &meshes[&0];
+
+    // Two vertices per point, two triangles per segment.
+    assert_eq!(mesh.count_vertices(), 6);
+    assert_eq!(mesh.indices().unwrap().len(), 12);
+
+    let positions = positions(mesh);
+    assert!(positions.iter().all(|p| p.z.abs() < 1e-5));
+    assert!(normals(mesh).iter().all(|n| n.abs_diff_eq(Vec3::Z, 1e-5)));
+    assert!(
+        triangle_normals(mesh)
+            .iter()
+            .all(|n| n.abs_diff_eq(Vec3::Z, 1e-4))
+    );
+
+    // The base strip spans the diameter, with U running from -X to +X.
+    let uv = uvs(mesh);
+    for (p, uv) in positions.iter().zip(&uv).take(2) {
+        assert!((p.x.abs() - 0.25).abs() < 1e-5);
+        assert_eq!(uv.x, if p.x < 0.0 { 0.0 } else { 1.0 });
+        assert_eq!(uv.y, 0.0);
+    }
+    // V advances by arc length over the strip width.
+    assert!((uv[2].y - 1.0 / 0.5).abs() < 1e-4);
+}
+
+#[test]
+fn direction_facing_turns_strips_towards_it() {
+    let s = strand(&[
+        blade_point(Vec3::ZERO, Quat::IDENTITY, 0),
+        blade_point(Vec3::Y * 2.0, Quat::IDENTITY, 0),
+    ]);
+    let facing = Vec3::new(1.0, 0.5, 0.0);
+    let meshes = LSystemMeshBuilder::new()
+        .with_ribbons(Some(RibbonFacing::Direction(facing)))
+        .build(&s);
+    let mesh = &meshes[&0];
+
+    // The vertical part of the direction is dropped: strips face +X.
+    for n in normals(mesh) {
+        assert!(n.abs_diff_eq(Vec3::X, 1e-5), "{n}");
+    }
+    for n in triangle_normals(mesh) {
+        assert!(n.abs_diff_eq(Vec3::X, 1e-4), "{n}");
+    }
+    for p in positions(mesh) {
+        assert!(p.x.abs() < 1e-5 && (p.z.abs() - 0.25).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn ribbons_keep_buckets_colors_and_cards() {
+    let s = strand(&[
+        blade_point(Vec3::ZERO, Quat::IDENTITY, 0),
+        blade_point(Vec3::Y, Quat::IDENTITY, 1),
+        blade_point(Vec3::Y * 2.0, Quat::IDENTITY, 2),
+    ]);
+    let meshes = LSystemMeshBuilder::new()
+        .with_ribbons(Some(RibbonFacing::Turtle))
+        .with_cards(2, CardStyle::Quad { size: 2.0 })
+        .build(&s);
+
+    assert_eq!(meshes[&0].count_vertices(), 4);
+    assert_eq!(meshes[&1].count_vertices(), 4);
+    assert_eq!(meshes[&2].count_vertices(), 4, "card stays a card");
+    assert!(
+        colors(&meshes[&0])
+            .iter()
+            .all(|c| *c == Vec4::new(0.2, 0.6, 0.1, 1.0))
+    );
+}
+
+#[test]
+fn ribbons_ignore_caps_and_none_restores_tubes() {
+    let ribbons = LSystemMeshBuilder::new()
+        .with_caps(CapStyle::Flat)
+        .with_ribbons(Some(RibbonFacing::Turtle))
+        .build(&blade());
+    assert_eq!(ribbons[&0].count_vertices(), 6);
+
+    let tubes = LSystemMeshBuilder::new()
+        .with_ribbons(Some(RibbonFacing::Turtle))
+        .with_ribbons(None)
+        .build(&blade());
+    assert_eq!(
+        tubes[&0].count_vertices(),
+        LSystemMeshBuilder::new().build(&blade())[&0].count_vertices()
+    );
+
+    // A zero direction falls back to the turtle orientation.
+    let fallback = LSystemMeshBuilder::new()
+        .with_ribbons(Some(RibbonFacing::Direction(Vec3::ZERO)))
+        .build(&blade());
+    assert_eq!(positions(&fallback[&0]), positions(&ribbons[&0]));
+}
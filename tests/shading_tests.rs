@@ -0,0 +1,127 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::{CapStyle, CardStyle, LSystemMeshBuilder, Shading};
+use common::{colors, indices, normals, point, positions, uvs};
+use symbios_turtle_3d::{Skeleton, SkeletonPoint};
+
+/// A straight trunk in material 0 that turns red and switches to material 1,
+/// plus a card material 2 at its tip.
+fn tree() -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.3, 0), None);
+    s.push_node(point(Vec3::Y, 0.3, 0));
+    s.push_node(SkeletonPoint {
+        color: Vec4::new(1.0, 0.0, 0.0, 1.0),
+        ..point(Vec3::Y * 2.0, 0.3, 1)
+    });
+    s.push_node(point(Vec3::new(0.5, 3.0, 0.0), 0.3, 1));
+    s.push_node(point(Vec3::new(0.5, 3.5, 0.0), 0.3, 2));
+    s
+}
+
+fn builder() -> LSystemMeshBuilder {
+    LSystemMeshBuilder::new()
+        .with_caps(CapStyle::Dome)
+        .with_cards(2, CardStyle::Crossed { size: 1.0 })
+}
+
+#[test]
+fn flat_shading_unshares_vertices_and_keeps_attributes() {
+    let smooth = builder().build(&tree());
+    let flat = builder().with_shading(Shading::Flat).build(&tree());
+    assert_eq!(
+        flat.keys()
+            .copied()
+            .collect::<std::collections::BTreeSet<_>>(),
+        smooth.keys().copied().collect()
+    );
+
+    for (id, mesh) in &flat {
+        let reference = &smooth[id];
+        let corners = indices(reference);
+        assert_eq!(mesh.count_vertices(), corners.len());
+        assert_eq!(indices(mesh), (0..corners.len()).collect::<Vec<_>>());
+
+        let (mesh_positions, reference_positions) = (positions(mesh), positions(reference));
+        let (reference_colors, reference_uvs) = (colors(reference), uvs(reference));
+        for (corner, &v) in corners.iter().enumerate() {
+            assert_eq!(mesh_positions[corner], reference_positions[v]);
+            assert_eq!(colors(mesh)[corner], reference_colors[v]);
+            assert_eq!(uvs(mesh)[corner], reference_uvs[v]);
+        }
+    }
+}
+
+#[test]
+fn flat_normals_match_their_triangle() {
+    let flat = builder().with_shading(Shading::Flat).build(&tree());
+    for mesh in flat.values() {
+        let positions = positions(mesh);
+        let normals = normals(mesh);
+        for tri in indices(mesh).chunks_exact(3) {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| positions[i]);
+            let face = (b - a).cross(c - a).normalize();
+            for &corner in tri {
+                assert!(normals[corner].abs_diff_eq(face, 1e-4), "{face}");
+            }
+        }
+    }
+}
+
+#[test]
+fn auto_smooth_keeps_shallow_edges_soft() {
+    let mut column = Skeleton::new();
+    column.start_strand(point(Vec3::ZERO, 0.3, 0), None);
+    for i in 1..=3 {
+        column.push_node(point(Vec3::Y * i as f32, 0.3, 0));
+    }
+    let build = |shading| {
+        let meshes = LSystemMeshBuilder::new()
+            .with_shading(shading)
+            .build(&column);
+        let mesh = &meshes[&0];
+        (positions(mesh), normals(mesh))
+    };
+    let auto_smooth = |degrees: f32| Shading::AutoSmooth {
+        angle: degrees.to_radians(),
+    };
+
+    // Faces around an 8-sided tube meet at 45 degrees; along it at 0.
+    let (_, flat) = build(Shading::Flat);
+    let (_, sharp) = build(auto_smooth(30.0));
+    assert!(
+        sharp
+            .iter()
+            .zip(&flat)
+            .all(|(a, b)| a.abs_diff_eq(*b, 1e-5))
+    );
+
+    let (positions, soft) = build(auto_smooth(60.0));
+    // Every corner at a shared spot on the straight trunk, seam included,
+    // ends up with the same radial normal.
+    for (i, (p, n)) in positions.iter().zip(&soft).enumerate() {
+        assert!(n.abs_diff_eq(p.with_y(0.0).normalize(), 1e-4), "{p} {n}");
+        for (q, m) in positions.iter().zip(&soft).skip(i + 1) {
+            if p.distance(*q) < 1e-4 {
+                assert!(n.abs_diff_eq(*m, 1e-4));
+            }
+        }
+    }
+}
+
+#[test]
+fn faceted_builds_keep_maps_and_sanitize_angles() {
+    let nan = builder()
+        .with_shading(Shading::AutoSmooth { angle: f32::NAN })
+        .build(&tree());
+    let flat = builder().with_shading(Shading::Flat).build(&tree());
+    assert_eq!(normals(&nan[&1]), normals(&flat[&1]));
+
+    let mapped = builder().with_shading(Shading::Flat).build_mapped(&tree());
+    for (id, (mesh, map)) in &mapped {
+        let triangles = indices(mesh).len() / 3;
+        assert_eq!(triangles, indices(&flat[id]).len() / 3);
+        assert!((0..triangles).all(|t| map.locate(t).is_some()));
+    }
+}
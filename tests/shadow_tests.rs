@@ -0,0 +1,148 @@
+mod common;
+
+use bevy::camera::visibility::RenderLayers;
+use bevy::light::NotShadowCaster;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_symbios::materials::MaterialPalette;
+use bevy_symbios::shadow::*;
+use bevy_symbios::{CardStyle, LSystemMeshBuilder, StrandSmoothing};
+use common::{point, positions};
+use symbios_turtle_3d::Skeleton;
+
+/// A straight trunk (radius 0.2, material 0), a bark branch (radius 0.1,
+/// material 1), a twig (radius 0.01) and a leaf point (material 2) at its tip.
+fn tree() -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.2, 0), None);
+    for i in 1..5 {
+        s.push_node(point(Vec3::Y * i as f32, 0.2, 0));
+    }
+    s.start_strand(point(Vec3::Y * 2.0, 0.1, 1), Some(0));
+    s.push_node(point(Vec3::new(1.0, 3.0, 0.0), 0.1, 1));
+    s.start_strand(point(Vec3::Y * 3.0, 0.01, 0), Some(0));
+    s.push_node(point(Vec3::new(-1.0, 4.0, 0.0), 0.01, 2));
+    s
+}
+
+fn builder() -> LSystemMeshBuilder {
+    LSystemMeshBuilder::new()
+        .with_resolution(12)
+        .with_smoothing(StrandSmoothing::CatmullRom { subdivisions: 4 })
+        .with_cards(2, CardStyle::Quad { size: 0.5 })
+        .with_hierarchy_attributes(true)
+}
+
+#[test]
+fn proxy_keeps_only_positions_normals_and_indices() {
+    let proxy = builder().build_shadow_proxy(&tree(), &ShadowProxy::default());
+    let ids: Vec<_> = proxy.attributes().map(|(attr, _)| attr.id).collect();
+    assert_eq!(ids.len(), 2);
+    assert!(proxy.attribute(Mesh::ATTRIBUTE_POSITION).is_some());
+    assert!(proxy.attribute(Mesh::ATTRIBUTE_NORMAL).is_some());
+    assert!(proxy.attribute(Mesh::ATTRIBUTE_UV_0).is_none());
+    assert!(proxy.attribute(Mesh::ATTRIBUTE_COLOR).is_none());
+
+    let vertices = proxy.count_vertices();
+    let indices = proxy.indices().unwrap();
+    assert!(indices.iter().all(|i| i < vertices));
+    let full: usize = builder()
+        .build(&tree())
+        .values()
+        .map(Mesh::count_vertices)
+        .sum();
+    assert!(vertices * 4 < full, "{vertices} vs {full}");
+}
+
+#[test]
+fn proxy_drops_twigs_and_cards_but_keeps_every_bark_material() {
+    let settings = ShadowProxy::default()
+        .with_resolution(4)
+        .with_min_radius(0.05)
+        .with_merge_angle(Some(0.01));
+    let proxy = builder().build_shadow_proxy(&tree(), &settings);
+
+    // Trunk merged to one segment plus the branch: two tubes of two rings.
+    assert_eq!(proxy.count_vertices(), 2 * 2 * 5);
+    assert!(
+        positions(&proxy).iter().all(|p| p.x > -0.5 && p.y < 4.5),
+        "nothing of the twig or its leaf"
+    );
+    assert!(positions(&proxy).iter().any(|p| p.x > 0.9));
+
+    // Without culling the twig comes back, its leaf card still does not.
+    let kept = builder().build_shadow_proxy(&tree(), &settings.with_min_radius(0.0));
+    assert_eq!(kept.count_vertices(), 3 * 2 * 5);
+}
+
+#[test]
+fn visual_meshes_match_a_plain_build() {
+    let built = builder().build_with_shadow_proxy(&tree(), &ShadowProxy::default());
+    let plain = builder().build(&tree());
+    assert_eq!(built.meshes.len(), plain.len());
+    for (id, mesh) in &plain {
+        assert_eq!(built.meshes[id].count_vertices(), mesh.count_vertices());
+    }
+    assert_eq!(
+        built.self_shadowing.iter().copied().collect::<Vec<_>>(),
+        [2]
+    );
+    assert_eq!(
+        built.proxy.count_vertices(),
+        builder()
+            .build_shadow_proxy(&tree(), &ShadowProxy::default())
+            .count_vertices()
+    );
+}
+
+#[test]
+fn spawn_marks_visual_meshes_and_hides_the_proxy() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(bevy::asset::AssetPlugin::default())
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>();
+    let handles = {
+        let mut meshes = app.world_mut().resource_mut::<Assets<Mesh>>();
+        builder()
+            .build_with_shadow_proxy(&tree(), &ShadowProxy::default())
+            .into_handles(&mut meshes)
+    };
+    let palette = MaterialPalette {
+        materials: HashMap::new(),
+        primary_material: Handle::default(),
+    };
+
+    let parent = app.world_mut().spawn(Transform::default()).id();
+    let spawned = {
+        let mut commands = app.world_mut().commands();
+        spawn_shadow_proxied(&mut commands, parent, &handles, &palette)
+    };
+    app.world_mut().flush();
+
+    assert_eq!(spawned.len(), handles.meshes.len() + 1);
+    assert_eq!(
+        app.world().get::<Children>(parent).unwrap().len(),
+        spawned.len()
+    );
+    let (proxy, visual) = spawned.split_last().unwrap();
+    assert_eq!(app.world().get::<Mesh3d>(*proxy).unwrap().0, handles.proxy);
+    assert_eq!(
+        app.world().get::<RenderLayers>(*proxy),
+        Some(&RenderLayers::layer(SHADOW_PROXY_LAYER))
+    );
+    assert!(app.world().get::<NotShadowCaster>(*proxy).is_none());
+    assert!(shadow_caster_layers().intersects(&RenderLayers::layer(SHADOW_PROXY_LAYER)));
+    assert!(!RenderLayers::default().intersects(&RenderLayers::layer(SHADOW_PROXY_LAYER)));
+
+    let mut order = Vec::new();
+    for entity in visual {
+        let mesh = &app.world().get::<Mesh3d>(*entity).unwrap().0;
+        let (id, _) = handles.meshes.iter().find(|(_, h)| *h == mesh).unwrap();
+        let casts = app.world().get::<NotShadowCaster>(*entity).is_none();
+        assert_eq!(casts, *id == 2, "only the card mesh casts, material {id}");
+        assert!(app.world().get::<RenderLayers>(*entity).is_none());
+        order.push(*id);
+    }
+    assert!(order.is_sorted(), "visual entities out of order: {order:?}");
+}
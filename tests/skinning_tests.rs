@@ -0,0 +1,233 @@
+mod common;
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::mesh::VertexAttributeValues;
+use bevy::mesh::skinning::{SkinnedMesh, SkinnedMeshInverseBindposes};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_symbios::LSystemMeshBuilder;
+use bevy_symbios::materials::MaterialPalette;
+use bevy_symbios::skinning::*;
+use common::{point, positions};
+use symbios_turtle_3d::Skeleton;
+
+/// Trunk of four unit segments with a branch of length 2 from its middle.
+fn tree() -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.1, 0), None);
+    for i in 1..=4 {
+        s.push_node(point(Vec3::Y * i as f32, 0.1, 0));
+    }
+    s.start_strand(point(Vec3::Y * 2.0, 0.1, 0), Some(0));
+    s.push_node(point(Vec3::new(2.0, 2.0, 0.0), 0.1, 0));
+    s
+}
+
+fn joint_attributes(mesh: &Mesh) -> Vec<([u16; 4], [f32; 4])> {
+    let VertexAttributeValues::Uint16x4(indices) =
+        mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX).unwrap()
+    else {
+        panic!("expected Uint16x4");
+    };
+    let VertexAttributeValues::Float32x4(weights) =
+        mesh.attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT).unwrap()
+    else {
+        panic!("expected Float32x4");
+    };
+    indices
+        .iter()
+        .copied()
+        .zip(weights.iter().copied())
+        .collect()
+}
+
+#[test]
+fn joints_follow_segments_and_strand_parents() {
+    let skinned = LSystemMeshBuilder::new().build_skinned(&tree(), 2).unwrap();
+    let layout: Vec<_> = skinned
+        .joints
+        .iter()
+        .map(|j| (j.parent, j.strand, j.point))
+        .collect();
+    assert_eq!(
+        layout,
+        vec![
+            (None, 0, 0),
+            (Some(0), 0, 2),
+            // The branch starts at the trunk's second joint.
+            (Some(1), 1, 0),
+        ]
+    );
+
+    // Chaining local transforms reproduces each bind pose.
+    let mut globals: Vec<Mat4> = Vec::new();
+    for joint in &skinned.joints {
+        let parent = joint.parent.map_or(Mat4::IDENTITY, |p| globals[p]);
+        let global = parent * joint.transform.to_matrix();
+        assert!((global * joint.inverse_bindpose).abs_diff_eq(Mat4::IDENTITY, 1e-5));
+        globals.push(global);
+    }
+    assert_eq!(skinned.inverse_bindposes().len(), 3);
+}
+
+#[test]
+fn plain_builds_have_no_joint_attributes() {
+    let meshes = LSystemMeshBuilder::new().build(&tree());
+    assert!(meshes[&0].attribute(Mesh::ATTRIBUTE_JOINT_INDEX).is_none());
+}
+
+#[test]
+fn weights_blend_across_joint_boundaries() {
+    let skinned = LSystemMeshBuilder::new().build_skinned(&tree(), 2).unwrap();
+    let mesh = &skinned.meshes[&0];
+    let joints = joint_attributes(mesh);
+    assert_eq!(joints.len(), mesh.count_vertices());
+
+    for (p, (indices, weights)) in positions(mesh).iter().zip(&joints) {
+        assert!((weights.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        assert!(indices.iter().all(|&i| (i as usize) < skinned.joints.len()));
+        let weight_of = |joint: u16| -> f32 {
+            indices
+                .iter()
+                .zip(weights)
+                .filter(|(i, _)| **i == joint)
+                .map(|(_, w)| w)
+                .sum()
+        };
+        if p.x > 0.5 {
+            // Branch vertices belong to the branch's only joint.
+            assert_eq!(weight_of(2), 1.0);
+        } else if p.y < 0.01 || p.y > 3.99 {
+            // Trunk ends sit fully on their own joint.
+            let joint = if p.y < 0.01 { 0 } else { 1 };
+            assert!((weight_of(joint) - 1.0).abs() < 1e-5, "{p}");
+        } else if (p.y - 2.0).abs() < 0.01 && weight_of(2) == 0.0 {
+            // Trunk ring at the joint boundary is shared evenly.
+            assert!((weight_of(0) - 0.5).abs() < 1e-5, "{p}");
+            assert!((weight_of(1) - 0.5).abs() < 1e-5, "{p}");
+        }
+    }
+}
+
+#[test]
+fn zero_segments_per_joint_is_clamped() {
+    let skinned = LSystemMeshBuilder::new().build_skinned(&tree(), 0).unwrap();
+    // Four trunk joints plus one branch joint.
+    assert_eq!(skinned.joints.len(), 5);
+}
+
+#[test]
+fn degenerate_rotations_and_positions_keep_bind_poses_finite() {
+    let mut skeleton = tree();
+    skeleton.strands[0][0].rotation = Quat::from_xyzw(0.0, 0.0, 0.0, 0.0);
+    skeleton.strands[0][2].rotation = Quat::from_xyzw(f32::NAN, 0.0, 0.0, 1.0);
+    skeleton.strands[0][3].position = Vec3::NAN;
+    let skinned = LSystemMeshBuilder::new()
+        .build_skinned(&skeleton, 1)
+        .unwrap();
+
+    for joint in &skinned.joints {
+        assert!(joint.transform.is_finite(), "{joint:?}");
+        assert!(joint.inverse_bindpose.is_finite(), "{joint:?}");
+    }
+    assert_eq!(skinned.joints[0].transform.rotation, Quat::IDENTITY);
+    // The non-finite point's joint sits on the last finite position.
+    assert_eq!(skinned.joints[3].transform.translation, Vec3::ZERO);
+    for (_, weights) in joint_attributes(&skinned.meshes[&0]) {
+        assert!(weights.iter().all(|w| w.is_finite()));
+    }
+}
+
+#[test]
+fn oversized_skins_raise_segments_per_joint_or_fail() {
+    // 600 segments at one joint each would need 600 joints.
+    let mut long = Skeleton::new();
+    long.start_strand(point(Vec3::ZERO, 0.1, 0), None);
+    for i in 1..=600 {
+        long.push_node(point(Vec3::Y * i as f32 * 0.01, 0.1, 0));
+    }
+    let skinned = LSystemMeshBuilder::new().build_skinned(&long, 1).unwrap();
+    assert_eq!(skinned.segments_per_joint, 3, "two per joint is still 300");
+    assert_eq!(skinned.joints.len(), 200);
+    let (indices, _): (Vec<_>, Vec<_>) = joint_attributes(&skinned.meshes[&0]).into_iter().unzip();
+    assert!(indices.iter().flatten().all(|&j| usize::from(j) < 200));
+
+    // Every strand needs a joint of its own.
+    let mut bushy = Skeleton::new();
+    for i in 0..=MAX_JOINTS {
+        bushy.start_strand(point(Vec3::X * i as f32, 0.1, 0), None);
+        bushy.push_node(point(Vec3::new(i as f32, 1.0, 0.0), 0.1, 0));
+    }
+    assert_eq!(
+        LSystemMeshBuilder::new().build_skinned(&bushy, 1).err(),
+        Some(SkinningError::TooManyStrands {
+            strands: MAX_JOINTS + 1,
+            max_joints: MAX_JOINTS,
+        })
+    );
+}
+
+#[test]
+fn spawn_builds_joint_hierarchy_and_skinned_parts() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(bevy::asset::AssetPlugin::default())
+        .init_asset::<Mesh>()
+        .init_asset::<SkinnedMeshInverseBindposes>()
+        .init_asset::<StandardMaterial>();
+    let primary = app
+        .world_mut()
+        .resource_mut::<Assets<StandardMaterial>>()
+        .add(StandardMaterial::default());
+    app.insert_resource(MaterialPalette {
+        materials: HashMap::new(),
+        primary_material: primary,
+    });
+    let root = app.world_mut().spawn(Transform::default()).id();
+
+    let skinned = LSystemMeshBuilder::new().build_skinned(&tree(), 2).unwrap();
+    let spawned = app
+        .world_mut()
+        .run_system_once(
+            move |mut commands: Commands,
+                  mut meshes: ResMut<Assets<Mesh>>,
+                  mut bindposes: ResMut<Assets<SkinnedMeshInverseBindposes>>,
+                  palette: Res<MaterialPalette>| {
+                let skinned = LSystemMeshBuilder::new().build_skinned(&tree(), 2).unwrap();
+                spawn_skinned(
+                    &mut commands,
+                    root,
+                    skinned,
+                    &mut meshes,
+                    &mut bindposes,
+                    &palette,
+                )
+            },
+        )
+        .unwrap();
+
+    let world = app.world();
+    assert_eq!(spawned.joints.len(), skinned.joints.len());
+    for (entity, joint) in spawned.joints.iter().zip(&skinned.joints) {
+        let expected_parent = joint.parent.map_or(root, |p| spawned.joints[p]);
+        assert_eq!(
+            world.get::<ChildOf>(*entity).unwrap().parent(),
+            expected_parent
+        );
+        assert_eq!(
+            *world.get::<LSystemJoint>(*entity).unwrap(),
+            LSystemJoint {
+                strand: joint.strand,
+                point: joint.point,
+            }
+        );
+    }
+    assert_eq!(spawned.parts.len(), 1);
+    let skin = world.get::<SkinnedMesh>(spawned.parts[0]).unwrap();
+    assert_eq!(skin.joints, spawned.joints);
+    let bindposes = world
+        .resource::<Assets<SkinnedMeshInverseBindposes>>()
+        .get(&skin.inverse_bindposes)
+        .unwrap();
+    assert_eq!(bindposes.len(), skinned.joints.len());
+}
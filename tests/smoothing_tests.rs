@@ -0,0 +1,103 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::{LSystemMeshBuilder, StrandSmoothing};
+use common::{point, positions};
+use symbios_turtle_3d::Skeleton;
+
+/// An L-shaped strand: up one unit, then across one unit.
+fn elbow(material_ids: [u8; 3]) -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.2, material_ids[0]), None);
+    s.push_node(point(Vec3::Y, 0.1, material_ids[1]));
+    s.push_node(point(Vec3::new(1.0, 1.0, 0.0), 0.1, material_ids[2]));
+    s
+}
+
+fn ring_centers(mesh: &Mesh, res: usize) -> Vec<Vec3> {
+    positions(mesh)
+        .chunks(res + 1)
+        .map(|ring| ring[..res].iter().sum::<Vec3>() / res as f32)
+        .collect()
+}
+
+#[test]
+fn smoothing_is_off_by_default() {
+    let meshes = LSystemMeshBuilder::new().build(&elbow([0, 0, 0]));
+    assert_eq!(meshes[&0].count_vertices(), 3 * 9);
+}
+
+#[test]
+fn catmull_rom_interpolates_original_points() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_smoothing(StrandSmoothing::CatmullRom { subdivisions: 3 })
+        .build(&elbow([0, 0, 0]));
+    let mesh = &meshes[&0];
+    // 2 segments * 4 steps + 1 = 9 shared rings.
+    assert_eq!(mesh.count_vertices(), 9 * 9);
+
+    let centers = ring_centers(mesh, 8);
+    assert!(centers[0].abs_diff_eq(Vec3::ZERO, 1e-4));
+    assert!(centers[4].abs_diff_eq(Vec3::Y, 1e-4));
+    assert!(centers[8].abs_diff_eq(Vec3::new(1.0, 1.0, 0.0), 1e-4));
+    // Inserted points bow outwards instead of following the straight chord.
+    assert!(centers[6].y > 1.0);
+}
+
+#[test]
+fn catmull_rom_interpolates_radius_without_overshoot() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_smoothing(StrandSmoothing::CatmullRom { subdivisions: 3 })
+        .build(&elbow([0, 0, 0]));
+    let positions = positions(&meshes[&0]);
+    let centers = ring_centers(&meshes[&0], 8);
+    let radius_of = |ring: usize| positions[ring * 9].distance(centers[ring]);
+    assert!((radius_of(0) - 0.2).abs() < 1e-4);
+    assert!((radius_of(2) - 0.15).abs() < 1e-4);
+    assert!((radius_of(4) - 0.1).abs() < 1e-4);
+}
+
+#[test]
+fn bezier_cuts_the_corner_but_keeps_endpoints() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_smoothing(StrandSmoothing::Bezier { subdivisions: 3 })
+        .build(&elbow([0, 0, 0]));
+    let centers = ring_centers(&meshes[&0], 8);
+    assert!(centers[0].abs_diff_eq(Vec3::ZERO, 1e-4));
+    assert!(
+        centers
+            .last()
+            .unwrap()
+            .abs_diff_eq(Vec3::new(1.0, 1.0, 0.0), 1e-4)
+    );
+    assert!(
+        centers.iter().all(|c| !c.abs_diff_eq(Vec3::Y, 1e-3)),
+        "the corner point is only a control point"
+    );
+}
+
+#[test]
+fn smoothing_preserves_material_boundaries() {
+    for smoothing in [
+        StrandSmoothing::CatmullRom { subdivisions: 3 },
+        StrandSmoothing::Bezier { subdivisions: 3 },
+    ] {
+        let meshes = LSystemMeshBuilder::new()
+            .with_smoothing(smoothing)
+            .build(&elbow([0, 1, 1]));
+        assert_eq!(meshes.len(), 2, "{smoothing:?}");
+        let first = ring_centers(&meshes[&0], 8);
+        let second = ring_centers(&meshes[&1], 8);
+        // Material 0 stays on the vertical segment, material 1 on the horizontal one.
+        assert!(first.iter().all(|c| c.x < 0.5), "{smoothing:?}");
+        assert!(second.iter().all(|c| c.y > 0.5), "{smoothing:?}");
+    }
+}
+
+#[test]
+fn zero_subdivisions_disable_smoothing() {
+    let meshes = LSystemMeshBuilder::new()
+        .with_smoothing(StrandSmoothing::CatmullRom { subdivisions: 0 })
+        .build(&elbow([0, 0, 0]));
+    assert_eq!(meshes[&0].count_vertices(), 3 * 9);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_symbios::{JunctionStyle, LSystemMeshBuilder, StrandCache};
+use common::{point, positions};
+use symbios_turtle_3d::Skeleton;
+
+/// A trunk with `branches` side branches, each one unit higher than the last.
+fn tree(branches: usize) -> Skeleton {
+    let mut s = Skeleton::new();
+    s.start_strand(point(Vec3::ZERO, 0.1, 0), None);
+    for i in 1..=4 {
+        s.push_node(point(Vec3::Y * i as f32, 0.1, 0));
+    }
+    for b in 0..branches {
+        let base = Vec3::Y * (b + 1) as f32;
+        s.start_strand(point(base, 0.1, 0), Some(0));
+        s.push_node(point(base + Vec3::X, 0.1, 1));
+    }
+    s
+}
+
+fn assert_same_meshes(builder: LSystemMeshBuilder, skeleton: &Skeleton, cache: &mut StrandCache) {
+    let full = builder.clone().build(skeleton);
+    let incremental = builder.build_incremental(skeleton, cache);
+    assert_eq!(full.len(), incremental.len());
+    for (id, mesh) in &full {
+        let other = &incremental[id];
+        assert_eq!(positions(mesh), positions(other), "material {id}");
+        assert_eq!(
+            mesh.indices().unwrap().iter().collect::<Vec<_>>(),
+            other.indices().unwrap().iter().collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn incremental_build_matches_full_build() {
+    let mut cache = StrandCache::new();
+    let builder = LSystemMeshBuilder::new()
+        .with_junctions(JunctionStyle::Blend { flare: 0.5 })
+        .with_hierarchy_attributes(true);
+    assert_same_meshes(builder.clone(), &tree(2), &mut cache);
+    // Second pass is served entirely from the cache.
+    assert_same_meshes(builder, &tree(2), &mut cache);
+    assert_eq!(cache.misses(), 3);
+    assert_eq!(cache.hits(), 3);
+}
+
+#[test]
+fn only_new_strands_are_meshed() {
+    let mut cache = StrandCache::new();
+    let builder = LSystemMeshBuilder::new();
+    builder.clone().build_incremental(&tree(1), &mut cache);
+    cache.reset_stats();
+
+    builder.build_incremental(&tree(3), &mut cache);
+    assert_eq!(cache.hits(), 2, "trunk and first branch are reused");
+    assert_eq!(cache.misses(), 2);
+}
+
+#[test]
+fn modified_parent_invalidates_junction_children() {
+    let mut cache = StrandCache::new();
+    let builder = LSystemMeshBuilder::new().with_junctions(JunctionStyle::Weld);
+    builder.clone().build_incremental(&tree(2), &mut cache);
+    cache.reset_stats();
+
+    let mut moved = tree(2);
+    moved.strands[0][4].radius = 0.2;
+    assert_same_meshes(builder, &moved, &mut cache);
+    assert_eq!(cache.misses(), 3, "welded branches depend on the trunk");
+
+    // Without junctions the branches do not read the trunk.
+    let mut cache = StrandCache::new();
+    LSystemMeshBuilder::new().build_incremental(&tree(2), &mut cache);
+    cache.reset_stats();
+    LSystemMeshBuilder::new().build_incremental(&moved, &mut cache);
+    assert_eq!((cache.hits(), cache.misses()), (2, 1));
+}
+
+#[test]
+fn options_are_part_of_the_key() {
+    let mut cache = StrandCache::new();
+    LSystemMeshBuilder::new().build_incremental(&tree(1), &mut cache);
+    LSystemMeshBuilder::new()
+        .with_resolution(12)
+        .build_incremental(&tree(1), &mut cache);
+    assert_eq!(cache.hits(), 0);
+    assert_eq!(cache.len(), 4);
+}
+
+#[test]
+fn evict_stale_keeps_latest_strands() {
+    let mut cache = StrandCache::new();
+    LSystemMeshBuilder::new().build_incremental(&tree(3), &mut cache);
+    assert_eq!(cache.len(), 4);
+    LSystemMeshBuilder::new().build_incremental(&tree(1), &mut cache);
+    assert_eq!(cache.evict_stale(), 2);
+    assert_eq!(cache.len(), 2);
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
@@ -0,0 +1,184 @@
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_symbios::materials::*;
+use bevy_symbios::wind::*;
+
+fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(bevy::asset::AssetPlugin::default())
+        .init_asset::<Image>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<WindMaterial>()
+        .init_resource::<Wind>()
+        .init_resource::<WindPalette>()
+        .init_resource::<MaterialSettingsMap>()
+        .add_systems(Update, sync_wind_materials);
+
+    let mut palette = HashMap::new();
+    let primary = {
+        let mut mats = app.world_mut().resource_mut::<Assets<StandardMaterial>>();
+        for id in 0..3u16 {
+            palette.insert(
+                id,
+                mats.add(StandardMaterial {
+                    perceptual_roughness: 0.1 * (id + 1) as f32,
+                    ..default()
+                }),
+            );
+        }
+        palette[&0].clone()
+    };
+    app.insert_resource(MaterialPalette {
+        materials: palette,
+        primary_material: primary,
+    });
+    app
+}
+
+fn wind_material(app: &App, id: u16) -> Option<WindMaterial> {
+    let handle = app.world().resource::<WindPalette>().materials.get(&id)?;
+    app.world()
+        .resource::<Assets<WindMaterial>>()
+        .get(handle)
+        .cloned()
+}
+
+#[test]
+fn only_opted_in_slots_get_wind_materials() {
+    let mut app = test_app();
+    app.world_mut()
+        .resource_mut::<MaterialSettingsMap>()
+        .settings
+        .get_mut(&1)
+        .unwrap()
+        .wind = Some(WindResponse::default());
+    app.update();
+
+    let palette = app.world().resource::<WindPalette>();
+    assert_eq!(palette.materials.len(), 1);
+    let material = wind_material(&app, 1).expect("slot 1 opted in");
+    assert!((material.base.perceptual_roughness - 0.2).abs() < 1e-6);
+}
+
+#[test]
+fn uniform_combines_wind_and_response() {
+    let mut app = test_app();
+    app.insert_resource(Wind {
+        direction: Vec3::new(3.0, 0.0, 4.0),
+        strength: 2.0,
+        gust_frequency: 0.5,
+    });
+    let response = WindResponse {
+        flexibility: 0.1,
+        stiffness: 5.0,
+        flutter: 0.04,
+    };
+    app.world_mut()
+        .resource_mut::<MaterialSettingsMap>()
+        .settings
+        .get_mut(&0)
+        .unwrap()
+        .wind = Some(response);
+    app.update();
+
+    let uniform = wind_material(&app, 0).unwrap().extension.wind;
+    assert!(
+        uniform
+            .direction
+            .abs_diff_eq(Vec3::new(0.6, 0.0, 0.8), 1e-6)
+    );
+    assert_eq!(uniform.strength, 2.0);
+    assert_eq!(uniform.gust_frequency, 0.5);
+    assert_eq!(uniform.flexibility, 0.1);
+    assert_eq!(uniform.stiffness, 5.0);
+    assert_eq!(uniform.flutter, 0.04);
+}
+
+#[test]
+fn base_follows_palette_and_opt_out_removes_slot() {
+    let mut app = test_app();
+    app.world_mut()
+        .resource_mut::<MaterialSettingsMap>()
+        .settings
+        .get_mut(&2)
+        .unwrap()
+        .wind = Some(WindResponse::default());
+    app.update();
+
+    // Editing the palette material propagates to the wind copy.
+    let handle = app.world().resource::<MaterialPalette>().materials[&2].clone();
+    app.world_mut()
+        .resource_mut::<Assets<StandardMaterial>>()
+        .get_mut(&handle)
+        .unwrap()
+        .metallic = 0.7;
+    app.world_mut()
+        .resource_mut::<MaterialPalette>()
+        .set_changed();
+    app.update();
+    assert_eq!(wind_material(&app, 2).unwrap().base.metallic, 0.7);
+
+    app.world_mut()
+        .resource_mut::<MaterialSettingsMap>()
+        .settings
+        .get_mut(&2)
+        .unwrap()
+        .wind = None;
+    app.update();
+    assert!(app.world().resource::<WindPalette>().materials.is_empty());
+}
+
+#[test]
+fn idle_frames_leave_wind_materials_untouched() {
+    let mut app = test_app();
+    app.world_mut()
+        .resource_mut::<MaterialSettingsMap>()
+        .settings
+        .get_mut(&0)
+        .unwrap()
+        .wind = Some(WindResponse::default());
+    app.update();
+
+    let modified = |app: &mut App, update: &dyn Fn(&mut App)| {
+        let mut cursor = app
+            .world()
+            .resource::<Messages<AssetEvent<WindMaterial>>>()
+            .get_cursor_current();
+        update(app);
+        let messages = app.world().resource::<Messages<AssetEvent<WindMaterial>>>();
+        cursor
+            .read(messages)
+            .filter(|event| matches!(event, AssetEvent::Modified { .. }))
+            .count()
+    };
+
+    // The gust clock runs in the shaders, so time passing changes nothing.
+    assert_eq!(modified(&mut app, &|app| app.update()), 0);
+    assert_eq!(modified(&mut app, &|app| app.update()), 0);
+
+    let changed = modified(&mut app, &|app| {
+        app.world_mut().resource_mut::<Wind>().strength = 3.0;
+        app.update();
+    });
+    assert_eq!(changed, 1);
+    assert_eq!(wind_material(&app, 0).unwrap().extension.wind.strength, 3.0);
+}
+
+#[test]
+fn wind_setting_round_trips_and_defaults_to_none() {
+    let settings = MaterialSettings {
+        wind: Some(WindResponse {
+            flexibility: 0.05,
+            ..default()
+        }),
+        ..default()
+    };
+    let json = serde_json::to_string(&settings).unwrap();
+    let back: MaterialSettings = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.wind, settings.wind);
+
+    // Palettes written before wind existed still parse.
+    let old: MaterialSettings = serde_json::from_str(r#"{"roughness":0.3}"#).unwrap();
+    assert!(old.wind.is_none());
+}